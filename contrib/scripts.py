@@ -3,15 +3,20 @@
 
 from __future__ import annotations
 
+import os
 import re
 import sys
 from inspect import getmembers
 from inspect import getsource
 from inspect import isclass
 from pathlib import Path
+from tempfile import TemporaryDirectory
 from textwrap import dedent
+from timeit import repeat
+from timeit import timeit
 
 from click import group
+from click import option
 from click import secho
 
 BASE_PATH = Path(__file__).parent.parent
@@ -134,5 +139,109 @@ def import_booking_tests() -> None:
         sys.exit(1)
 
 
+@cli.command()
+@option("--count", default=100_000, help="Number of transactions to convert.")
+def benchmark_amounts(count: int) -> None:
+    """Benchmark the conversion of many amounts to Beancount."""
+    from uromyces import convert_entries  # noqa: PLC0415
+    from uromyces import load_string  # noqa: PLC0415
+
+    currencies = ["USD", "EUR", "CHF", "GBP"]
+    transactions = "".join(
+        f"2020-01-01 *\n  Assets:Cash  {i} {currencies[i % len(currencies)]}\n"
+        "  Equity:Opening\n"
+        for i in range(count)
+    )
+    entries = load_string(transactions, "/benchmark.beancount").entries
+
+    def convert() -> None:
+        convert_entries(entries)
+
+    secho(f"{count} transactions: {timeit(convert, number=10) / 10:.4f}s")
+
+
+@cli.command()
+@option("--count", default=100_000, help="Number of transactions.")
+def benchmark_filter_entries(count: int) -> None:
+    """Benchmark filtering entries in Rust against a loop in Python."""
+    from uromyces import filter_entries  # noqa: PLC0415
+    from uromyces import load_string  # noqa: PLC0415
+    from uromyces import Transaction  # noqa: PLC0415
+
+    payees = ["Shop", "Restaurant", "Landlord", "Employer"]
+    transactions = "".join(
+        f'2020-01-01 * "{payees[i % len(payees)]}" "Transaction {i}"\n'
+        "  Assets:Cash  -1 USD\n  Expenses:Food\n"
+        for i in range(count)
+    )
+    entries = load_string(transactions, "/benchmark.beancount").entries
+    payee = re.compile("^Shop")
+    narration = re.compile("1$")
+
+    def rust() -> None:
+        filter_entries(entries, payee_regex="^Shop", narration_regex="1$")
+
+    def python() -> None:
+        _ = [
+            e
+            for e in entries
+            if isinstance(e, Transaction)
+            and e.payee is not None
+            and payee.search(e.payee)
+            and narration.search(e.narration)
+        ]
+
+    secho(f"{count} transactions:")
+    secho(f"  Rust:   {timeit(rust, number=10) / 10:.4f}s")
+    secho(f"  Python: {timeit(python, number=10) / 10:.4f}s")
+
+
+@cli.command()
+@option("--files", default=30, help="Number of included files.")
+@option("--count", default=2_000, help="Number of transactions per file.")
+@option("--threads", default=None, type=int, help="Number of threads.")
+@option("--directory", default=None, help="Write the ledger to this path.")
+def benchmark_includes(
+    files: int, count: int, threads: int | None, directory: str | None
+) -> None:
+    """Benchmark loading a ledger that is split into many included files.
+
+    The included files are parsed in parallel, run this with different
+    values for `--threads` to compare.
+    """
+    if threads is not None:
+        os.environ["RAYON_NUM_THREADS"] = str(threads)
+    from uromyces._uromyces import load_file  # noqa: PLC0415
+
+    accounts = ["Expenses:Food", "Expenses:Rent", "Income:Salary"]
+    with TemporaryDirectory() as tmp_dir:
+        path = Path(directory or tmp_dir).resolve()
+        path.mkdir(parents=True, exist_ok=True)
+        main = path / "main.beancount"
+        opens = "".join(
+            f"2000-01-01 open {account}\n"
+            for account in ["Assets:Cash", *accounts]
+        )
+        includes = "".join(
+            f'include "{i:02}.beancount"\n' for i in range(files)
+        )
+        main.write_text(opens + includes)
+        for i in range(files):
+            (path / f"{i:02}.beancount").write_text(
+                "".join(
+                    f'2020-01-01 * "Payee {j}" "File {i}, transaction {j}"\n'
+                    f"  {accounts[j % len(accounts)]}  {j}.{i:02} USD\n"
+                    "  Assets:Cash\n"
+                    for j in range(count)
+                )
+            )
+
+        def load() -> None:
+            load_file(str(main))
+
+        secho(f"{files} files, {count} transactions each:")
+        secho(f"  {min(repeat(load, number=1, repeat=5)):.4f}s (best of 5)")
+
+
 if __name__ == "__main__":
     cli()
@@ -7,22 +7,32 @@ from typing import TYPE_CHECKING
 from uromyces import _uromyces
 from uromyces._plugins import run
 from uromyces._types import Directive
+from uromyces._uromyces import account_ancestors
+from uromyces._uromyces import account_is_child_of
 from uromyces._uromyces import Amount
 from uromyces._uromyces import Balance
 from uromyces._uromyces import Close
 from uromyces._uromyces import Commodity
+from uromyces._uromyces import compute_residual
+from uromyces._uromyces import convert_entries
 from uromyces._uromyces import Cost
 from uromyces._uromyces import CostSpec
 from uromyces._uromyces import Custom
 from uromyces._uromyces import CustomValue
+from uromyces._uromyces import diff_entries
 from uromyces._uromyces import Document
+from uromyces._uromyces import entry_from_json
 from uromyces._uromyces import EntryMeta
 from uromyces._uromyces import Event
+from uromyces._uromyces import filter_entries
+from uromyces._uromyces import is_balanced
 from uromyces._uromyces import Ledger
 from uromyces._uromyces import Note
 from uromyces._uromyces import Open
 from uromyces._uromyces import Pad
+from uromyces._uromyces import parse_only
 from uromyces._uromyces import Posting
+from uromyces._uromyces import posting_weight
 from uromyces._uromyces import PostingMeta
 from uromyces._uromyces import Price
 from uromyces._uromyces import Query
@@ -65,10 +75,19 @@ __all__ = [  # noqa: RUF022
     "RawPosting",
     "RawTransaction",
     # Functions
+    "account_ancestors",
+    "account_is_child_of",
+    "compute_residual",
     "convert_entries",
     "convert_options",
+    "diff_entries",
+    "entry_from_json",
+    "filter_entries",
+    "is_balanced",
     "load_file",
     "load_string",
+    "parse_only",
+    "posting_weight",
     "summarize_clamp",
 ]
 
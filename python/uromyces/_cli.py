@@ -80,7 +80,7 @@ def compare(
     This loads the given file with both uromyces and Beancount and compares
     the output. Differences will be printed out.
 
-    Some metadata fields (__tolerances__ and __automatic__) are ignored.
+    The __tolerances__ metadata field is ignored.
     """
     # Lazily import here to improve startup performance - in particular
     # the Fava one is slow.
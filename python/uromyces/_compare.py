@@ -57,13 +57,11 @@ def isclose_cost(
 
 
 def clean_metadata(entry: data.Directive) -> None:
-    """Remove some metadata from entry and postings.
+    """Remove some metadata from entry.
 
-    These are not set by uromyces (yet?), so remove them.
+    This is not set by uromyces (yet?), so remove it.
     """
     entry.meta.pop("__tolerances__", None)
-    for posting in getattr(entry, "postings", []):
-        posting.meta.pop("__automatic__", None)
 
 
 def compare_postings(
@@ -13,6 +13,7 @@ from uromyces._uromyces import Balance
 from uromyces._uromyces import Booking
 from uromyces._uromyces import Close
 from uromyces._uromyces import Commodity
+from uromyces._uromyces import convert_entries
 from uromyces._uromyces import Custom
 from uromyces._uromyces import CustomValue
 from uromyces._uromyces import Document
@@ -36,7 +37,7 @@ if TYPE_CHECKING:
 
 def beancount_entries(entries: Sequence[Directive]) -> list[data.Directive]:
     """Convert entries of the ledger to Beancount entries."""
-    return [entry._convert() for entry in entries]  # noqa: SLF001
+    return convert_entries(entries)
 
 
 def uromyces_entries(
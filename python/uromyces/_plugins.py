@@ -93,7 +93,7 @@ def run(ledger: Ledger) -> None:
     ):
         for plugin in plugins:
             if entries is None:
-                if ledger.run_plugin(plugin.name):
+                if ledger.run_plugin(plugin.name, plugin.config):
                     # Rust implementation of the plugin
                     continue
                 entries = ledger.entries
@@ -13,6 +13,7 @@ from uromyces._uromyces import Open
 from uromyces._uromyces import Pad
 from uromyces._uromyces import Price
 from uromyces._uromyces import Query
+from uromyces._uromyces import RawTransaction
 from uromyces._uromyces import Transaction
 
 if TYPE_CHECKING:
@@ -32,3 +33,18 @@ Directive: TypeAlias = (
     | Query
     | Transaction
 )
+
+RawDirective: TypeAlias = (
+    Balance
+    | Close
+    | Commodity
+    | Custom
+    | Document
+    | Event
+    | Note
+    | Open
+    | Pad
+    | Price
+    | Query
+    | RawTransaction
+)
@@ -1,4 +1,6 @@
 import datetime
+from collections.abc import Callable
+from collections.abc import Hashable
 from collections.abc import ItemsView
 from collections.abc import Iterator
 from collections.abc import KeysView
@@ -20,13 +22,16 @@ from fava.beans.abc import Meta
 from fava.beans.abc import MetaValue
 
 from uromyces._types import Directive
+from uromyces._types import RawDirective
 
 class UroError:
     filename: str | None
     lineno: int | None
     source: Meta
     message: str
+    kind: str | None
     entry: Directive | None
+    span: tuple[int, int, int, int] | None
 
 class Booking(Enum):
     STRICT = "STRICT"
@@ -41,6 +46,7 @@ class _Directive:
     date: datetime.date
     links: frozenset[str]
     tags: frozenset[str]
+    kind: str
 
     def _convert(self) -> data.Directive: ...
     def to_json(self) -> str: ...
@@ -55,6 +61,19 @@ class Amount:
         number: Decimal,
         currency: str,
     ) -> Amount: ...
+    @staticmethod
+    def zero(currency: str) -> Amount: ...
+    @staticmethod
+    def from_string(string: str) -> Amount: ...
+    def round(self, ndigits: int) -> Amount: ...
+    def round_for(self, options: UromycesOptions) -> Amount: ...
+    def is_zero(self) -> bool: ...
+    def __add__(self, other: Amount) -> Amount: ...
+    def __sub__(self, other: Amount) -> Amount: ...
+    def __mul__(self, other: Decimal | int) -> Amount: ...
+    def __rmul__(self, other: Decimal | int) -> Amount: ...
+    def __neg__(self) -> Amount: ...
+    def __abs__(self) -> Amount: ...
 
 @final
 class RawAmount:
@@ -63,8 +82,8 @@ class RawAmount:
 
     def __new__(
         cls: type[RawAmount],
-        number: Decimal | None,
-        currency: str | None,
+        number: Decimal | None = None,
+        currency: str | None = None,
     ) -> RawAmount: ...
 
 @final
@@ -81,6 +100,10 @@ class Cost:
         date: datetime.date | None,
         label: str | None,
     ) -> Cost: ...
+    def __lt__(self, other: Cost) -> bool: ...
+    def __le__(self, other: Cost) -> bool: ...
+    def __gt__(self, other: Cost) -> bool: ...
+    def __ge__(self, other: Cost) -> bool: ...
 
 @final
 class CostSpec:
@@ -93,12 +116,12 @@ class CostSpec:
 
     def __new__(
         cls: type[CostSpec],
-        number_per: Decimal | None,
-        number_total: Decimal | None,
-        currency: str | None,
-        date: datetime.date | None,
-        label: str | None,
-        merge: bool,
+        number_per: Decimal | None = None,
+        number_total: Decimal | None = None,
+        currency: str | None = None,
+        date: datetime.date | None = None,
+        label: str | None = None,
+        merge: bool | None = None,
     ) -> CostSpec: ...
 
 class CustomValue:
@@ -134,10 +157,20 @@ class CustomValue:
         dtype: type[datetime.date],
     ) -> CustomValue: ...
 
+@final
+class SourceSpan:
+    start_line: int
+    start_col: int
+    end_line: int
+    end_col: int
+    start_byte: int
+    end_byte: int
+
 @final
 class EntryMeta(Mapping[str, MetaValue]):
     filename: str
     lineno: int
+    span: SourceSpan | None
 
     def __new__(cls: type[EntryMeta], meta: Meta) -> EntryMeta: ...
     def __contains__(self, key: object) -> bool: ...
@@ -366,7 +399,7 @@ class Open(_Directive, abc.Open):
         links: set[str] | frozenset[str] | None = None,
         account: str | None = None,
         currencies: list[str] | None = None,
-        booking: Booking | None = None,
+        booking: Booking | None = ...,
     ) -> Open: ...
 
 @final
@@ -453,6 +486,7 @@ class Posting(abc.Posting):
     price: protocols.Amount | None
     flag: str | None
     meta: Meta | None
+    interpolated: bool
 
     def __new__(
         cls: type[Posting],
@@ -494,6 +528,11 @@ class Transaction(_Directive, abc.Transaction):
         narration: str | None = None,
         postings: list[Posting] | None = None,
     ) -> Transaction: ...
+    def inferred_tolerances(
+        self: Transaction, options: UromycesOptions
+    ) -> Tolerances: ...
+    def residual(self: Transaction) -> list[Amount]: ...
+    def is_balanced(self: Transaction, options: UromycesOptions) -> bool: ...
 
 @final
 class RawPosting:
@@ -518,11 +557,35 @@ class RootAccounts:
     income: str
     expenses: str
 
+    def __init__(
+        self,
+        *,
+        assets: str = "Assets",
+        liabilities: str = "Liabilities",
+        equity: str = "Equity",
+        income: str = "Income",
+        expenses: str = "Expenses",
+    ) -> None: ...
+    def is_balance_sheet_account(self, account: str) -> bool: ...
+    def is_income_statement_account(self, account: str) -> bool: ...
+
 class Precisions:
     has_sign: bool
     max: int
     common: int
 
+class PrecisionStats:
+    has_sign: bool
+    max: int
+    common: int
+    counts: dict[int, int]
+
+class Tolerances:
+    default: Decimal
+    tolerances: dict[str, Decimal]
+
+    def get(self, currency: str) -> Decimal: ...
+
 class UromycesOptions:
     title: str
     root_accounts: RootAccounts
@@ -537,12 +600,50 @@ class UromycesOptions:
     documents: Sequence[str]
     booking_method: Booking
     insert_pythonpath: bool
+    warn_duplicate_includes: bool
+    warn_redundant_balances: bool
+    warn_unused_open_currencies: bool
+    strict_options: bool
+    allow_single_posting_transactions: bool
+    account_rounding: str | None
+    infer_tolerance_from_cost: bool
+    balance_tolerance_min: Decimal
     display_precisions: Mapping[str, Precisions]
 
+    def quantize_for_display(
+        self,
+        number: Decimal,
+        currency: str,
+    ) -> Decimal: ...
+    def format_for_display(self, number: Decimal, currency: str) -> str: ...
+
 class Plugin:
     name: str
     config: str | None
 
+    def config_as_json(self) -> Any: ...
+
+class PriceMap:
+    def price_at(
+        self, base: str, quote: str, date: datetime.date
+    ) -> Decimal | None: ...
+
+class AccountInfo:
+    account: str
+    open_date: datetime.date
+    close_date: datetime.date | None
+    currencies: list[str]
+    booking: Booking | None
+    meta: EntryMeta
+
+class LoadTimings:
+    parse: datetime.timedelta
+    combine: datetime.timedelta
+    book: datetime.timedelta
+    plugins: datetime.timedelta
+    validations: datetime.timedelta
+    total: datetime.timedelta
+
 class Ledger:
     filename: str
     entries: list[Directive]
@@ -550,14 +651,74 @@ class Ledger:
     includes: list[str]
     options: UromycesOptions
     plugins: list[Plugin]
+    timings: LoadTimings
 
     def replace_entries(self: Ledger, entries: list[Directive]) -> None: ...
     def add_error(self: Ledger, error: Any) -> None: ...
+    def insert_entry(self: Ledger, entry: Directive) -> None: ...
     def run_validations(self: Ledger) -> None: ...
-    def run_plugin(self: Ledger, name: str) -> bool: ...
+    def run_plugin(
+        self: Ledger, name: str, config: str | None = None
+    ) -> bool: ...
+    def assert_balances(
+        self: Ledger,
+        assertions: Sequence[tuple[str, datetime.date, protocols.Amount]],
+    ) -> list[UroError | None]: ...
+    def currencies(self: Ledger) -> list[str]: ...
+    def all_tags(self: Ledger) -> list[str]: ...
+    def all_links(self: Ledger) -> list[str]: ...
+    def accounts(self: Ledger) -> list[AccountInfo]: ...
+    def commodity_meta(self: Ledger, currency: str) -> EntryMeta | None: ...
+    def errors_by_file(self: Ledger) -> dict[str | None, list[UroError]]: ...
+    def errors_sorted(self: Ledger) -> list[UroError]: ...
+    def get_prices(self: Ledger) -> PriceMap: ...
+    def display_precisions_stats(
+        self: Ledger,
+    ) -> dict[str, PrecisionStats]: ...
+    def filtered(
+        self: Ledger,
+        begin: datetime.date | None = None,
+        end: datetime.date | None = None,
+    ) -> Ledger: ...
+    def price_at(
+        self: Ledger, base: str, quote: str, date: datetime.date
+    ) -> Decimal | None: ...
+    def group_transactions_by(
+        self: Ledger,
+        key_fn: Callable[[Transaction], Hashable],
+    ) -> dict[Hashable, list[Transaction]]: ...
 
 def load_file(filename: str) -> Ledger: ...
 def load_string(string: str, filename: str) -> Ledger: ...
+def account_is_child_of(account: str, other: str) -> bool: ...
+def account_ancestors(account: str) -> list[str]: ...
+def date_add_days(date: datetime.date, days: int) -> datetime.date: ...
+def date_add_months(date: datetime.date, months: int) -> datetime.date: ...
+def date_start_of_month(date: datetime.date) -> datetime.date: ...
+def date_range(
+    begin: datetime.date, end: datetime.date
+) -> list[datetime.date]: ...
+def parse_only(
+    string: str, filename: str | None = None
+) -> tuple[list[RawDirective], list[UroError]]: ...
+def entry_from_json(s: str) -> Directive: ...
+def filter_entries(
+    entries: Sequence[Directive],
+    payee_regex: str | None = None,
+    narration_regex: str | None = None,
+    account_regex: str | None = None,
+) -> list[Directive]: ...
+def diff_entries(
+    a: Sequence[Directive],
+    b: Sequence[Directive],
+    ignore_positions: bool = False,
+) -> tuple[list[Directive], list[Directive]]: ...
+def convert_entries(entries: Sequence[Directive]) -> list[data.Directive]: ...
+def compute_residual(postings: Sequence[Posting]) -> list[Amount]: ...
+def is_balanced(
+    postings: Sequence[Posting], options: UromycesOptions
+) -> bool: ...
+def posting_weight(posting: Posting) -> Amount: ...
 def summarize_clamp(
     entries: Sequence[Directive],
     begin_date: datetime.date,
@@ -1,4 +1,4 @@
-use crate::types::{Filename, LineNumber, RawPosting};
+use crate::types::{Amount, Filename, LineNumber, RawPosting};
 
 /// An error that occurs during interpolation or booking.
 #[derive(Debug)]
@@ -15,19 +15,47 @@ pub(super) enum BookingErrorKind {
     UnresolvedCostCurrency,
     UnresolvedPriceCurrency,
     MultipleAutoPostings,
+    SinglePosting,
     // Closing of positions
-    InsufficientLots,
+    InsufficientLots(Amount),
     NoMatchesForReduction,
-    UnsupportedAverageBooking,
+    NoLotAtCost(Amount),
     AmbiguousMatches,
     // Interpolation
     TooManyMissingNumbers,
+    /// A second incomplete posting, with the line of the first one.
+    TooManyIncompletePostings(LineNumber),
     MissingAmountNumber,
     MissingCostNumber,
+    MissingUnitsForTotalCost,
     DivisionFailed,
+    // Validation of costs
+    NegativeCost(Amount),
 }
 
 impl BookingErrorKind {
+    /// A machine-readable name for this kind of error.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::UnresolvedUnitsCurrency => "unresolved_units_currency",
+            Self::UnresolvedCostCurrency => "unresolved_cost_currency",
+            Self::UnresolvedPriceCurrency => "unresolved_price_currency",
+            Self::MultipleAutoPostings => "multiple_auto_postings",
+            Self::SinglePosting => "single_posting",
+            Self::InsufficientLots(..) => "insufficient_lots",
+            Self::NoMatchesForReduction => "no_matches_for_reduction",
+            Self::NoLotAtCost(..) => "no_lot_at_cost",
+            Self::AmbiguousMatches => "ambiguous_matches",
+            Self::TooManyMissingNumbers => "too_many_missing_numbers",
+            Self::TooManyIncompletePostings(..) => "too_many_incomplete_postings",
+            Self::MissingAmountNumber => "missing_amount_number",
+            Self::MissingCostNumber => "missing_cost_number",
+            Self::MissingUnitsForTotalCost => "missing_units_for_total_cost",
+            Self::DivisionFailed => "division_failed",
+            Self::NegativeCost(..) => "negative_cost",
+        }
+    }
+
     pub(super) fn with_posting(self, posting: &RawPosting) -> BookingError {
         BookingError {
             filename: posting.meta.filename.clone(),
@@ -43,29 +71,47 @@ impl std::fmt::Display for BookingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         type T = BookingErrorKind;
 
-        match self.kind {
+        match &self.kind {
             T::UnresolvedUnitsCurrency => write!(f, "Unresolved units currency"),
             T::UnresolvedCostCurrency => write!(f, "Unresolved cost currency"),
             T::UnresolvedPriceCurrency => write!(f, "Unresolved price currency"),
             T::MultipleAutoPostings => write!(f, "There can be at most one auto posting"),
-            T::InsufficientLots => write!(f, "Not enough lots in inventory to reduce position"),
+            T::SinglePosting => write!(f, "Transaction has only a single posting"),
+            T::InsufficientLots(shortfall) => write!(
+                f,
+                "Not enough lots in inventory to reduce position (short by {shortfall})"
+            ),
             T::NoMatchesForReduction => {
                 write!(f, "No matching lots in inventory to reduce position")
             }
-            T::UnsupportedAverageBooking => {
-                write!(f, "The AVERAGE booking method is not supported")
+            T::NoLotAtCost(cost) => {
+                write!(
+                    f,
+                    "No lot at a cost of {cost} in inventory to reduce position"
+                )
             }
             T::AmbiguousMatches => write!(f, "Ambiguous matches"),
             T::TooManyMissingNumbers => write!(f, "Too many missing numbers in transaction"),
+            T::TooManyIncompletePostings(first) => write!(
+                f,
+                "Too many missing numbers in transaction (incomplete postings on lines {first} and {})",
+                self.lineno
+            ),
             T::MissingAmountNumber => write!(f, "Amount is missing a number"),
             T::MissingCostNumber => write!(f, "Cost is missing a number"),
+            T::MissingUnitsForTotalCost => {
+                write!(f, "Units number is required for a total cost")
+            }
             T::DivisionFailed => write!(f, "Division failed (e.g. division by zero)"),
+            T::NegativeCost(cost) => write!(f, "Cost is negative: {cost}"),
         }
     }
 }
 
 impl From<BookingError> for crate::errors::UroError {
     fn from(e: BookingError) -> Self {
-        Self::new(e.to_string()).with_position(e.filename, e.lineno)
+        Self::new(e.to_string())
+            .with_kind(e.kind.name())
+            .with_position(e.filename, e.lineno)
     }
 }
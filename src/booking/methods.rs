@@ -1,7 +1,7 @@
 use std::cmp::Reverse;
 
 use crate::inventory::{Inventory, InventoryPosition, InventoryPositionWithCost};
-use crate::types::{Amount, Booking, Cost, Decimal, RawPosting};
+use crate::types::{Amount, Booking, Cost, CostLabel, Currency, Date, Decimal, RawPosting};
 
 use super::errors::{BookingError, BookingErrorKind};
 
@@ -69,6 +69,17 @@ impl Remainder {
         assert!(self.remaining_number.is_sign_positive());
         !self.remaining_number.is_zero()
     }
+    /// The (absolute) number that is still left to reduce.
+    fn remaining(&self) -> Decimal {
+        self.remaining_number
+    }
+    /// The error for a reduction that leaves a remainder, containing the shortfall.
+    fn insufficient_lots(&self, posting_units: &Amount) -> BookingErrorKind {
+        BookingErrorKind::InsufficientLots(Amount::new(
+            self.remaining(),
+            posting_units.currency.clone(),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +103,11 @@ fn test_remainder() {
     assert_eq!(remainder.reduce(&d("5")), d("-2"));
     assert!(!remainder.is_strictly_positive());
 
+    let mut remainder = Remainder::new(d("-5"));
+    assert_eq!(remainder.reduce(&d("3")), d("-3"));
+    assert!(remainder.is_strictly_positive());
+    assert_eq!(remainder.remaining(), d("2"));
+
     let remainder_neg = Remainder::new(d("-1"));
     assert!(remainder_neg.is_strictly_positive());
 }
@@ -108,15 +124,19 @@ fn resolve_ordered(
     let mut resolved = vec![];
     let mut remainder = Remainder::new(posting_units.number);
 
+    // Ties are broken by date (oldest first) for HIFO and by label for FIFO and LIFO, so that the
+    // lots to reduce do not depend on the order of the positions in the inventory.
     match order {
         ClosingOrder::Fifo => {
-            matches.sort_by_key(|position| position.cost.date);
+            matches.sort_by(|a, b| (a.cost.date, &a.cost.label).cmp(&(b.cost.date, &b.cost.label)));
         }
         ClosingOrder::Hifo => {
-            matches.sort_by_key(|position| Reverse(position.cost.number));
+            matches.sort_by_key(|position| (Reverse(position.cost.number), position.cost.date));
         }
         ClosingOrder::Lifo => {
-            matches.sort_by_key(|position| Reverse(position.cost.date));
+            matches.sort_by(|a, b| {
+                (Reverse(a.cost.date), &a.cost.label).cmp(&(Reverse(b.cost.date), &b.cost.label))
+            });
         }
     }
     for position in matches {
@@ -133,7 +153,7 @@ fn resolve_ordered(
     }
 
     if remainder.is_strictly_positive() {
-        Err(BookingErrorKind::InsufficientLots)
+        Err(remainder.insufficient_lots(posting_units))
     } else {
         Ok(resolved)
     }
@@ -169,7 +189,7 @@ fn resolve_strict(
         let reduced = remainder.reduce(position.number);
 
         if remainder.is_strictly_positive() {
-            Err(BookingErrorKind::InsufficientLots)
+            Err(remainder.insufficient_lots(posting_units))
         } else {
             Ok(vec![(
                 Amount::new(reduced, position.currency.clone()),
@@ -209,7 +229,108 @@ fn resolve_strict_with_size(
     )])
 }
 
+/// Close positions at the average cost of all matching positions.
+///
+/// The running balances are computed from the booked postings, so the averaging has to be
+/// expressed in the resulting postings: all matching lots are closed at their respective cost and
+/// the part that is not reduced is added back as a single lot at the average cost (with the date of
+/// the oldest lot, so that this lot is still reduced first by FIFO booking). The weight of these
+/// postings is just the reduced units at the average cost.
+fn resolve_average(
+    posting_units: &Amount,
+    matches: &[InventoryPositionWithCost],
+) -> Result<Vec<(Amount, Cost)>, BookingErrorKind> {
+    let total_units: Decimal = matches.iter().map(|p| *p.number).sum();
+    let mut remainder = Remainder::new(posting_units.number);
+    remainder.reduce(&total_units);
+    if remainder.is_strictly_positive() {
+        return Err(remainder.insufficient_lots(posting_units));
+    }
+
+    let first = &matches[0];
+    if matches.len() == 1 {
+        return Ok(vec![(posting_units.clone(), first.cost.clone())]);
+    }
+    if matches
+        .iter()
+        .any(|p| p.cost.currency != first.cost.currency)
+    {
+        return Err(BookingErrorKind::AmbiguousMatches);
+    }
+
+    let total_cost: Decimal = matches.iter().map(|p| *p.number * p.cost.number).sum();
+    let average = total_cost
+        .checked_div(total_units)
+        .ok_or(BookingErrorKind::DivisionFailed)?;
+    let date = matches
+        .iter()
+        .map(|p| p.cost.date)
+        .min()
+        .unwrap_or(first.cost.date);
+    let label = if matches.iter().all(|p| p.cost.label == first.cost.label) {
+        first.cost.label.clone()
+    } else {
+        None
+    };
+
+    let mut resolved = matches
+        .iter()
+        .map(|p| (Amount::new(-*p.number, p.currency.clone()), p.cost.clone()))
+        .collect::<Vec<_>>();
+    let remaining = total_units + posting_units.number;
+    if !remaining.is_zero() {
+        resolved.push((
+            Amount::new(remaining, posting_units.currency.clone()),
+            Cost::new(average, first.cost.currency.clone(), date, label),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Merge an augmentation with the merge cost syntax (like `{100.00 USD, *}`) into existing lots.
+///
+/// All the given existing lots are closed and a single lot with the total units (including the
+/// added ones) is added back at the average cost, with the date of the oldest lot.
+pub(super) fn resolve_merged_augmentation(
+    posting_units: &Amount,
+    cost_number: Decimal,
+    cost_currency: &Currency,
+    cost_date: Date,
+    label: Option<&CostLabel>,
+    matches: &[InventoryPositionWithCost],
+) -> Result<Vec<(Amount, Cost)>, BookingErrorKind> {
+    let total_units = posting_units.number + matches.iter().map(|p| *p.number).sum::<Decimal>();
+    let total_cost = posting_units.number * cost_number
+        + matches
+            .iter()
+            .map(|p| *p.number * p.cost.number)
+            .sum::<Decimal>();
+    let average = total_cost
+        .checked_div(total_units)
+        .ok_or(BookingErrorKind::DivisionFailed)?;
+    let date = matches
+        .iter()
+        .map(|p| p.cost.date)
+        .fold(cost_date, std::cmp::min);
+    let label = label
+        .filter(|l| matches.iter().all(|p| p.cost.label.as_ref() == Some(*l)))
+        .cloned();
+
+    let mut resolved = matches
+        .iter()
+        .map(|p| (Amount::new(-*p.number, p.currency.clone()), p.cost.clone()))
+        .collect::<Vec<_>>();
+    resolved.push((
+        Amount::new(total_units, posting_units.currency.clone()),
+        Cost::new(average, cost_currency.clone(), date, label),
+    ));
+    Ok(resolved)
+}
+
 /// Resolves matching positions.
+///
+/// A reduction with the merge cost syntax `{*}` always merges the matching positions, just like
+/// the `AVERAGE` booking method, independent of the booking method of the account.
 pub(super) fn resolve_matches(
     method: &BookingMethod,
     posting: &mut RawPosting,
@@ -219,6 +340,10 @@ pub(super) fn resolve_matches(
     debug_assert!(posting.cost.is_some());
     debug_assert!(!units.number.is_zero());
 
+    if posting.cost.as_ref().is_some_and(|cost| cost.merge) {
+        return resolve_average(units, &matches).map_err(|kind| kind.with_posting(posting));
+    }
+
     match method {
         BookingMethod::Ordered(order) => {
             resolve_ordered(units, matches, order).map_err(|kind| kind.with_posting(posting))
@@ -230,7 +355,7 @@ pub(super) fn resolve_matches(
             resolve_strict_with_size(units, &matches).map_err(|kind| kind.with_posting(posting))
         }
         BookingMethod::Average => {
-            Err(BookingErrorKind::UnsupportedAverageBooking.with_posting(posting))
+            resolve_average(units, &matches).map_err(|kind| kind.with_posting(posting))
         }
     }
 }
@@ -4,16 +4,19 @@ use hashbrown::HashMap;
 use crate::conversions::get_weight;
 use crate::inventory::Inventory;
 use crate::ledgers::{Ledger, RawLedger};
+use crate::options::BeancountOptions;
 use crate::tolerances::Tolerances;
 use crate::types::{
-    Account, Amount, Booking, Cost, CostSpec, Currency, Date, Decimal, Entry, Posting, RawAmount,
-    RawEntry, RawPosting, RawTransaction, Transaction,
+    Account, Amount, Booking, Cost, CostSpec, Currency, Date, Decimal, Entry, Filename, Posting,
+    RawAmount, RawEntry, RawPosting, RawTransaction, Transaction,
 };
 use crate::util::timer::SimpleTimer;
 
 use currency_groups::group_and_fill_in_currencies;
 use errors::{BookingError, BookingErrorKind};
-use methods::{BookingMethod, close_with_resolved_matches, resolve_matches};
+use methods::{
+    BookingMethod, close_with_resolved_matches, resolve_matches, resolve_merged_augmentation,
+};
 
 mod currency_groups;
 mod errors;
@@ -58,13 +61,44 @@ impl BookingMethods {
 
 type AccountBalances = HashMap<Account, Inventory>;
 
+/// Fill in the per-unit cost for postings that only specify a total cost (like `{{1000 USD}}`).
+///
+/// This requires the units number, so this errors for postings with a total cost but without a
+/// units number - these cannot be interpolated.
+fn fill_in_cost_per_unit(postings: &mut [RawPosting]) -> Result<(), BookingError> {
+    for posting in postings {
+        let Some(number_total) = posting
+            .cost
+            .as_ref()
+            .filter(|c| c.number_per.is_none())
+            .and_then(|c| c.number_total)
+        else {
+            continue;
+        };
+        let Some(units_number) = posting.units.number else {
+            return Err(BookingErrorKind::MissingUnitsForTotalCost.with_posting(posting));
+        };
+        let number_per = number_total
+            .checked_div(units_number.abs())
+            .ok_or_else(|| BookingErrorKind::DivisionFailed.with_posting(posting))?;
+        if let Some(cost) = &mut posting.cost {
+            cost.number_per = Some(number_per);
+            cost.number_total = None;
+        }
+    }
+    Ok(())
+}
+
 /// Find positions in the account balances that can be closed with the given postings.
 ///
-/// This mutates the given list of raw postings in place.
+/// Augmentations with the merge cost syntax `{*}` are merged with the existing lots of the
+/// commodity (in the same cost currency) here as well. This mutates the given list of raw postings
+/// in place. The date is used as the lot date for augmentations.
 fn close_positions(
     balances: &AccountBalances,
     postings: &mut Vec<RawPosting>,
     methods: &BookingMethods,
+    date: Date,
 ) -> Result<(), BookingError> {
     let mut additional_postings = Vec::new();
     // We keep local balances to allow multiple reductions to the same account in one
@@ -125,11 +159,49 @@ fn close_positions(
                 })
                 .collect::<Vec<_>>();
             if matches.is_empty() {
-                return Err(BookingErrorKind::NoMatchesForReduction.with_posting(posting));
+                // If a cost was given to select lots, but no lot has this cost, we can give a
+                // more specific error. Cost numbers that only differ in scale (like `100` and
+                // `100.00`) are considered equal.
+                let kind = match (cost.number_per, &cost.currency) {
+                    (Some(number), Some(currency))
+                        if !balance.iter_with_cost().any(|pos| {
+                            units.currency == *pos.currency
+                                && pos.cost.number == number
+                                && pos.cost.currency == *currency
+                        }) =>
+                    {
+                        BookingErrorKind::NoLotAtCost(Amount::new(number, currency.clone()))
+                    }
+                    _ => BookingErrorKind::NoMatchesForReduction,
+                };
+                return Err(kind.with_posting(posting));
             }
             let resolved_matches = resolve_matches(&booking_method, posting, matches, &units)?;
             let mut resolved = close_with_resolved_matches(posting, balance, resolved_matches);
             additional_postings.append(&mut resolved);
+        } else if cost.merge
+            && let (Some(cost_number), Some(cost_currency)) = (cost.number_per, &cost.currency)
+        {
+            let matches = balance
+                .iter_with_cost()
+                .filter(|pos| {
+                    units.currency == *pos.currency && *cost_currency == pos.cost.currency
+                })
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                continue;
+            }
+            let resolved_matches = resolve_merged_augmentation(
+                &units,
+                cost_number,
+                cost_currency,
+                cost.date.unwrap_or(date),
+                cost.label.as_ref(),
+                &matches,
+            )
+            .map_err(|kind| kind.with_posting(posting))?;
+            let mut resolved = close_with_resolved_matches(posting, balance, resolved_matches);
+            additional_postings.append(&mut resolved);
         }
     }
     postings.append(&mut additional_postings);
@@ -174,6 +246,11 @@ fn complete_cost_spec(
         .as_ref()
         .expect("cost to have currency")
         .clone();
+    if number < Decimal::ZERO {
+        return Err(BookingErrorKind::NegativeCost(Amount::new(
+            number, currency,
+        )));
+    }
 
     Ok(Cost {
         number,
@@ -207,6 +284,7 @@ fn find_missing_value(posting: &RawPosting, date: Date) -> Result<MissingNumber,
         .transpose();
 
     match (units, price, cost) {
+        (_, _, Err(e @ BookingErrorKind::NegativeCost(..))) => Err(e.with_posting(posting)),
         (Ok(u), Ok(p), Ok(c)) => Ok(MissingNumber::None(u, p, c)),
         (Err(..), Ok(p), Ok(c)) => Ok(MissingNumber::UnitsNumber(p, c)),
         (Ok(u), Err(..), Ok(c)) => Ok(MissingNumber::PriceNumber(u, c)),
@@ -227,7 +305,7 @@ fn interpolate_and_fill_in_missing(
     tolerances: &Tolerances,
     date: Date,
 ) -> Result<Vec<Posting>, BookingError> {
-    let mut incomplete = None;
+    let mut incomplete: Option<(RawPosting, MissingNumber)> = None;
     let mut complete_postings = Vec::with_capacity(postings.len());
 
     for posting in postings {
@@ -235,8 +313,11 @@ fn interpolate_and_fill_in_missing(
         if let MissingNumber::None(units, price, cost) = missing_type {
             complete_postings.push(posting.complete(units, price, cost));
         } else {
-            if incomplete.is_some() {
-                return Err(BookingErrorKind::TooManyMissingNumbers.with_posting(&posting));
+            if let Some((first, _)) = &incomplete {
+                let first_line = first.meta.lineno;
+                return Err(
+                    BookingErrorKind::TooManyIncompletePostings(first_line).with_posting(&posting)
+                );
             }
             incomplete = Some((posting, missing_type));
         }
@@ -288,8 +369,9 @@ fn interpolate_and_fill_in_missing(
                         Some(weight.checked_div(units.number).ok_or_else(|| {
                             BookingErrorKind::DivisionFailed.with_posting(&posting)
                         })?);
+                    // This can only fail for a negative cost, numbers and currency are given.
                     let cost = complete_cost_spec(&cost_spec, date, posting.units.number)
-                        .expect("cost to not have missing number or currency");
+                        .map_err(|e| e.with_posting(&posting))?;
                     Some((units, price, Some(cost)))
                 }
             }
@@ -309,13 +391,54 @@ fn interpolate_and_fill_in_missing(
             MissingNumber::None(units, price, cost) => Some((units, price, cost)),
         };
         if let Some((units, price, cost)) = interpolated {
-            complete_postings.push(posting.complete(units, price, cost));
+            let mut posting = posting.complete(units, price, cost);
+            posting.meta.set_automatic();
+            complete_postings.push(posting);
         }
     }
 
     Ok(complete_postings)
 }
 
+/// Compute the residual of the given booked postings, i.e., the sum of their weights.
+#[must_use]
+pub fn compute_residual(postings: &[Posting]) -> Inventory {
+    postings.iter().map(get_weight).collect()
+}
+
+/// Check whether the given booked postings balance.
+///
+/// The residual has to be small with respect to the tolerances inferred from the postings.
+#[must_use]
+pub fn is_balanced(postings: &[Posting], options: &BeancountOptions) -> bool {
+    let tolerances = Tolerances::infer_from_booked(postings, options);
+    tolerances.is_small(&compute_residual(postings))
+}
+
+/// Post the residual of the booked postings to the rounding account.
+///
+/// Only the currencies with a non-zero residual that is within the tolerances are swept into the
+/// rounding account, larger residuals are left to be reported by the balance check.
+fn add_rounding_postings(
+    postings: &mut Vec<Posting>,
+    account: &Account,
+    filename: &Filename,
+    options: &BeancountOptions,
+) {
+    let tolerances = Tolerances::infer_from_booked(postings, options);
+    let rounding = compute_residual(postings)
+        .iter()
+        .filter(|pos| {
+            !pos.number.is_zero() && tolerances.is_small_number(*pos.number, pos.currency)
+        })
+        .map(|pos| {
+            let units = Amount::new(-*pos.number, pos.currency.clone());
+            Posting::new_simple(filename.clone(), account.clone(), units)
+        })
+        .collect::<Vec<_>>();
+    postings.extend(rounding);
+}
+
 /// Update the running balances for all postings of a booked transaction.
 fn update_running_balances(balances: &mut AccountBalances, transaction: &Transaction) {
     for posting in &transaction.postings {
@@ -343,7 +466,8 @@ pub(crate) fn book_entries(raw_ledger: RawLedger) -> (Ledger, AccountBalances) {
 
             let groups = group_and_fill_in_currencies(&txn.postings, balances)?;
             for (currency, mut postings) in groups {
-                close_positions(balances, &mut postings, &booking_methods)?;
+                fill_in_cost_per_unit(&mut postings)?;
+                close_positions(balances, &mut postings, &booking_methods, txn.date)?;
                 booked_postings.append(&mut interpolate_and_fill_in_missing(
                     postings,
                     &currency,
@@ -352,6 +476,14 @@ pub(crate) fn book_entries(raw_ledger: RawLedger) -> (Ledger, AccountBalances) {
                 )?);
             }
             booked_postings.sort_by_key(|p| p.meta.lineno);
+            if let Some(account) = &raw_ledger.options.account_rounding {
+                add_rounding_postings(
+                    &mut booked_postings,
+                    account,
+                    &txn.meta.filename,
+                    &raw_ledger.options,
+                );
+            }
             booked_postings
         };
         Ok(txn.complete(booked_postings))
@@ -363,9 +495,29 @@ pub(crate) fn book_entries(raw_ledger: RawLedger) -> (Ledger, AccountBalances) {
     let mut ledger = Ledger::from_raw_empty_entries(&raw_ledger);
 
     for raw_entry in raw_ledger.entries {
+        // A lone posting has nothing to balance against, this is almost always a mistake. The
+        // transaction is still booked. Since postings might be interpolated, this is checked on
+        // the booked postings, a lone posting without weight is fine.
+        let single_posting = match &raw_entry {
+            RawEntry::RawTransaction(txn)
+                if !raw_ledger.options.allow_single_posting_transactions =>
+            {
+                match txn.postings.as_slice() {
+                    [posting] => Some(BookingErrorKind::SinglePosting.with_posting(posting)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
         match raw_entry {
             RawEntry::RawTransaction(i) => match handle_txn(&balances, i) {
                 Ok(txn) => {
+                    if let Some(err) = single_posting
+                        && let [posting] = txn.postings.as_slice()
+                        && !get_weight(posting).number.is_zero()
+                    {
+                        errors.push(err.into());
+                    }
                     update_running_balances(&mut balances, &txn);
                     entries.push(Entry::Transaction(txn));
                 }
@@ -6,7 +6,7 @@ use crate::ledgers::RawLedger;
 use crate::parse::parse_string;
 use crate::test_utils;
 use crate::types::{
-    AbsoluteUTF8Path, Account, Booking, Entry, MIN_DATE, Posting, RawEntry, RawPosting,
+    AbsoluteUTF8Path, Account, Booking, Entry, Filename, MIN_DATE, Posting, RawEntry, RawPosting,
     RawTransaction,
 };
 
@@ -89,6 +89,8 @@ fn run_booking_test(path: &Path) {
 
         let mut ledger = raw_ledger.clone();
         ledger.options.booking_method = booking_method;
+        // The test transactions usually just consist of the single posting to book.
+        ledger.options.allow_single_posting_transactions = true;
         ledger.entries = vec![];
 
         if let Some(ante_txn) = find_first_with_tag(ANTE, entries) {
@@ -167,3 +169,198 @@ fn booking_test() {
         run_booking_test(path);
     });
 }
+
+#[test]
+fn interpolated_postings_are_marked() {
+    let input = r#"
+2000-01-01 * "auto-posting for two currencies"
+  Assets:Cash     20 USD
+  Assets:Cash     10 EUR
+  Assets:Other
+
+2000-01-02 * "price number missing"
+  Assets:Stock    10 APL @ USD
+  Assets:Cash    -50 USD
+"#;
+    let filename = Filename::new_dummy("string");
+    let raw_ledger =
+        RawLedger::from_single_parsed_file(filename.clone(), parse_string(input, &filename));
+    let (booked, _) = book_entries(raw_ledger);
+    assert!(booked.errors.is_empty());
+
+    let automatic = booked
+        .entries
+        .iter()
+        .filter_map(|e| e.as_transaction())
+        .map(|t| {
+            t.postings
+                .iter()
+                .map(|p| (p.account.to_string(), p.meta.is_automatic()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        automatic,
+        vec![
+            vec![
+                ("Assets:Cash".to_owned(), false),
+                ("Assets:Cash".to_owned(), false),
+                ("Assets:Other".to_owned(), true),
+                ("Assets:Other".to_owned(), true),
+            ],
+            vec![
+                ("Assets:Stock".to_owned(), true),
+                ("Assets:Cash".to_owned(), false),
+            ],
+        ]
+    );
+}
+
+#[test]
+fn average_booking_recomputes_average_after_augmentation() {
+    let input = "
+2016-01-01 *
+  Assets:Account          10 HOOL {100.00 USD}
+  Assets:Account          10 HOOL {110.00 USD}
+  Assets:Cash
+
+2016-01-02 *
+  Assets:Account          -5 HOOL {}
+  Assets:Cash
+
+2016-01-03 *
+  Assets:Account           5 HOOL {125.00 USD}
+  Assets:Cash
+
+2016-01-04 *
+  Assets:Account         -10 HOOL {}
+  Assets:Cash
+";
+    let filename = Filename::new_dummy("string");
+    let mut raw_ledger =
+        RawLedger::from_single_parsed_file(filename.clone(), parse_string(input, &filename));
+    raw_ledger.options.booking_method = Booking::Average;
+    let (booked, balances) = book_entries(raw_ledger);
+    assert!(booked.errors.is_empty());
+
+    let cash = balances.get(&Account::from("Assets:Cash")).unwrap();
+    let balance = balances.get(&Account::from("Assets:Account")).unwrap();
+    let print = |inventory: &crate::inventory::Inventory| {
+        inventory
+            .iter()
+            .map(|p| p.print_units_and_cost())
+            .collect::<Vec<_>>()
+    };
+    // (15 * 105.00 + 5 * 125.00) / 20 = 110.00
+    assert_eq!(
+        print(balance),
+        ["units=10 HOOL, cost=110.00 USD, 2016-01-01"]
+    );
+    // -2100.00 + 525.00 - 625.00 + 1100.00
+    assert_eq!(print(cash), ["units=-1100.00 USD, cost=None"]);
+}
+
+#[test]
+fn single_posting_transactions() {
+    let input = r#"
+2000-01-01 * "lone auto-posting"
+  Assets:Cash
+
+2000-01-02 * "lone posting"
+  Assets:Cash     0 USD
+
+2000-01-03 * "two postings"
+  Assets:Cash     0 USD
+  Assets:Other
+
+2000-01-04 * "lone posting with a weight"
+  Assets:Cash     10 USD
+"#;
+    let filename = Filename::new_dummy("string");
+    let raw_ledger =
+        RawLedger::from_single_parsed_file(filename.clone(), parse_string(input, &filename));
+    let (booked, _) = book_entries(raw_ledger.clone());
+    assert_eq!(booked.entries.len(), 4);
+    assert_eq!(
+        booked
+            .errors
+            .iter()
+            .map(|e| (e.message(), e.lineno()))
+            .collect::<Vec<_>>(),
+        [("Transaction has only a single posting", Some(13))]
+    );
+
+    let mut raw_ledger = raw_ledger;
+    raw_ledger.options.allow_single_posting_transactions = true;
+    let (booked, _) = book_entries(raw_ledger);
+    assert!(booked.errors.is_empty());
+}
+
+#[test]
+fn rounding_residuals_are_posted_to_the_rounding_account() {
+    let input = r#"
+2000-01-01 * "residual within the tolerance"
+  Assets:Cash     10.003 USD
+  Assets:Other   -10.00 USD
+
+2000-01-02 * "residual too large"
+  Assets:Cash     10.10 USD
+  Assets:Other   -10.00 USD
+
+2000-01-03 * "balanced"
+  Assets:Cash     10.00 USD
+  Assets:Other   -10.00 USD
+"#;
+    let filename = Filename::new_dummy("string");
+    let mut raw_ledger =
+        RawLedger::from_single_parsed_file(filename.clone(), parse_string(input, &filename));
+    let postings = |raw_ledger: RawLedger| {
+        let (booked, _) = book_entries(raw_ledger);
+        assert!(booked.errors.is_empty());
+        booked
+            .entries
+            .iter()
+            .filter_map(|e| e.as_transaction())
+            .map(|t| {
+                t.postings
+                    .iter()
+                    .map(|p| format!("{} {}", p.account, p.units))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert!(postings(raw_ledger.clone()).iter().all(|p| p.len() == 2));
+
+    raw_ledger.options.account_rounding = Some(Account::from("Equity:Rounding"));
+    assert_eq!(
+        postings(raw_ledger),
+        vec![
+            vec![
+                "Assets:Cash 10.003 USD",
+                "Assets:Other -10.00 USD",
+                "Equity:Rounding -0.003 USD"
+            ],
+            vec!["Assets:Cash 10.10 USD", "Assets:Other -10.00 USD"],
+            vec!["Assets:Cash 10.00 USD", "Assets:Other -10.00 USD"],
+        ]
+    );
+}
+
+#[test]
+fn booking_errors_have_a_kind() {
+    let input = r#"
+2000-01-01 * "buy"
+  Assets:Stock     10 HOOL {10 USD}
+  Assets:Cash
+
+2000-01-02 * "sell more than there is"
+  Assets:Stock    -20 HOOL {10 USD}
+  Assets:Cash
+"#;
+    let filename = Filename::new_dummy("string");
+    let raw_ledger =
+        RawLedger::from_single_parsed_file(filename.clone(), parse_string(input, &filename));
+    let (booked, _) = book_entries(raw_ledger);
+    let kinds = booked.errors.iter().map(UroError::kind).collect::<Vec<_>>();
+    assert_eq!(kinds, [Some("insufficient_lots")]);
+}
@@ -1,14 +1,19 @@
 //! Load files and combine multiple parse results into one (raw) ledger.
 
-use std::collections::VecDeque;
 use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
 
-use hashbrown::HashSet;
+use flate2::read::MultiGzDecoder;
+use hashbrown::{HashMap, HashSet};
+use rayon::prelude::*;
 
 use crate::booking;
-use crate::display_precision::DisplayPrecisions;
+use crate::display_precision::{DisplayPrecisions, DisplayPrecisionsStats};
 use crate::errors::UroError;
-use crate::ledgers::{Ledger, RawLedger};
+use crate::ledgers::{Ledger, LoadTimings, RawLedger};
 use crate::parse;
 use crate::parse::ParsedFile;
 use crate::types::{AbsoluteUTF8Path, Filename, Plugin, RawDirective};
@@ -18,11 +23,17 @@ use crate::util::timer::SimpleTimer;
 struct PathAndResult {
     path: Filename,
     result: ParsedFile,
+    /// The files that include this file (empty for the main file).
+    included_from: Vec<Filename>,
 }
 
 impl PathAndResult {
     fn new(path: Filename, result: ParsedFile) -> Self {
-        Self { path, result }
+        Self {
+            path,
+            result,
+            included_from: Vec::new(),
+        }
     }
 }
 
@@ -35,11 +46,89 @@ impl PathAndResult {
 /// should be orchestrated from the calling Python code.
 #[must_use]
 pub fn load(main_path: AbsoluteUTF8Path) -> Ledger {
-    let paths_and_results = load_beancount_file(main_path);
-    let raw_ledger = combine_files(paths_and_results);
-    let (mut ledger, _) = booking::book_entries(raw_ledger);
-    crate::plugins::run_pre(&mut ledger);
-    ledger
+    let mut t = SimpleTimer::new();
+    let paths_and_results = load_beancount_file(main_path, load_single_beancount_file);
+    let parse = t.lap();
+    book_and_run_pre_plugins(paths_and_results, parse)
+}
+
+/// A cache of parsed files to speed up reloading a ledger.
+///
+/// For each file, this stores the modification time of the file together with the result of
+/// parsing it.
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    files: HashMap<AbsoluteUTF8Path, (SystemTime, ParsedFile)>,
+}
+
+impl ParseCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of cached files.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether there are no cached files.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Remove all files from the cache that are not in the given list.
+    fn retain_files<'a>(&mut self, paths: impl Iterator<Item = &'a Filename>) {
+        let paths = paths.collect::<HashSet<_>>();
+        self.files
+            .retain(|path, _| paths.contains(&Filename::from(path.clone())));
+    }
+
+    /// Parse the file at the given path, reusing the cached result if the file is unchanged.
+    ///
+    /// Newly parsed files are added to `updates`, to be inserted into the cache afterwards.
+    fn load(
+        &self,
+        path: &AbsoluteUTF8Path,
+        updates: &Mutex<Vec<(AbsoluteUTF8Path, (SystemTime, ParsedFile))>>,
+    ) -> Result<ParsedFile, UroError> {
+        let mtime = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some(mtime) = mtime
+            && let Some((cached_mtime, result)) = self.files.get(path)
+            && *cached_mtime == mtime
+        {
+            return Ok(result.clone());
+        }
+        let result = load_single_beancount_file(path)?;
+        if let Some(mtime) = mtime {
+            updates
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((path.clone(), (mtime, result.clone())));
+        }
+        Ok(result)
+    }
+}
+
+/// Load a Beancount file, reusing the parse results of unchanged files from the cache.
+///
+/// Like [`load`], but files whose modification time did not change since they were last
+/// loaded with the given cache are not read and parsed again. All subsequent steps (like
+/// booking) are still run for all entries. Files that are no longer included are removed from
+/// the cache.
+#[must_use]
+pub fn load_incremental(main_path: AbsoluteUTF8Path, cache: &mut ParseCache) -> Ledger {
+    let mut t = SimpleTimer::new();
+    let updates = Mutex::new(Vec::new());
+    let paths_and_results = load_beancount_file(main_path, |path| cache.load(path, &updates));
+    let parse = t.lap();
+    cache
+        .files
+        .extend(updates.into_inner().unwrap_or_else(PoisonError::into_inner));
+    cache.retain_files(paths_and_results.iter().map(|r| &r.path));
+    book_and_run_pre_plugins(paths_and_results, parse)
 }
 
 /// Load a Beancount string.
@@ -51,21 +140,90 @@ pub fn load(main_path: AbsoluteUTF8Path) -> Ledger {
 /// should be orchestrated from the calling Python code.
 #[must_use]
 pub fn load_string(string: &str, filename: Filename) -> Ledger {
+    let mut t = SimpleTimer::new();
     let result = parse::parse_string(string, &filename);
     let paths_and_results = vec![PathAndResult::new(filename, result)];
+    let parse = t.lap();
+    book_and_run_pre_plugins(paths_and_results, parse)
+}
+
+/// Combine and book the parsed files and run the plugins that run right after booking.
+///
+/// Records the time that each step took (and the given parse time) on the ledger.
+fn book_and_run_pre_plugins(paths_and_results: Vec<PathAndResult>, parse: Duration) -> Ledger {
+    let mut t = SimpleTimer::new();
     let raw_ledger = combine_files(paths_and_results);
+    let combine = t.lap();
     let (mut ledger, _) = booking::book_entries(raw_ledger);
+    let book = t.lap();
     crate::plugins::run_pre(&mut ledger);
+    ledger.timings = LoadTimings {
+        parse,
+        combine,
+        book,
+        plugins: t.lap(),
+        validations: Duration::ZERO,
+    };
     ledger
 }
 
+/// Load a Beancount file and run the built-in validations.
+///
+/// Like [`load`], but the returned ledger also contains the errors of the built-in validations.
+/// The validations assume that the entries are sorted, which they are after loading.
+/// This still does not run any user-specified plugins.
+#[must_use]
+pub fn load_validated(main_path: AbsoluteUTF8Path) -> Ledger {
+    let mut ledger = load(main_path);
+    ledger.run_validations();
+    ledger
+}
+
+/// Load a Beancount string and run the built-in validations.
+///
+/// Like [`load_string`], but the returned ledger also contains the errors of the built-in
+/// validations. The validations assume that the entries are sorted, which they are after loading.
+/// This still does not run any user-specified plugins.
+#[must_use]
+pub fn load_string_validated(string: &str, filename: Filename) -> Ledger {
+    let mut ledger = load_string(string, filename);
+    ledger.run_validations();
+    ledger
+}
+
+/// The magic bytes at the start of gzip-compressed files.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Read a file to a string, decompressing it if it is gzip-compressed.
+///
+/// Files are considered to be compressed if they have a `.gz` extension or start with the gzip
+/// magic bytes.
+fn read_beancount_file(path: &AbsoluteUTF8Path) -> Result<String, UroError> {
+    let io_error = |io_error: io::Error| {
+        UroError::new(format!("Could not read file due to IO error: {io_error}"))
+            .with_filename(path.clone().into())
+    };
+    let bytes = fs::read(path).map_err(io_error)?;
+    let is_gz = Path::extension(path.as_ref()).is_some_and(|ext| ext == "gz");
+    if is_gz || bytes.starts_with(&GZIP_MAGIC) {
+        let mut string = String::new();
+        MultiGzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut string)
+            .map_err(|e| {
+                UroError::new(format!("Could not decompress gzip file: {e}"))
+                    .with_filename(path.clone().into())
+            })?;
+        Ok(string)
+    } else {
+        String::from_utf8(bytes)
+            .map_err(|e| io_error(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+}
+
 /// Load and parse a single Beancount file.
 fn load_single_beancount_file(path: &AbsoluteUTF8Path) -> Result<ParsedFile, UroError> {
     // Always append a newline at the end, to avoid errors on a last missing end-of-line.
-    let string = fs::read_to_string(path).map_err(|io_error| {
-        UroError::new(format!("Could not read file due to IO error: {io_error}"))
-            .with_filename(path.clone().into())
-    })?;
+    let string = read_beancount_file(path)?;
     let mut t = SimpleTimer::new();
     let result = parse::parse_string(&string, &path.clone().into());
     log::info!("{}", t.elapsed(&format!("{path}: parsing")));
@@ -73,24 +231,50 @@ fn load_single_beancount_file(path: &AbsoluteUTF8Path) -> Result<ParsedFile, Uro
 }
 
 /// Load and parse a Beancount file and all includes.
-fn load_beancount_file(main_path: AbsoluteUTF8Path) -> Vec<PathAndResult> {
-    let mut path_queue = VecDeque::new();
-    path_queue.push_back(main_path);
+///
+/// The given function is used to load and parse each single file. The includes are followed
+/// breadth-first, level by level: all files of a level are parsed in parallel and the includes in
+/// them form the next level. The results are in the same order as if the files were parsed one
+/// after the other.
+fn load_beancount_file(
+    main_path: AbsoluteUTF8Path,
+    load_file: impl Fn(&AbsoluteUTF8Path) -> Result<ParsedFile, UroError> + Sync,
+) -> Vec<PathAndResult> {
+    let mut level = vec![main_path];
     // keep track of loaded files to avoid doing them twice
     let mut loaded = HashSet::new();
+    // keep track of which files include a file to be able to report redundant includes
+    let mut included_from: HashMap<AbsoluteUTF8Path, Vec<Filename>> = HashMap::new();
+    // keep track of the files that a file includes to be able to report circular includes
+    let mut includes: HashMap<AbsoluteUTF8Path, Vec<AbsoluteUTF8Path>> = HashMap::new();
     let mut results = Vec::new();
 
-    while let Some(path) = path_queue.pop_front() {
-        // Check if that we have not seen this file.
-        if loaded.insert(path.clone()) {
-            let mut result = match load_single_beancount_file(&path) {
-                Ok(res) => res,
-                Err(err) => ParsedFile::from_error(err),
-            };
+    while !level.is_empty() {
+        // Check that we have not seen these files.
+        level.retain(|path| loaded.insert(path.clone()));
+        let parsed = level
+            .par_iter()
+            .map(|path| load_file(path).unwrap_or_else(ParsedFile::from_error))
+            .collect::<Vec<_>>();
+
+        let mut next_level = Vec::new();
+        for (path, mut result) in level.into_iter().zip(parsed) {
             for directive in &result.directives {
                 if let RawDirective::Include { pattern } = directive {
                     match paths::glob_include(&path, pattern) {
-                        Ok(included_paths) => path_queue.extend(included_paths.into_iter()),
+                        Ok(included_paths) => {
+                            for included_path in &included_paths {
+                                included_from
+                                    .entry(included_path.clone())
+                                    .or_default()
+                                    .push(path.clone().into());
+                            }
+                            includes
+                                .entry(path.clone())
+                                .or_default()
+                                .extend(included_paths.iter().cloned());
+                            next_level.extend(included_paths);
+                        }
                         Err(glob_include_error) => result.errors.push(
                             UroError::new(format!(
                                 "Include pattern '{pattern}' failed: {glob_include_error}"
@@ -100,10 +284,123 @@ fn load_beancount_file(main_path: AbsoluteUTF8Path) -> Vec<PathAndResult> {
                     }
                 }
             }
-            results.push(PathAndResult::new(path.into(), result));
+            results.push((path, result));
+        }
+        level = next_level;
+    }
+    if let Some((main_path, _)) = results.first() {
+        for cycle in find_include_cycles(main_path, &includes) {
+            let chain = cycle
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" → ");
+            let last_file = &cycle[cycle.len() - 2];
+            if let Some((_, result)) = results.iter_mut().find(|(path, _)| path == last_file) {
+                result.errors.push(
+                    UroError::new(format!("Circular include: {chain}"))
+                        .with_filename(last_file.clone().into()),
+                );
+            }
         }
     }
     results
+        .into_iter()
+        .map(|(path, result)| {
+            let mut path_and_result = PathAndResult::new(path.clone().into(), result);
+            if let Some(included_from) = included_from.remove(&path) {
+                path_and_result.included_from = included_from;
+            }
+            path_and_result
+        })
+        .collect()
+}
+
+/// Find all cycles in the include graph that is reachable from the main file.
+///
+/// Each cycle is given as the chain of files, starting and ending with the same file.
+fn find_include_cycles(
+    main_path: &AbsoluteUTF8Path,
+    includes: &HashMap<AbsoluteUTF8Path, Vec<AbsoluteUTF8Path>>,
+) -> Vec<Vec<AbsoluteUTF8Path>> {
+    fn visit<'a>(
+        path: &'a AbsoluteUTF8Path,
+        includes: &'a HashMap<AbsoluteUTF8Path, Vec<AbsoluteUTF8Path>>,
+        chain: &mut Vec<&'a AbsoluteUTF8Path>,
+        done: &mut HashSet<&'a AbsoluteUTF8Path>,
+        cycles: &mut Vec<Vec<AbsoluteUTF8Path>>,
+    ) {
+        chain.push(path);
+        for included in includes.get(path).into_iter().flatten() {
+            if let Some(start) = chain.iter().position(|p| *p == included) {
+                let mut cycle = chain[start..].iter().copied().cloned().collect::<Vec<_>>();
+                cycle.push(included.clone());
+                cycles.push(cycle);
+            } else if !done.contains(included) {
+                visit(included, includes, chain, done, cycles);
+            }
+        }
+        chain.pop();
+        done.insert(path);
+    }
+
+    let mut cycles = Vec::new();
+    visit(
+        main_path,
+        includes,
+        &mut Vec::new(),
+        &mut HashSet::new(),
+        &mut cycles,
+    );
+    cycles
+}
+
+/// Errors for all files that are reached by more than one include directive.
+fn duplicate_include_errors(result: &[PathAndResult]) -> Vec<UroError> {
+    result
+        .iter()
+        .filter(|r| r.included_from.len() > 1)
+        .map(|r| {
+            let included_from = r
+                .included_from
+                .iter()
+                .map(|f| format!("'{f}'"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            UroError::new(format!(
+                "File '{}' is included more than once (from {included_from})",
+                r.path
+            ))
+            .with_filename(r.included_from[1].clone())
+        })
+        .collect()
+}
+
+/// Errors for all option directives in a file that come after the first entry of the file.
+///
+/// The options are still applied, as they are global to the ledger.
+fn late_option_errors(result: &ParsedFile) -> Vec<UroError> {
+    let Some(first_entry_lineno) = result.first_entry_lineno else {
+        return Vec::new();
+    };
+    result
+        .directives
+        .iter()
+        .filter_map(|d| match d {
+            RawDirective::Option {
+                key,
+                filename,
+                lineno,
+                ..
+            } if *lineno > first_entry_lineno => Some(
+                UroError::new(format!(
+                    "Option '{key}' is set after the first entry (on line {first_entry_lineno})"
+                ))
+                .with_position(filename.clone(), *lineno),
+            ),
+            _ => None,
+        })
+        .collect()
 }
 
 /// Combine the parsed results from multiple files.
@@ -117,16 +414,22 @@ fn combine_files(result: Vec<PathAndResult>) -> RawLedger {
     let mut combined =
         RawLedger::from_filename_and_includes(result[0].path.clone(), all_includes, entry_count);
     let mut t = SimpleTimer::new();
+    let duplicate_includes = duplicate_include_errors(&result);
+    let mut option_errors = Vec::new();
 
     // Merge all ledgers
     for PathAndResult {
         path: _,
         mut result,
+        included_from: _,
     } in result
     {
-        combined
-            .options
-            .update_from_raw_directives(&result.directives);
+        combined.errors.append(&mut late_option_errors(&result));
+        option_errors.append(
+            &mut combined
+                .options
+                .update_from_raw_directives(&result.directives),
+        );
         combined.entries.append(&mut result.entries);
         combined.errors.append(&mut result.errors);
         combined.plugins.append(
@@ -143,13 +446,336 @@ fn combine_files(result: Vec<PathAndResult>) -> RawLedger {
                 .collect(),
         );
     }
+    if combined.options.warn_duplicate_includes {
+        combined.errors.extend(duplicate_includes);
+    }
+    // Only decide after all options are set, so the strict option applies to all of them.
+    if combined.options.strict_options {
+        combined.errors.append(&mut option_errors);
+    } else {
+        for error in option_errors {
+            log::warn!("{}", error.message());
+        }
+    }
     log::info!("{}", t.elapsed("combining options and entries"));
 
     combined.entries.sort();
     log::info!("{}", t.elapsed("sorting entries"));
 
-    combined.options.display_precisions = DisplayPrecisions::from_raw_entries(&combined.entries);
+    let stats = DisplayPrecisionsStats::from_raw_entries(&combined.entries);
+    combined.options.display_precisions = DisplayPrecisions::from(&stats);
+    combined.options.display_precisions_stats = stats;
     log::info!("{}", t.elapsed("compute display context"));
 
     combined
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::types::{Currency, Entry};
+
+    fn test_ledger_path(name: &str) -> AbsoluteUTF8Path {
+        let path = std::env::current_dir()
+            .unwrap()
+            .join("tests")
+            .join("ledgers")
+            .join(name);
+        path.as_path().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_includes() {
+        let ledger = load(test_ledger_path("duplicate-includes.beancount"));
+        assert_eq!(ledger.includes.len(), 4);
+        // the shared file is only loaded once
+        assert_eq!(ledger.entries.len(), 3);
+        assert_eq!(ledger.errors.len(), 1);
+        let message = ledger.errors[0].message();
+        assert!(message.starts_with("File '"));
+        assert!(message.contains("shared.beancount' is included more than once"));
+        assert!(message.contains("a.beancount', '"));
+        assert!(message.ends_with("b.beancount')"));
+    }
+
+    #[test]
+    fn test_circular_includes() {
+        let ledger = load(test_ledger_path("circular-includes.beancount"));
+        assert_eq!(ledger.includes.len(), 3);
+        assert_eq!(ledger.entries.len(), 2);
+        assert_eq!(ledger.errors.len(), 1);
+        let dir = test_ledger_path("circular-includes");
+        let a = format!("{dir}/a.beancount");
+        let b = format!("{dir}/b.beancount");
+        assert_eq!(
+            ledger.errors[0].message(),
+            format!("Circular include: {a} → {b} → {a}")
+        );
+        assert_eq!(ledger.errors[0].filename().unwrap().to_string(), b);
+    }
+
+    #[test]
+    fn test_gzip_includes() {
+        let ledger = load(test_ledger_path("gzip-includes.beancount"));
+        assert!(ledger.errors.is_empty());
+        assert_eq!(ledger.includes.len(), 2);
+        assert!(
+            ledger.includes[1]
+                .to_string()
+                .ends_with("2019.beancount.gz")
+        );
+        assert_eq!(ledger.entries.len(), 4);
+        let transaction = ledger.entries.iter().find_map(Entry::as_transaction);
+        assert!(
+            transaction
+                .unwrap()
+                .meta
+                .filename
+                .to_string()
+                .ends_with("2019.beancount.gz")
+        );
+
+        let ledger = load(test_ledger_path("gzip-includes/invalid.beancount.gz"));
+        assert_eq!(ledger.errors.len(), 1);
+        assert!(
+            ledger.errors[0]
+                .message()
+                .starts_with("Could not decompress gzip file: ")
+        );
+        assert!(ledger.errors[0].filename().is_some());
+
+        // files that consist of multiple gzip members (e.g. concatenated) are read completely
+        let ledger = load(test_ledger_path("gzip-includes/multi-member.beancount.gz"));
+        assert!(ledger.errors.is_empty());
+        assert_eq!(ledger.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_options_after_first_entry() {
+        let ledger = load_string(
+            r#"
+option "title" "Title"
+2020-01-01 open Assets:Cash
+option "operating_currency" "EUR"
+"#,
+            Filename::new_dummy("string"),
+        );
+        assert_eq!(ledger.options.operating_currency, [Currency::from("EUR")]);
+        assert_eq!(ledger.errors.len(), 1);
+        assert_eq!(
+            ledger.errors[0].message(),
+            "Option 'operating_currency' is set after the first entry (on line 3)"
+        );
+    }
+
+    #[test]
+    fn test_strict_options() {
+        let string = r#"
+option "unknown_option" "value"
+option "plugin_processing_mode" "raw"
+option "booking_method" "INVALID"
+"#;
+        let ledger = load_string(string, Filename::new_dummy("string"));
+        assert!(!ledger.options.strict_options);
+        assert!(ledger.errors.is_empty());
+
+        // the strict option also applies to options set before it
+        let ledger = load_string(
+            &format!("{string}option \"uromyces_strict_options\" \"TRUE\"\n"),
+            Filename::new_dummy("string"),
+        );
+        assert!(ledger.options.strict_options);
+        let messages = ledger
+            .errors
+            .iter()
+            .map(UroError::message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "Unknown option 'unknown_option'",
+                "The option 'plugin_processing_mode' is not (yet) supported in uromyces",
+                "Invalid booking method 'INVALID'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_incremental() {
+        let dir = std::env::temp_dir().join(format!("uromyces-parse-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.beancount");
+        let included = dir.join("included.beancount");
+        fs::write(
+            &main,
+            "include \"included.beancount\"\n2020-01-01 open Assets:Cash\n",
+        )
+        .unwrap();
+        fs::write(&included, "2020-01-01 open Assets:Bank\n").unwrap();
+        let main_path: AbsoluteUTF8Path = main.as_path().try_into().unwrap();
+        let accounts = |ledger: &Ledger| {
+            let mut accounts = ledger
+                .entries
+                .iter()
+                .flat_map(Entry::accounts)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            accounts.sort();
+            accounts
+        };
+
+        let mut cache = ParseCache::new();
+        let ledger = load_incremental(main_path.clone(), &mut cache);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(accounts(&ledger), ["Assets:Bank", "Assets:Cash"]);
+        assert_eq!(ledger.entries, load(main_path.clone()).entries);
+
+        // Files with an unchanged modification time are not parsed again.
+        let mtime = fs::metadata(&included).unwrap().modified().unwrap();
+        fs::write(&included, "2020-01-01 open Assets:Other\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&included)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let ledger = load_incremental(main_path.clone(), &mut cache);
+        assert_eq!(accounts(&ledger), ["Assets:Bank", "Assets:Cash"]);
+
+        // But changed files are.
+        fs::File::options()
+            .write(true)
+            .open(&included)
+            .unwrap()
+            .set_modified(mtime + Duration::from_secs(1))
+            .unwrap();
+        let ledger = load_incremental(main_path.clone(), &mut cache);
+        assert_eq!(accounts(&ledger), ["Assets:Cash", "Assets:Other"]);
+
+        // Files that are no longer included are removed from the cache.
+        fs::write(&main, "2020-01-01 open Assets:Cash\n").unwrap();
+        let ledger = load_incremental(main_path, &mut cache);
+        assert_eq!(accounts(&ledger), ["Assets:Cash"]);
+        assert_eq!(cache.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_includes_not_reported_by_default() {
+        let paths_and_results =
+            load_beancount_file(test_ledger_path("duplicate-includes.beancount"), |path| {
+                load_single_beancount_file(path)
+            });
+        assert_eq!(duplicate_include_errors(&paths_and_results).len(), 1);
+        let mut paths_and_results = paths_and_results;
+        paths_and_results[0].result.directives.remove(0);
+        let raw_ledger = combine_files(paths_and_results);
+        assert!(!raw_ledger.options.warn_duplicate_includes);
+        assert!(raw_ledger.errors.is_empty());
+    }
+
+    #[test]
+    fn test_literal_includes() {
+        let ledger = load(test_ledger_path("literal-includes.beancount"));
+        assert_eq!(ledger.includes.len(), 2);
+        assert!(ledger.includes[1].ends_with("export [2024-01].beancount"));
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(
+            ledger
+                .errors
+                .iter()
+                .map(UroError::message)
+                .collect::<Vec<_>>(),
+            [
+                "Include pattern 'literal-includes/missing.beancount' failed: file does not exist",
+                "Include pattern 'literal-includes/*.missing' failed: glob does not match any files"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_file_with_byte_order_mark() {
+        let ledger = load(test_ledger_path("byte-order-mark.beancount"));
+        assert!(ledger.errors.is_empty());
+        assert_eq!(ledger.options.title, "File with a byte order mark");
+        assert_eq!(ledger.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_pushed_tags_and_meta_do_not_leak_into_includes() {
+        let ledger = load(test_ledger_path("pushed-tags.beancount"));
+        assert!(ledger.errors.is_empty());
+        assert_eq!(ledger.entries.len(), 3);
+        for entry in &ledger.entries {
+            let (meta, tags) = match entry {
+                Entry::Open(open) => (&open.meta, &open.tags),
+                Entry::Transaction(txn) => (&txn.meta, &txn.tags),
+                _ => panic!("unexpected entry type"),
+            };
+            let from_parent = meta.filename.ends_with("pushed-tags.beancount");
+            assert_eq!(tags.contains("parent"), from_parent);
+            assert_eq!(meta.get("source").is_some(), from_parent);
+        }
+    }
+
+    #[test]
+    fn test_load_timings() {
+        let start = Instant::now();
+        let mut ledger = load(test_ledger_path("errors-in-includes.beancount"));
+        let elapsed = start.elapsed();
+        let timings = ledger.timings.clone();
+        // the steps run one after another within the load call
+        assert!(timings.total() <= elapsed);
+        assert_eq!(
+            timings.total(),
+            timings.parse + timings.combine + timings.book + timings.plugins
+        );
+        assert_eq!(timings.validations, Duration::ZERO);
+
+        let start = Instant::now();
+        ledger.run_validations();
+        let elapsed = start.elapsed();
+        assert!(ledger.timings.validations <= elapsed);
+        assert_eq!(
+            ledger.timings.total(),
+            timings.total() + ledger.timings.validations
+        );
+        assert_eq!(ledger.timings.parse, timings.parse);
+        assert_eq!(ledger.timings.book, timings.book);
+
+        // a single string without any includes
+        let start = Instant::now();
+        let ledger = load_string(
+            "2020-01-01 open Assets:Cash\n",
+            Filename::new_dummy("string"),
+        );
+        assert!(ledger.timings.total() <= start.elapsed());
+        assert_eq!(ledger.timings.validations, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_load_validated() {
+        let path = test_ledger_path("errors-in-includes.beancount");
+        assert!(load(path.clone()).errors.is_empty());
+        assert_eq!(load_validated(path).errors.len(), 2);
+
+        let string = "2020-01-01 close Assets:Cash\n";
+        assert!(
+            load_string(string, Filename::new_dummy("string"))
+                .errors
+                .is_empty()
+        );
+        let ledger = load_string_validated(string, Filename::new_dummy("string"));
+        assert_eq!(
+            ledger
+                .errors
+                .iter()
+                .map(UroError::message)
+                .collect::<Vec<_>>(),
+            ["Closing unopened account Assets:Cash."]
+        );
+    }
+}
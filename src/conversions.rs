@@ -3,10 +3,32 @@ use crate::types::{Amount, Posting};
 
 /// Get the weight of a posting.
 ///
-/// The weight of the posting is the following:
+/// This is the amount that the posting contributes to the balance of its transaction and is
+/// determined as follows (the first matching rule wins, so a cost takes precedence over a price):
 /// - if the posting has a cost, multiply the units by the cost
 /// - if the posting has a price, multiply the units by the price
 /// - units otherwise
+///
+/// ```
+/// use uromyces::conversions::get_weight;
+/// use uromyces::types::{Entry, Filename};
+///
+/// let ledger = uromyces::load_string(
+///     r#"
+/// 2020-01-01 * "Exchange"
+///   Assets:Cash:USD   10 USD @ 0.9 EUR
+///   Assets:Cash:EUR  -9 EUR
+/// "#,
+///     Filename::new_dummy("string"),
+/// );
+/// let Entry::Transaction(transaction) = &ledger.entries[0] else {
+///     panic!("expected a transaction");
+/// };
+/// let weight = get_weight(&transaction.postings[0]);
+/// assert_eq!(weight.number.to_string(), "9.0");
+/// assert_eq!(weight.currency.to_string(), "EUR");
+/// ```
+#[must_use]
 pub fn get_weight(posting: &Posting) -> Amount {
     if let Some(cost) = &posting.cost {
         Amount::new(cost.number * posting.units.number, cost.currency.clone())
@@ -0,0 +1,104 @@
+//! Compare two lists of entries.
+//!
+//! This is useful to compare the entries of two ledgers, for example the entries that Beancount
+//! and uromyces produce for the same input file.
+
+use hashbrown::HashMap;
+
+use crate::types::{Entry, Filename};
+
+/// A copy of the entry without the source positions of the entry and its postings.
+fn without_positions(entry: &Entry) -> Entry {
+    let mut entry = entry.clone();
+    let meta = entry.meta_mut();
+    meta.filename = Filename::new_dummy("position");
+    meta.lineno = 0;
+    if let Entry::Transaction(transaction) = &mut entry {
+        for posting in &mut transaction.postings {
+            posting.meta.filename = None;
+            posting.meta.lineno = None;
+        }
+    }
+    entry
+}
+
+/// The entries that are only in `a` and the entries that are only in `b`.
+///
+/// Entries are compared by equality, entries that occur multiple times need to occur just as many
+/// times in the other list. With `ignore_positions`, the filenames and line numbers of the entries
+/// and their postings are not considered. The order of the entries is kept.
+#[must_use]
+pub fn diff_entries(a: &[Entry], b: &[Entry], ignore_positions: bool) -> (Vec<Entry>, Vec<Entry>) {
+    let key = |entry: &Entry| {
+        if ignore_positions {
+            without_positions(entry)
+        } else {
+            entry.clone()
+        }
+    };
+    let only_in = |entries: &[Entry], other: &[Entry]| {
+        let mut counts: HashMap<Entry, usize> = HashMap::new();
+        for entry in other {
+            *counts.entry(key(entry)).or_default() += 1;
+        }
+        entries
+            .iter()
+            .filter(|entry| match counts.get_mut(&key(entry)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    (only_in(a, b), only_in(b, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_string;
+
+    #[test]
+    fn test_diff_entries() {
+        let input = r#"
+2020-01-01 open Assets:Cash
+2020-01-01 open Expenses:Food
+
+2020-01-02 * "Bakery" "Bread"
+  Assets:Cash     -5 USD
+  Expenses:Food
+"#;
+        let a = load_string(input, Filename::new_dummy("a")).entries;
+        let b = load_string(
+            &format!("\n\n2020-01-03 event \"location\" \"Berlin\"{input}"),
+            Filename::new_dummy("b"),
+        )
+        .entries;
+        let dates = |entries: &[Entry]| {
+            entries
+                .iter()
+                .map(|e| e.date().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let (only_a, only_b) = diff_entries(&a, &a, false);
+        assert!(only_a.is_empty() && only_b.is_empty());
+
+        // all entries differ in their filename and line numbers
+        let (only_a, only_b) = diff_entries(&a, &b, false);
+        assert_eq!(only_a, a);
+        assert_eq!(only_b, b);
+
+        let (only_a, only_b) = diff_entries(&a, &b, true);
+        assert!(only_a.is_empty());
+        assert_eq!(dates(&only_b), ["2020-01-03"]);
+
+        // duplicates are only matched once
+        let (only_a, only_b) = diff_entries(&[a.clone(), a.clone()].concat(), &a, false);
+        assert_eq!(only_a, a);
+        assert!(only_b.is_empty());
+    }
+}
@@ -17,7 +17,8 @@ const MAX_PRECISION_INDEX: usize = MAX_PRECISION + 1;
 
 /// Stats about the used precisions for a currency.
 #[derive(Clone, Debug, PartialEq, Eq)]
-struct PrecisionStats {
+#[pyclass(frozen, skip_from_py_object, module = "uromyces")]
+pub struct PrecisionStats {
     has_sign: bool,
     precisions: [u32; MAX_PRECISION_INDEX],
 }
@@ -26,7 +27,7 @@ impl PrecisionStats {
     #![allow(clippy::cast_possible_truncation)]
 
     #[must_use]
-    pub fn new() -> Self {
+    fn new() -> Self {
         Self {
             has_sign: false,
             precisions: [0; MAX_PRECISION_INDEX],
@@ -34,7 +35,8 @@ impl PrecisionStats {
     }
 
     /// Get the maximum number of used decimal digits.
-    fn get_max(&self) -> u8 {
+    #[must_use]
+    pub fn get_max(&self) -> u8 {
         let mut max_index = 0;
         for (index, count) in self.precisions.iter().enumerate() {
             if count > &0 {
@@ -45,7 +47,8 @@ impl PrecisionStats {
     }
 
     /// Get the most common number of decimal digits.
-    fn get_common(&self) -> u8 {
+    #[must_use]
+    pub fn get_common(&self) -> u8 {
         let mut max_index = 0;
         let mut max_count = 0;
         for (index, count) in self.precisions.iter().enumerate() {
@@ -57,6 +60,18 @@ impl PrecisionStats {
         max_index as u8
     }
 
+    /// Whether any of the numbers was negative.
+    #[must_use]
+    pub fn has_sign(&self) -> bool {
+        self.has_sign
+    }
+
+    /// The number of times that each number of decimal digits (the index) was used.
+    #[must_use]
+    pub fn counts(&self) -> &[u32] {
+        &self.precisions
+    }
+
     /// Update stats with the given number.
     fn update(&mut self, dec: Decimal) {
         self.has_sign = self.has_sign || !dec.is_sign_positive();
@@ -66,8 +81,54 @@ impl PrecisionStats {
     }
 }
 
-#[derive(Clone, Debug)]
-struct DisplayPrecisionsStats {
+#[pymethods]
+impl PrecisionStats {
+    #[getter(has_sign)]
+    fn py_has_sign(&self) -> bool {
+        self.has_sign
+    }
+    #[getter]
+    fn max(&self) -> u8 {
+        self.get_max()
+    }
+    #[getter]
+    fn common(&self) -> u8 {
+        self.get_common()
+    }
+    /// The counts for all used numbers of decimal digits.
+    #[getter(counts)]
+    fn py_counts(&self) -> BTreeMap<usize, u32> {
+        self.precisions
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(scale, count)| (scale, *count))
+            .collect()
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "PrecisionStats(has_sign={}, max={}, common={}, counts={:?})",
+            if self.has_sign { "True" } else { "False" },
+            self.get_max(),
+            self.get_common(),
+            self.py_counts()
+        )
+    }
+}
+
+impl<'py> IntoPyObject<'py> for &PrecisionStats {
+    type Target = PrecisionStats;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        self.clone().into_pyobject(py)
+    }
+}
+
+/// The full precision stats for all currencies (from which the [`DisplayPrecisions`] are derived).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisplayPrecisionsStats {
     map: HashMap<Currency, PrecisionStats>,
 }
 
@@ -90,8 +151,8 @@ impl Precisions {
     }
 }
 
-impl From<PrecisionStats> for Precisions {
-    fn from(value: PrecisionStats) -> Self {
+impl From<&PrecisionStats> for Precisions {
+    fn from(value: &PrecisionStats) -> Self {
         Precisions {
             has_sign: value.has_sign,
             max: value.get_max(),
@@ -121,13 +182,29 @@ impl DisplayPrecisions {
     /// Create precision stats and summarise them to obtain the most common and max precisions.
     #[must_use]
     pub fn from_raw_entries(entries: &[RawEntry]) -> Self {
-        DisplayPrecisionsStats::from_raw_entries(entries).into()
+        (&DisplayPrecisionsStats::from_raw_entries(entries)).into()
+    }
+
+    /// Round the number to the most common precision of the currency.
+    ///
+    /// Numbers for currencies without any recorded precisions are returned unchanged.
+    #[must_use]
+    pub fn quantize(&self, number: Decimal, currency: &Currency) -> Decimal {
+        self.0
+            .get(currency)
+            .map_or(number, |p| number.quantize(p.common.into()))
     }
 }
 
-impl From<DisplayPrecisionsStats> for DisplayPrecisions {
-    fn from(value: DisplayPrecisionsStats) -> Self {
-        Self(value.map.into_iter().map(|(c, p)| (c, p.into())).collect())
+impl From<&DisplayPrecisionsStats> for DisplayPrecisions {
+    fn from(value: &DisplayPrecisionsStats) -> Self {
+        Self(
+            value
+                .map
+                .iter()
+                .map(|(c, p)| (c.clone(), p.into()))
+                .collect(),
+        )
     }
 }
 
@@ -139,6 +216,18 @@ impl DisplayPrecisionsStats {
         }
     }
 
+    /// Get the stats for the given currency.
+    #[must_use]
+    pub fn get(&self, currency: &Currency) -> Option<&PrecisionStats> {
+        self.map.get(currency)
+    }
+
+    /// The stats for all currencies, sorted by currency.
+    #[must_use]
+    pub fn sorted(&self) -> BTreeMap<&Currency, &PrecisionStats> {
+        self.map.iter().collect()
+    }
+
     fn update(&mut self, number: Decimal, currency: &Currency) {
         self.map
             .raw_entry_mut()
@@ -165,6 +254,7 @@ impl DisplayPrecisionsStats {
         }
     }
 
+    /// Collect the precisions of all numbers in the given entries.
     #[must_use]
     pub fn from_raw_entries(entries: &[RawEntry]) -> Self {
         let mut res = Self::new();
@@ -258,6 +348,11 @@ mod tests {
         p.update_from_amount(&c_eur2);
         p.update_from_amount(&c_eur2);
         let eur: Currency = "EUR".into();
-        assert_eq!(p.map.get(&eur).unwrap().get_common(), 2);
+        let stats = p.get(&eur).unwrap();
+        assert_eq!(stats.get_common(), 2);
+        assert_eq!(&stats.counts()[..3], [2, 0, 3]);
+
+        let precisions = DisplayPrecisions::from(&p);
+        assert_eq!(precisions.quantize(d("1.2345"), &eur), d("1.23"));
     }
 }
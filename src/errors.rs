@@ -9,10 +9,13 @@ use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyMapping};
 use serde::{Deserialize, Serialize};
 
-use crate::types::{Entry, Filename, LineNumber};
+use crate::types::{Entry, Filename, LineNumber, SourceSpan};
 
 /// This is a user-surfaceable error.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Like for [`EntryMeta`](crate::types::EntryMeta), the span is not considered for equality since
+/// it is not serialized.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[pyclass(frozen, eq, module = "uromyces", skip_from_py_object)]
 pub struct UroError {
     /// The file that this error occured in (if it can be attributed).
@@ -24,9 +27,27 @@ pub struct UroError {
     /// The error message.
     #[pyo3(get)]
     message: String,
+    /// A machine-readable kind of the error (if known), like `insufficient_lots`.
+    #[pyo3(get)]
+    kind: Option<String>,
     entry: Option<Box<Entry>>,
+    /// The position of the erroring source text (only for errors from parsing).
+    #[serde(skip)]
+    span: Option<SourceSpan>,
+}
+
+impl PartialEq for UroError {
+    fn eq(&self, other: &Self) -> bool {
+        self.filename == other.filename
+            && self.lineno == other.lineno
+            && self.message == other.message
+            && self.kind == other.kind
+            && self.entry == other.entry
+    }
 }
 
+impl Eq for UroError {}
+
 #[pymethods]
 impl UroError {
     /// Convert this to a Python dictionary like the `meta` attribute of Beancount entries.
@@ -47,6 +68,12 @@ impl UroError {
     fn entry(&self) -> Option<Entry> {
         self.entry.as_ref().map(|b| *b.clone())
     }
+    /// The position of the erroring source text as `(start_line, start_col, end_line, end_col)`.
+    #[getter(span)]
+    fn py_span(&self) -> Option<(LineNumber, u32, LineNumber, u32)> {
+        self.span
+            .map(|s| (s.start_line, s.start_col, s.end_line, s.end_col))
+    }
 }
 
 // Turn a Python object into a [`UroError`].
@@ -97,6 +124,30 @@ impl UroError {
         &self.message
     }
 
+    /// Get the machine-readable kind of the error (if known).
+    #[must_use]
+    pub fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// Get the file that this error occured in (if it can be attributed).
+    #[must_use]
+    pub fn filename(&self) -> Option<&Filename> {
+        self.filename.as_ref()
+    }
+
+    /// Get the line that this error occured on (if it can be attributed).
+    #[must_use]
+    pub fn lineno(&self) -> Option<LineNumber> {
+        self.lineno
+    }
+
+    /// Get the position of the source text that this error occured in (if known).
+    #[must_use]
+    pub fn span(&self) -> Option<&SourceSpan> {
+        self.span.as_ref()
+    }
+
     /// Create an error (without filename and line number).
     #[must_use]
     pub(crate) fn new<S: AsRef<str>>(message: S) -> Self {
@@ -104,10 +155,19 @@ impl UroError {
             filename: None,
             lineno: None,
             message: message.as_ref().to_string(),
+            kind: None,
             entry: None,
+            span: None,
         }
     }
 
+    /// Set the machine-readable kind of the error.
+    #[must_use]
+    pub(crate) fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_owned());
+        self
+    }
+
     /// Add a filename for the file that this error occurs in.
     #[must_use]
     pub(crate) fn with_filename(mut self, filename: Filename) -> Self {
@@ -123,6 +183,13 @@ impl UroError {
         self
     }
 
+    /// Add the position of the source text that this error occurs in.
+    #[must_use]
+    pub(crate) fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
     /// Add a reference to the entry that this error occurs in.
     #[must_use]
     pub(crate) fn with_entry<E: Clone + Into<Entry>>(mut self, entry: &E) -> Self {
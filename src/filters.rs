@@ -0,0 +1,123 @@
+//! Filter entries by regular expressions.
+//!
+//! This is much faster than filtering the entries in Python, since the regular expressions are
+//! only compiled once and the entries do not need to be converted to Python objects.
+
+use regex::Regex;
+
+use crate::types::{Entry, Transaction};
+
+/// A filter for entries, all the given regular expressions need to match.
+///
+/// Like Python's `re.search`, the regular expressions may match anywhere in the string.
+#[derive(Debug, Default)]
+pub struct EntryFilter {
+    /// Match the payee of transactions (transactions without a payee never match).
+    pub payee: Option<Regex>,
+    /// Match the narration of transactions.
+    pub narration: Option<Regex>,
+    /// Match any of the accounts of an entry.
+    pub account: Option<Regex>,
+}
+
+impl EntryFilter {
+    /// Whether the filter has any transaction-specific regular expressions.
+    pub(crate) fn is_for_transactions(&self) -> bool {
+        self.payee.is_some() || self.narration.is_some()
+    }
+
+    /// Whether the filter has a regular expression for accounts.
+    pub(crate) fn is_for_accounts(&self) -> bool {
+        self.account.is_some()
+    }
+
+    /// Check whether the transaction matches this filter.
+    #[must_use]
+    pub fn matches_transaction(&self, txn: &Transaction) -> bool {
+        self.payee
+            .as_ref()
+            .is_none_or(|payee| txn.payee.as_ref().is_some_and(|p| payee.is_match(p)))
+            && self
+                .narration
+                .as_ref()
+                .is_none_or(|narration| narration.is_match(&txn.narration))
+            && self
+                .account
+                .as_ref()
+                .is_none_or(|account| txn.postings.iter().any(|p| account.is_match(&p.account)))
+    }
+
+    /// Check whether the entry matches this filter.
+    #[must_use]
+    pub fn matches(&self, entry: &Entry) -> bool {
+        match entry {
+            Entry::Transaction(txn) => self.matches_transaction(txn),
+            _ if self.is_for_transactions() => false,
+            _ => self
+                .account
+                .as_ref()
+                .is_none_or(|account| entry.accounts().iter().any(|a| account.is_match(a))),
+        }
+    }
+
+    /// Filter the entries, keeping the ones that match.
+    #[must_use]
+    pub fn filter(&self, entries: &[Entry]) -> Vec<Entry> {
+        entries
+            .iter()
+            .filter(|e| self.matches(e))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_string;
+    use crate::types::Filename;
+
+    #[test]
+    fn test_filter_entries() {
+        let ledger = load_string(
+            r#"
+2020-01-01 open Assets:Cash
+2020-01-01 open Expenses:Food
+2020-01-01 open Expenses:Rent
+
+2020-01-02 * "Bakery" "Bread"
+  Assets:Cash     -5 USD
+  Expenses:Food
+
+2020-01-03 * "Rent for January"
+  Assets:Cash   -500 USD
+  Expenses:Rent
+
+2020-01-04 note Expenses:Food "Eat less bread"
+"#,
+            Filename::new_dummy("string"),
+        );
+        let filter = |payee: Option<&str>, narration: Option<&str>, account: Option<&str>| {
+            let filter = EntryFilter {
+                payee: payee.map(|r| Regex::new(r).unwrap()),
+                narration: narration.map(|r| Regex::new(r).unwrap()),
+                account: account.map(|r| Regex::new(r).unwrap()),
+            };
+            filter
+                .filter(&ledger.entries)
+                .iter()
+                .map(|e| e.date().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(filter(None, None, None).len(), 6);
+        assert_eq!(filter(Some("Bak"), None, None), ["2020-01-02"]);
+        assert_eq!(filter(None, Some("(?i)rent"), None), ["2020-01-03"]);
+        assert_eq!(filter(None, Some("."), Some("Food")), ["2020-01-02"]);
+        assert_eq!(
+            filter(None, None, Some("^Expenses:Food$")),
+            ["2020-01-01", "2020-01-02", "2020-01-04"]
+        );
+        assert!(filter(Some("Bakery"), Some("Rent"), None).is_empty());
+    }
+}
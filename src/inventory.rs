@@ -296,6 +296,18 @@ impl Inventory {
         self.add_to_key(&key, number)
     }
 
+    /// The units of this inventory, summing up all positions of a currency regardless of cost.
+    #[must_use]
+    pub fn units_only(&self) -> Self {
+        self.iter().map(|pos| pos.units()).collect()
+    }
+
+    /// The total cost of this inventory, in the cost currencies (positions without cost are kept).
+    #[must_use]
+    pub fn reduce_to_cost(&self) -> Self {
+        self.iter().map(|pos| pos.total_cost()).collect()
+    }
+
     /// Check whether the given amount could reduce this inventory (without checking costs)
     #[must_use]
     pub fn is_reduced_by(&self, amount: &Amount) -> bool {
@@ -395,4 +407,27 @@ mod tests {
         assert!(!inv.is_reduced_by(&a("2 USD")));
         assert!(inv.is_reduced_by(&a("-2 USD")));
     }
+
+    #[test]
+    fn test_inventory_units_only_and_reduce_to_cost() {
+        let cost = |number: &str| Cost::new(d(number), "USD".into(), MIN_DATE, None);
+        let mut inv = Inventory::new();
+        inv.add_position(&(a("5 HOOL"), cost("10")));
+        inv.add_position(&(a("5 HOOL"), cost("12")));
+        inv.add_position(&a("2.00 EUR"));
+        inv.add_position(&a("-20 USD"));
+        assert_eq!(inv.iter().count(), 4);
+
+        let units = inv.units_only();
+        assert_eq!(units.get(&"HOOL".into(), None), Some(d("10")));
+        assert_eq!(units.get(&"EUR".into(), None), Some(d("2.00")));
+        assert_eq!(units.get(&"USD".into(), None), Some(d("-20")));
+        assert!(units.cost_currencies().is_empty());
+        assert_eq!(units.iter().count(), 3);
+
+        let at_cost = inv.reduce_to_cost();
+        assert_eq!(at_cost.get(&"USD".into(), None), Some(d("90")));
+        assert_eq!(at_cost.get(&"EUR".into(), None), Some(d("2.00")));
+        assert_eq!(at_cost.iter().count(), 2);
+    }
 }
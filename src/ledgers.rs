@@ -1,13 +1,24 @@
 //!  Ledgers encompass all the data from parsed and booked input Beancount journals.
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use indexmap::{IndexMap, IndexSet};
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
 use serde::{Deserialize, Serialize};
 
+use crate::display_precision::PrecisionStats;
 use crate::errors::UroError;
 use crate::options::BeancountOptions;
 #[cfg(test)]
 use crate::parse::ParsedFile;
-use crate::plugins::{run_named_plugin, run_validations};
-use crate::types::{Entry, Filename, Plugin, RawEntry};
+use crate::plugins::{check_assertions, run_named_plugin, run_validations};
+use crate::prices::PriceMap;
+use crate::types::{
+    Account, Amount, Balance, Booking, Currency, Date, Decimal, Entry, EntryMeta, Filename, Plugin,
+    RawEntry, TagsLinks,
+};
+use crate::util::timer::SimpleTimer;
 
 /// The result of parsing a Beancount file and all its includes.
 #[derive(Debug, Clone)]
@@ -56,6 +67,84 @@ impl RawLedger {
     }
 }
 
+/// The time that the different steps of loading a ledger took.
+///
+/// Plugins and validations that are run on the ledger after loading are added up.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[pyclass(frozen, skip_from_py_object, module = "uromyces")]
+pub struct LoadTimings {
+    /// Reading and parsing the main file and all includes.
+    #[pyo3(get)]
+    pub parse: Duration,
+    /// Combining the parsed files (and sorting the entries).
+    #[pyo3(get)]
+    pub combine: Duration,
+    /// Booking the entries.
+    #[pyo3(get)]
+    pub book: Duration,
+    /// Running the plugins (the built-in ones run on load and all named ones).
+    #[pyo3(get)]
+    pub plugins: Duration,
+    /// Running the validations.
+    #[pyo3(get)]
+    pub validations: Duration,
+}
+
+impl LoadTimings {
+    /// The total time of all steps.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.parse + self.combine + self.book + self.plugins + self.validations
+    }
+}
+
+#[pymethods]
+impl LoadTimings {
+    #[getter]
+    #[pyo3(name = "total")]
+    fn py_total(&self) -> Duration {
+        self.total()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LoadTimings(parse={:?}, combine={:?}, book={:?}, plugins={:?}, validations={:?})",
+            self.parse, self.combine, self.book, self.plugins, self.validations
+        )
+    }
+}
+
+/// An account with the data from its open and close entries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[pyclass(frozen, get_all, skip_from_py_object, module = "uromyces")]
+pub struct AccountInfo {
+    /// The account name.
+    pub account: Account,
+    /// The date of the open entry.
+    pub open_date: Date,
+    /// The date of the close entry (if the account is closed).
+    pub close_date: Option<Date>,
+    /// The currency constraints of the open entry.
+    pub currencies: Vec<Currency>,
+    /// The booking method of the open entry.
+    pub booking: Option<Booking>,
+    /// The metadata of the open entry.
+    pub meta: EntryMeta,
+}
+
+#[pymethods]
+impl AccountInfo {
+    fn __repr__(&self) -> String {
+        let close_date = self
+            .close_date
+            .map_or_else(|| "None".to_owned(), |d| d.to_string());
+        format!(
+            "AccountInfo(account='{}', open_date={}, close_date={close_date})",
+            self.account, self.open_date
+        )
+    }
+}
+
 /// The result of parsing a Beancount file and all its includes and running booking.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[pyclass(skip_from_py_object, module = "uromyces")]
@@ -78,6 +167,10 @@ pub struct Ledger {
     /// Plugins (with optional config)
     #[pyo3(get)]
     pub plugins: Vec<Plugin>,
+    /// The time that the steps of loading this ledger took.
+    #[pyo3(get)]
+    #[serde(skip)]
+    pub timings: LoadTimings,
 }
 
 impl Ledger {
@@ -90,20 +183,171 @@ impl Ledger {
             options: raw_ledger.options.clone(),
             includes: raw_ledger.includes.clone(),
             plugins: raw_ledger.plugins.clone(),
+            timings: LoadTimings::default(),
         }
     }
 
     /// Run the validation plugins (and add any errors).
     pub fn run_validations(&mut self) {
+        let mut t = SimpleTimer::new();
         self.errors.append(&mut run_validations(self));
+        self.timings.validations += t.lap();
+    }
+
+    /// Check the given balance assertions (which need not be part of the ledger).
+    ///
+    /// Returns an error for each failing assertion and `None` for each passing one.
+    #[must_use]
+    pub fn assert_balances(&self, assertions: &[Balance]) -> Vec<Option<UroError>> {
+        check_assertions(self, assertions)
+    }
+
+    /// All currencies used in the ledger, in the order that they are first seen in the entries.
+    ///
+    /// This includes the currencies of amounts, costs, prices, open constraints and commodities.
+    #[must_use]
+    pub fn currencies(&self) -> IndexSet<&Currency> {
+        self.entries.iter().flat_map(Entry::currencies).collect()
+    }
+
+    /// All tags used in the entries of the ledger, sorted.
+    #[must_use]
+    pub fn all_tags(&self) -> BTreeSet<&str> {
+        self.entries.iter().flat_map(|e| e.tags().iter()).collect()
+    }
+
+    /// All links used in the entries of the ledger, sorted.
+    #[must_use]
+    pub fn all_links(&self) -> BTreeSet<&str> {
+        self.entries.iter().flat_map(|e| e.links().iter()).collect()
+    }
+
+    /// The metadata of the commodity directive for the given currency.
+    ///
+    /// If there are multiple commodity directives for the currency, the first one is used.
+    #[must_use]
+    pub fn commodity_meta(&self, currency: &Currency) -> Option<&EntryMeta> {
+        self.entries
+            .iter()
+            .filter_map(Entry::as_commodity)
+            .find(|c| &c.currency == currency)
+            .map(|c| &c.meta)
+    }
+
+    /// Group the errors by the file that they occured in.
+    ///
+    /// The files are in the order that they are first seen in the errors. Errors that cannot be
+    /// attributed to a file are grouped under `None`.
+    #[must_use]
+    pub fn errors_by_file(&self) -> IndexMap<Option<&Filename>, Vec<&UroError>> {
+        let mut groups: IndexMap<_, Vec<_>> = IndexMap::new();
+        for error in &self.errors {
+            groups.entry(error.filename()).or_default().push(error);
+        }
+        groups
+    }
+
+    /// The errors sorted by their position, by filename and then line number.
+    ///
+    /// Errors without a filename come last, errors without a line number first in their file.
+    /// Otherwise, the order of the errors is kept.
+    #[must_use]
+    pub fn errors_sorted(&self) -> Vec<&UroError> {
+        let mut errors = self.errors.iter().collect::<Vec<_>>();
+        errors.sort_by_key(|e| {
+            (
+                e.filename().is_none(),
+                e.filename().map(|f| &**f),
+                e.lineno(),
+            )
+        });
+        errors
+    }
+
+    /// All opened accounts (in the order of their open entries), with the dates they were opened
+    /// and closed on.
+    ///
+    /// If an account is opened or closed multiple times, the first entry is used.
+    #[must_use]
+    pub fn accounts(&self) -> Vec<AccountInfo> {
+        let mut accounts: IndexMap<&Account, AccountInfo> = IndexMap::new();
+        for entry in &self.entries {
+            match entry {
+                Entry::Open(open) => {
+                    accounts
+                        .entry(&open.account)
+                        .or_insert_with(|| AccountInfo {
+                            account: open.account.clone(),
+                            open_date: open.date,
+                            close_date: None,
+                            currencies: open.currencies.clone(),
+                            booking: open.booking,
+                            meta: open.meta.clone(),
+                        });
+                }
+                Entry::Close(close) => {
+                    if let Some(info) = accounts.get_mut(&close.account) {
+                        info.close_date.get_or_insert(close.date);
+                    }
+                }
+                _ => {}
+            }
+        }
+        accounts.into_values().collect()
+    }
+
+    /// Build a price map from all the price entries in the ledger.
+    #[must_use]
+    pub fn get_prices(&self) -> PriceMap {
+        PriceMap::from_entries(&self.entries)
+    }
+
+    /// A copy of this ledger with only the entries in the given date range.
+    ///
+    /// The begin date is inclusive, the end date exclusive. Unlike [`crate::summarize::clamp`],
+    /// this does not summarize the entries before the range.
+    #[must_use]
+    pub fn filter_by_date(&self, begin: Option<Date>, end: Option<Date>) -> Self {
+        debug_assert!(self.entries.is_sorted());
+        let start_index = begin.map_or(0, |begin| {
+            self.entries.partition_point(|e| e.date() < begin)
+        });
+        let end_index = end.map_or(self.entries.len(), |end| {
+            self.entries.partition_point(|e| e.date() < end)
+        });
+        Self {
+            filename: self.filename.clone(),
+            entries: self.entries[start_index..end_index.max(start_index)].to_vec(),
+            errors: self.errors.clone(),
+            options: self.options.clone(),
+            includes: self.includes.clone(),
+            plugins: self.plugins.clone(),
+            timings: self.timings.clone(),
+        }
+    }
+
+    /// Insert an entry at its position in the sorted entries.
+    ///
+    /// The entry is inserted after all entries that compare equal to it, so this results in the
+    /// same order as appending it and sorting all entries (the sort is stable).
+    pub fn insert_entry(&mut self, entry: Entry) {
+        debug_assert!(self.entries.is_sorted());
+        let index = self.entries.partition_point(|e| e <= &entry);
+        self.entries.insert(index, entry);
     }
 }
 
 #[pymethods]
 impl Ledger {
-    /// Run the plugin with the given name (returns true if it exists)
-    fn run_plugin(&mut self, plugin: &str, py: Python<'_>) -> bool {
-        py.detach(|| run_named_plugin(self, plugin))
+    /// Run the plugin with the given name and config (returns true if it exists)
+    #[pyo3(signature = (plugin, config=None))]
+    fn run_plugin(&mut self, plugin: &str, config: Option<&str>, py: Python<'_>) -> bool {
+        py.detach(|| {
+            let mut t = SimpleTimer::new();
+            let found = run_named_plugin(self, plugin, config);
+            self.timings.plugins += t.lap();
+            found
+        })
     }
 
     #[pyo3(name = "run_validations")]
@@ -111,13 +355,180 @@ impl Ledger {
         py.detach(|| self.run_validations());
     }
 
+    /// Check a list of (account, date, amount) balance assertions.
+    #[pyo3(name = "assert_balances")]
+    fn py_assert_balances(
+        &self,
+        assertions: Vec<(Account, Date, Amount)>,
+        py: Python<'_>,
+    ) -> Vec<Option<UroError>> {
+        let assertions = assertions
+            .into_iter()
+            .map(|(account, date, amount)| Balance {
+                meta: EntryMeta::empty(self.filename.clone(), 0),
+                date,
+                tags: TagsLinks::default(),
+                links: TagsLinks::default(),
+                account,
+                amount,
+                tolerance: None,
+            })
+            .collect::<Vec<_>>();
+        py.detach(|| self.assert_balances(&assertions))
+    }
+
+    /// All currencies used in the ledger, in the order that they are first seen.
+    #[pyo3(name = "currencies")]
+    fn py_currencies(&self) -> Vec<&Currency> {
+        self.currencies().into_iter().collect()
+    }
+
+    /// All tags used in the ledger, sorted.
+    #[pyo3(name = "all_tags")]
+    fn py_all_tags(&self) -> Vec<&str> {
+        self.all_tags().into_iter().collect()
+    }
+
+    /// All links used in the ledger, sorted.
+    #[pyo3(name = "all_links")]
+    fn py_all_links(&self) -> Vec<&str> {
+        self.all_links().into_iter().collect()
+    }
+
+    /// The metadata of the commodity directive for the given currency.
+    #[pyo3(name = "commodity_meta")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_commodity_meta(&self, currency: Currency) -> Option<EntryMeta> {
+        self.commodity_meta(&currency).cloned()
+    }
+
+    #[pyo3(name = "accounts")]
+    fn py_accounts(&self) -> Vec<AccountInfo> {
+        self.accounts()
+    }
+
+    /// A copy of this ledger with only the entries from `begin` (inclusive) to `end` (exclusive).
+    #[pyo3(signature = (begin=None, end=None))]
+    fn filtered(&self, begin: Option<Date>, end: Option<Date>, py: Python<'_>) -> Self {
+        py.detach(|| self.filter_by_date(begin, end))
+    }
+
+    /// The full stats of the used precisions per currency (to debug the display precisions).
+    fn display_precisions_stats(&self) -> BTreeMap<&Currency, &PrecisionStats> {
+        self.options.display_precisions_stats.sorted()
+    }
+
+    #[pyo3(name = "get_prices")]
+    fn py_get_prices(&self, py: Python<'_>) -> PriceMap {
+        py.detach(|| self.get_prices())
+    }
+
+    /// Get the latest price of `base` in `quote` on or before the given date.
+    ///
+    /// This builds the full price map, use `get_prices` for repeated lookups.
+    #[allow(clippy::needless_pass_by_value)]
+    fn price_at(&self, base: Currency, quote: Currency, date: Date) -> Option<Decimal> {
+        self.get_prices().get_rate(&base, &quote, date)
+    }
+
+    /// Group the errors of this ledger by filename (`None` for errors without a filename).
+    #[pyo3(name = "errors_by_file")]
+    fn py_errors_by_file<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let groups = PyDict::new(py);
+        for (filename, errors) in self.errors_by_file() {
+            groups.set_item(filename, PyList::new(py, errors.into_iter().cloned())?)?;
+        }
+        Ok(groups)
+    }
+
+    /// The errors of this ledger sorted by filename and line number (errors without one last).
+    #[pyo3(name = "errors_sorted")]
+    fn py_errors_sorted(&self) -> Vec<UroError> {
+        self.errors_sorted().into_iter().cloned().collect()
+    }
+
+    /// Group the transactions of this ledger by the key that the given function returns for them.
+    fn group_transactions_by<'py>(
+        &self,
+        key_fn: &Bound<'py, PyAny>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let groups = PyDict::new(py);
+        for transaction in self.entries.iter().filter_map(Entry::as_transaction) {
+            let transaction = Bound::new(py, transaction.clone())?;
+            let key = key_fn.call1((&transaction,))?;
+            if let Some(group) = groups.get_item(&key)? {
+                group.cast::<PyList>()?.append(transaction)?;
+            } else {
+                groups.set_item(key, PyList::new(py, [transaction])?)?;
+            }
+        }
+        Ok(groups)
+    }
+
+    fn __repr__(&self) -> String {
+        let title = if self.options.title.is_empty() {
+            "(untitled)".to_owned()
+        } else {
+            format!("'{}'", self.options.title)
+        };
+        let dates = match (self.entries.first(), self.entries.last()) {
+            (Some(first), Some(last)) => format!(", {} to {}", first.date(), last.date()),
+            _ => String::new(),
+        };
+        format!(
+            "<Ledger {title}: {} entries, {} errors{dates}>",
+            self.entries.len(),
+            self.errors.len()
+        )
+    }
+
     /// Replace the entries of this ledger.
     fn replace_entries(&mut self, entries: Vec<Entry>) {
         self.entries = entries;
     }
 
+    /// Insert an entry at its position in the sorted entries of this ledger.
+    #[pyo3(name = "insert_entry")]
+    fn py_insert_entry(&mut self, entry: Entry) {
+        self.insert_entry(entry);
+    }
+
     /// Append some error (from the Python side).
     fn add_error(&mut self, error: UroError) {
         self.errors.push(error);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::load_string;
+
+    use super::*;
+
+    #[test]
+    fn test_insert_entry() {
+        let mut ledger = load_string(
+            r#"
+2020-01-01 open Assets:Cash
+2020-01-02 * "Before"
+  Assets:Cash  1 USD
+  Assets:Cash -1 USD
+2020-01-03 * "After"
+  Assets:Cash  1 USD
+  Assets:Cash -1 USD
+"#,
+            Filename::new_dummy("string"),
+        );
+        let other = load_string("2020-01-02 open Assets:Bank", Filename::new_dummy("string"));
+        let open = other.entries[0].clone();
+
+        let mut expected = ledger.entries.clone();
+        expected.push(open.clone());
+        expected.sort();
+
+        ledger.insert_entry(open.clone());
+        assert_eq!(ledger.entries, expected);
+        assert_eq!(ledger.entries[1], open);
+    }
+}
@@ -14,15 +14,18 @@ use pyo3::prelude::*;
 
 pub mod booking;
 mod combine;
-mod conversions;
+pub mod conversions;
+pub mod diff;
 pub mod display_precision;
 pub mod errors;
+pub mod filters;
 pub mod inventory;
 mod ledgers;
 mod macros;
 pub mod options;
 pub mod parse;
 mod plugins;
+pub mod prices;
 pub mod summarize;
 #[cfg(test)]
 mod test_utils;
@@ -30,8 +33,10 @@ mod tolerances;
 pub mod types;
 mod util;
 
-pub use combine::{load, load_string};
-pub use ledgers::Ledger;
+pub use combine::{
+    ParseCache, load, load_incremental, load_string, load_string_validated, load_validated,
+};
+pub use ledgers::{AccountInfo, Ledger, LoadTimings};
 
 /// [pymodule] The uromyces.uromyces Python extension module.
 #[pymodule(name = "_uromyces")]
@@ -44,18 +49,22 @@ mod uromyces {
 
     // Base types
     #[pymodule_export]
-    use crate::Ledger;
-    #[pymodule_export]
     use crate::types::{
         Amount, Booking, Cost, CostSpec, CustomValue, EntryMeta, Posting, PostingMeta, RawAmount,
-        RawPosting,
+        RawPosting, RootAccounts, SourceSpan,
     };
+    #[pymodule_export]
+    use crate::{AccountInfo, Ledger, LoadTimings};
     // Entry types
     #[pymodule_export]
-    use crate::display_precision::Precisions;
+    use crate::display_precision::{PrecisionStats, Precisions};
     #[pymodule_export]
     use crate::options::BeancountOptions;
     #[pymodule_export]
+    use crate::prices::PriceMap;
+    #[pymodule_export]
+    use crate::tolerances::Tolerances;
+    #[pymodule_export]
     use crate::types::{
         Balance, Close, Commodity, Custom, Document, Event, Note, Open, Pad, Price, Query,
         RawTransaction, Transaction,
@@ -73,6 +82,29 @@ mod uromyces {
         py.detach(|| crate::load_string(string, filename))
     }
 
+    /// Parse a Beancount string to the raw entries and the parsing errors.
+    ///
+    /// This only parses the string, includes are not loaded and no booking or plugins are run.
+    #[pyfunction]
+    #[pyo3(signature = (string, filename=None))]
+    fn parse_only(
+        string: &str,
+        filename: Option<Filename>,
+        py: Python<'_>,
+    ) -> (Vec<types::RawEntry>, Vec<crate::errors::UroError>) {
+        let filename = filename.unwrap_or_else(|| Filename::new_dummy("string"));
+        let parsed = py.detach(|| crate::parse::parse_string(string, &filename));
+        (parsed.entries, parsed.errors)
+    }
+
+    /// Load an entry from its JSON serialisation (as produced by `to_json`).
+    #[pyfunction]
+    fn entry_from_json(s: &str) -> PyResult<types::Entry> {
+        serde_json::from_str(s).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid entry JSON: {e}"))
+        })
+    }
+
     /// Clamp the entries to the given interval.
     #[pyfunction]
     #[allow(clippy::needless_pass_by_value)]
@@ -83,14 +115,147 @@ mod uromyces {
         options: &BeancountOptions,
         py: Python<'_>,
     ) -> Vec<types::Entry> {
-        py.detach(|| {
-            summarize::clamp(
-                &entries,
-                begin_date,
-                end_date,
-                &options.get_summarization_accounts(),
-            )
-        })
+        py.detach(|| summarize::clamp(&entries, begin_date, end_date, options))
+    }
+
+    /// Filter the entries by regular expressions for the payee, narration, and accounts.
+    ///
+    /// Other entries than transactions are excluded if a payee or narration regex is given.
+    /// The given entry objects are returned, they are not copied.
+    #[pyfunction]
+    #[pyo3(signature = (entries, payee_regex=None, narration_regex=None, account_regex=None))]
+    fn filter_entries<'py>(
+        entries: Vec<Bound<'py, PyAny>>,
+        payee_regex: Option<&str>,
+        narration_regex: Option<&str>,
+        account_regex: Option<&str>,
+    ) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let compile = |regex: Option<&str>| {
+            regex
+                .map(|r| {
+                    regex::Regex::new(r).map_err(|e| {
+                        pyo3::exceptions::PyValueError::new_err(format!("Invalid regex: {e}"))
+                    })
+                })
+                .transpose()
+        };
+        let filter = crate::filters::EntryFilter {
+            payee: compile(payee_regex)?,
+            narration: compile(narration_regex)?,
+            account: compile(account_regex)?,
+        };
+        let mut filtered = Vec::new();
+        for entry in entries {
+            // Only convert other entries than transactions if needed, to avoid copying them.
+            let matches = if let Ok(txn) = entry.cast::<types::Transaction>() {
+                filter.matches_transaction(txn.get())
+            } else if filter.is_for_transactions() {
+                false
+            } else if filter.is_for_accounts() {
+                filter.matches(&entry.extract()?)
+            } else {
+                true
+            };
+            if matches {
+                filtered.push(entry);
+            }
+        }
+        Ok(filtered)
+    }
+
+    /// The entries that are only in `a` and the entries that are only in `b`.
+    ///
+    /// With `ignore_positions`, the filenames and line numbers of entries and postings are ignored.
+    #[pyfunction]
+    #[pyo3(signature = (a, b, ignore_positions=false))]
+    #[allow(clippy::needless_pass_by_value)]
+    fn diff_entries(
+        a: Vec<types::Entry>,
+        b: Vec<types::Entry>,
+        ignore_positions: bool,
+        py: Python<'_>,
+    ) -> (Vec<types::Entry>, Vec<types::Entry>) {
+        py.detach(|| crate::diff::diff_entries(&a, &b, ignore_positions))
+    }
+
+    /// Convert the entries to Beancount entries.
+    ///
+    /// The Python strings for currencies are shared between all the converted entries.
+    #[pyfunction]
+    #[allow(clippy::needless_pass_by_value)]
+    fn convert_entries(
+        entries: Vec<types::Entry>,
+        py: Python<'_>,
+    ) -> PyResult<Vec<Bound<'_, PyAny>>> {
+        types::convert_entries(py, &entries)
+    }
+
+    /// Compute the residual of the given (booked) postings, as a list of amounts.
+    #[pyfunction]
+    #[allow(clippy::needless_pass_by_value)]
+    fn compute_residual(postings: Vec<types::Posting>) -> Vec<types::Amount> {
+        use crate::inventory::Position;
+        crate::booking::compute_residual(&postings)
+            .iter()
+            .map(|pos| pos.units())
+            .collect()
+    }
+
+    /// Check whether the given (booked) postings balance, using the inferred tolerances.
+    #[pyfunction]
+    #[allow(clippy::needless_pass_by_value)]
+    fn is_balanced(postings: Vec<types::Posting>, options: &BeancountOptions) -> bool {
+        crate::booking::is_balanced(&postings, options)
+    }
+
+    /// The weight of the posting, i.e., the amount it contributes to the balance of a transaction.
+    ///
+    /// Cost takes precedence over price, which takes precedence over the units.
+    #[pyfunction]
+    fn posting_weight(posting: &types::Posting) -> types::Amount {
+        crate::conversions::get_weight(posting)
+    }
+
+    /// Check whether the account is a descendant of the other account.
+    #[pyfunction]
+    #[allow(clippy::needless_pass_by_value)]
+    fn account_is_child_of(account: types::Account, other: types::Account) -> bool {
+        account.is_child_of(&other)
+    }
+
+    /// All ancestors of the account, from the parent up to the root account.
+    #[pyfunction]
+    #[allow(clippy::needless_pass_by_value)]
+    fn account_ancestors(account: types::Account) -> Vec<String> {
+        account.ancestors().map(|a| a.to_string()).collect()
+    }
+
+    /// Add the given (possibly negative) number of days to the date.
+    #[pyfunction]
+    fn date_add_days(date: types::Date, days: i64) -> PyResult<types::Date> {
+        date.add_days(days)
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Date out of range"))
+    }
+
+    /// Add the given (possibly negative) number of months to the date.
+    ///
+    /// The day is clamped to the last day of the resulting month.
+    #[pyfunction]
+    fn date_add_months(date: types::Date, months: i32) -> PyResult<types::Date> {
+        date.add_months(months)
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Date out of range"))
+    }
+
+    /// The first day of the month of the date.
+    #[pyfunction]
+    fn date_start_of_month(date: types::Date) -> types::Date {
+        date.start_of_month()
+    }
+
+    /// All dates from `begin` (inclusive) to `end` (exclusive).
+    #[pyfunction]
+    fn date_range(begin: types::Date, end: types::Date) -> Vec<types::Date> {
+        types::Date::range(begin, end).collect()
     }
 
     #[pymodule_init]
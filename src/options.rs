@@ -3,11 +3,12 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::display_precision::DisplayPrecisions;
+use crate::display_precision::{DisplayPrecisions, DisplayPrecisionsStats};
 use crate::errors::UroError;
 use crate::tolerances::Tolerances;
 use crate::types::{
-    Booking, Currency, Decimal, JoinAccount, RawDirective, RootAccounts, SummarizationAccounts,
+    Account, Booking, Currency, Decimal, JoinAccount, RawDirective, RootAccounts,
+    SummarizationAccounts,
 };
 
 #[derive(Debug)]
@@ -15,6 +16,7 @@ pub(crate) enum BeancountOptionError {
     InvalidBookingMethod(String),
     InvalidToleranceDefault(String),
     InvalidToleranceMultiplier(String),
+    InvalidBalanceToleranceMin(String),
     UnsupportedOption(String),
     UnknownOption(String),
 }
@@ -33,6 +35,9 @@ impl std::fmt::Display for BeancountOptionError {
             Self::InvalidToleranceMultiplier(s) => {
                 write!(f, "Invalid tolerance multiplier '{s}'")
             }
+            Self::InvalidBalanceToleranceMin(s) => {
+                write!(f, "Invalid minimum balance tolerance '{s}'")
+            }
             Self::UnsupportedOption(s) => {
                 write!(f, "The option '{s}' is not (yet) supported in uromyces")
             }
@@ -44,14 +49,14 @@ impl std::fmt::Display for BeancountOptionError {
 }
 
 /// Beancount's options.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[pyclass(
     frozen,
     skip_from_py_object,
     module = "uromyces",
     name = "UromycesOptions"
 )]
-#[allow(clippy::module_name_repetitions)]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
 pub struct BeancountOptions {
     /// Title of the Beancount ledger.
     #[pyo3(get)]
@@ -82,7 +87,7 @@ pub struct BeancountOptions {
     pub operating_currency: Vec<Currency>,
     /// Imaginary currency to convert all units for conversions at a rate of zero.
     #[pyo3(get)]
-    conversion_currency: Currency,
+    pub conversion_currency: Currency,
     /// A list of document folders.
     #[pyo3(get)]
     pub documents: Vec<String>,
@@ -96,14 +101,71 @@ pub struct BeancountOptions {
     /// Whether the prepend the directory of the top-level file to sys.path.
     #[pyo3(get)]
     pub insert_pythonpath: bool,
+    /// Whether to report files that are included more than once (uromyces-specific).
+    #[pyo3(get)]
+    pub warn_duplicate_includes: bool,
+    /// Whether to report identical duplicate balance assertions (uromyces-specific).
+    #[pyo3(get)]
+    pub warn_redundant_balances: bool,
+    /// Whether to report currencies of open entries that are never used (uromyces-specific).
+    #[pyo3(get)]
+    pub warn_unused_open_currencies: bool,
+    /// Whether to report errors in options as ledger errors instead of warnings (uromyces-specific).
+    #[pyo3(get)]
+    pub strict_options: bool,
+    /// Whether transactions with just a single posting are allowed (uromyces-specific).
+    #[pyo3(get)]
+    pub allow_single_posting_transactions: bool,
+    /// Account to post rounding residuals of balanced transactions to.
+    #[pyo3(get)]
+    pub account_rounding: Option<Account>,
+    /// Whether to also infer tolerances for cost and price currencies (from the units tolerances).
+    #[pyo3(get)]
+    pub infer_tolerance_from_cost: bool,
+    /// The minimum inferred tolerance for balance assertions (uromyces-specific).
+    #[pyo3(get)]
+    pub balance_tolerance_min: Decimal,
     // not supported:
-    // - account_rounding
-    // - infer_tolerance_from_cost
     // - plugin_processing_mode
     #[pyo3(get)]
     pub display_precisions: DisplayPrecisions,
+    /// The full stats from which the display precisions were derived (for diagnostics).
+    #[serde(skip)]
+    pub display_precisions_stats: DisplayPrecisionsStats,
+}
+
+// The display precision stats are not serialized, so they are not considered for equality.
+impl PartialEq for BeancountOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title
+            && self.root_accounts == other.root_accounts
+            && self.account_current_conversions == other.account_current_conversions
+            && self.account_current_earnings == other.account_current_earnings
+            && self.account_previous_balances == other.account_previous_balances
+            && self.account_previous_conversions == other.account_previous_conversions
+            && self.account_previous_earnings == other.account_previous_earnings
+            && self.render_commas == other.render_commas
+            && self.operating_currency == other.operating_currency
+            && self.conversion_currency == other.conversion_currency
+            && self.documents == other.documents
+            && self.booking_method == other.booking_method
+            && self.inferred_tolerance_default == other.inferred_tolerance_default
+            && self.inferred_tolerance_multiplier == other.inferred_tolerance_multiplier
+            && self.insert_pythonpath == other.insert_pythonpath
+            && self.warn_duplicate_includes == other.warn_duplicate_includes
+            && self.warn_redundant_balances == other.warn_redundant_balances
+            && self.warn_unused_open_currencies == other.warn_unused_open_currencies
+            && self.strict_options == other.strict_options
+            && self.allow_single_posting_transactions == other.allow_single_posting_transactions
+            && self.account_rounding == other.account_rounding
+            && self.infer_tolerance_from_cost == other.infer_tolerance_from_cost
+            && self.balance_tolerance_min == other.balance_tolerance_min
+            && self.display_precisions == other.display_precisions
+    }
 }
 
+impl Eq for BeancountOptions {}
+
 impl Default for BeancountOptions {
     fn default() -> Self {
         Self {
@@ -122,7 +184,16 @@ impl Default for BeancountOptions {
             inferred_tolerance_default: Tolerances::default(),
             inferred_tolerance_multiplier: Decimal::new(5, 1),
             insert_pythonpath: false,
+            warn_duplicate_includes: false,
+            warn_redundant_balances: false,
+            warn_unused_open_currencies: false,
+            strict_options: false,
+            allow_single_posting_transactions: false,
+            account_rounding: None,
+            infer_tolerance_from_cost: false,
+            balance_tolerance_min: Decimal::ZERO,
             display_precisions: DisplayPrecisions::default(),
+            display_precisions_stats: DisplayPrecisionsStats::default(),
         }
     }
 }
@@ -177,12 +248,37 @@ impl BeancountOptions {
                     })?;
             }
             "insert_pythonpath" => self.insert_pythonpath = check_boolean_option(value),
+            "uromyces_warn_duplicate_includes" => {
+                self.warn_duplicate_includes = check_boolean_option(value);
+            }
+            "uromyces_warn_redundant_balances" => {
+                self.warn_redundant_balances = check_boolean_option(value);
+            }
+            "uromyces_warn_unused_open_currencies" => {
+                self.warn_unused_open_currencies = check_boolean_option(value);
+            }
+            "uromyces_strict_options" => self.strict_options = check_boolean_option(value),
+            "uromyces_allow_single_posting_transactions" => {
+                self.allow_single_posting_transactions = check_boolean_option(value);
+            }
+            "uromyces_balance_tolerance_min" => {
+                self.balance_tolerance_min = Decimal::from_str_exact(value)
+                    .ok()
+                    .filter(Decimal::is_sign_positive)
+                    .ok_or_else(|| {
+                        BeancountOptionError::InvalidBalanceToleranceMin(value.to_owned())
+                    })?;
+            }
             "long_string_maxlines" => {
                 // This option is a noop in uromyces as it doesn't handle parsing
                 // and the tree-sitter grammar has no such limit.
             }
 
-            "account_rounding" | "infer_tolerance_from_cost" | "plugin_processing_mode" => {
+            "account_rounding" => self.account_rounding = Some(value.into()),
+            "infer_tolerance_from_cost" => {
+                self.infer_tolerance_from_cost = check_boolean_option(value);
+            }
+            "plugin_processing_mode" => {
                 return Err(BeancountOptionError::UnsupportedOption(key.to_owned()));
             }
             _ => {
@@ -204,6 +300,29 @@ impl BeancountOptions {
         }
     }
 
+    /// Round the number like the other numbers of the currency for display.
+    ///
+    /// This uses the most common precision of the currency in the input files or the number's
+    /// own precision if the currency is unknown.
+    #[must_use]
+    pub fn quantize_for_display(&self, number: Decimal, currency: &Currency) -> Decimal {
+        self.display_precisions.quantize(number, currency)
+    }
+
+    /// Render the number like the other numbers of the currency for display.
+    ///
+    /// Like [`Self::quantize_for_display`], but this also adds thousands separators if the
+    /// `render_commas` option is set.
+    #[must_use]
+    pub fn format_for_display(&self, number: Decimal, currency: &Currency) -> String {
+        let number = self.quantize_for_display(number, currency);
+        if self.render_commas {
+            number.to_string_with_commas()
+        } else {
+            number.to_string()
+        }
+    }
+
     /// Update the option struct with values parsed from a Beancount file.
     pub(crate) fn update_from_raw_directives(
         &mut self,
@@ -230,10 +349,69 @@ impl BeancountOptions {
     }
 }
 
+#[pymethods]
+impl BeancountOptions {
+    /// Round the number like the other numbers of the currency for display.
+    #[pyo3(name = "quantize_for_display")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_quantize_for_display(&self, number: Decimal, currency: Currency) -> Decimal {
+        self.quantize_for_display(number, &currency)
+    }
+    /// Render the number like the other numbers of the currency for display.
+    #[pyo3(name = "format_for_display")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_format_for_display(&self, number: Decimal, currency: Currency) -> String {
+        self.format_for_display(number, &currency)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_display_quantization() {
+        let ledger = crate::load_string(
+            r#"
+option "render_commas" "TRUE"
+2020-01-01 price EUR 1.10 USD
+2020-01-02 price EUR 1.20 USD
+2020-01-03 price EUR 1.234 USD
+"#,
+            crate::types::Filename::new_dummy("string"),
+        );
+        let options = &ledger.options;
+        let usd = Currency::from("USD");
+        let number = Decimal::from_str_exact("1234.5678").unwrap();
+
+        assert_eq!(
+            options.quantize_for_display(number, &usd).to_string(),
+            "1234.57"
+        );
+        assert_eq!(options.format_for_display(number, &usd), "1,234.57");
+        // unknown currencies keep the precision of the number
+        let chf = Currency::from("CHF");
+        assert_eq!(options.quantize_for_display(number, &chf), number);
+        assert_eq!(options.format_for_display(number, &chf), "1,234.5678");
+
+        let options = BeancountOptions {
+            render_commas: false,
+            ..options.clone()
+        };
+        assert_eq!(options.format_for_display(number, &usd), "1234.57");
+    }
+
+    #[test]
+    fn test_options_equal_after_serialization() {
+        let ledger = crate::load_string(
+            "2020-01-01 price EUR 1.10 USD\n",
+            crate::types::Filename::new_dummy("string"),
+        );
+        let json = serde_json::to_string(&ledger.options).unwrap();
+        let options: BeancountOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(options, ledger.options);
+    }
+
     #[test]
     fn test_set_single_option() {
         let mut options = BeancountOptions::default();
@@ -263,6 +441,11 @@ mod tests {
             "1,0",
             "Invalid tolerance multiplier '1,0'",
         );
+        t(
+            "uromyces_balance_tolerance_min",
+            "-0.1",
+            "Invalid minimum balance tolerance '-0.1'",
+        );
         t("unknown_option", "asdf", "Unknown option 'unknown_option'");
     }
 }
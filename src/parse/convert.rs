@@ -10,6 +10,9 @@
 //! invalid input data. The latter kind should be bubbled up and will be attached to the list of
 //! errors that can be presented to the user.
 
+use std::cell::RefCell;
+
+use indexmap::IndexMap;
 use tree_sitter::Node;
 
 use super::ConversionResult;
@@ -17,15 +20,15 @@ use super::NodeGetters;
 use super::errors::ConversionError;
 use super::errors::ConversionErrorKind::{
     DivisionFailed, InternalError, InvalidBookingMethod, InvalidDate, InvalidDecimal,
-    InvalidDocumentFilename, UnsupportedTotalCost,
+    InvalidDocumentFilename, PerUnitCostInTotalCost, ReservedMetadataKey,
 };
 use super::node_fields;
 use super::node_ids;
 use crate::types::{
     AbsoluteUTF8Path, Account, Amount, Balance, Booking, BoxStr, Close, Commodity, CostLabel,
     CostSpec, Currency, Custom, CustomValue, Date, Decimal, Document, EntryMeta, Event, Filename,
-    Flag, Meta, MetaKeyValuePair, MetaValue, Note, Open, Pad, Price, Query, RawAmount, RawPosting,
-    RawTransaction, TagsLinks,
+    Flag, LineNumber, Meta, MetaKeyValuePair, MetaValue, Note, Open, Pad, Price, Query, RawAmount,
+    RawPosting, RawTransaction, SourceSpan, TagsLinks,
 };
 
 /// The state that all conversion node handlers have access to.
@@ -38,6 +41,12 @@ pub(super) struct ConversionState<'source> {
     pub pushed_meta: Meta,
     /// The currently pushed tags.
     pub pushed_tags: TagsLinks,
+    /// The line numbers of the `pushmeta` directives for the currently pushed metadata keys.
+    pub pushed_meta_lines: IndexMap<&'source str, LineNumber>,
+    /// The line numbers of the `pushtag` directives for the currently pushed tags.
+    pub pushed_tags_lines: IndexMap<&'source str, LineNumber>,
+    /// Non-fatal errors (warnings) that do not prevent the conversion of an entry.
+    pub warnings: RefCell<Vec<ConversionError>>,
 }
 
 impl<'source> ConversionState<'source> {
@@ -47,6 +56,9 @@ impl<'source> ConversionState<'source> {
             filename,
             pushed_meta: Meta::default(),
             pushed_tags: TagsLinks::new(),
+            pushed_meta_lines: IndexMap::new(),
+            pushed_tags_lines: IndexMap::new(),
+            warnings: RefCell::default(),
         }
     }
 
@@ -55,6 +67,42 @@ impl<'source> ConversionState<'source> {
         &self.string[node.start_byte()..node.end_byte()]
     }
 
+    /// Whether the node is the exponent of a number in scientific notation (like `1.5e3`).
+    ///
+    /// The grammar does not know about exponents, so they are parsed as an error node right after
+    /// the number (or after the expression that ends with the number).
+    pub fn is_exponent(&self, node: Node) -> bool {
+        let is_exponent_error = node.is_error()
+            && self.get_str(node).strip_prefix('e').is_some_and(|digits| {
+                !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+            });
+        // Looking up the previous sibling is not cheap, so only do so for candidates.
+        let Some(previous) = is_exponent_error.then(|| node.prev_sibling()).flatten() else {
+            return false;
+        };
+        // the last token of the previous node
+        let mut cursor = previous.walk();
+        while cursor.goto_last_child() {}
+        let number = cursor.node();
+        number.kind_id() == node_ids::NUMBER && number.end_byte() == node.start_byte()
+    }
+
+    /// The exponent of the number node, if it is in scientific notation.
+    fn exponent<'tree>(&self, number: Node<'tree>) -> Option<Node<'tree>> {
+        if self.string.as_bytes().get(number.end_byte()) != Some(&b'e') {
+            return None;
+        }
+        let mut node = number;
+        loop {
+            if let Some(next) = node.next_sibling() {
+                return self.is_exponent(next).then_some(next);
+            }
+            node = node
+                .parent()
+                .filter(|p| p.end_byte() == number.end_byte())?;
+        }
+    }
+
     /// Get the single char of a flag node.
     fn get_flag(&self, node: Node) -> Flag {
         Flag::try_from(self.string.as_bytes()[node.start_byte()]).unwrap_or_default()
@@ -116,6 +164,22 @@ impl FromNode for CostLabel {
     }
 }
 
+impl FromNode for SourceSpan {
+    fn from_node(node: Node, _s: &ConversionState) -> Self {
+        let start = node.start_position();
+        let end = node.end_position();
+        let to_u32 = |n: usize| u32::try_from(n).expect("position to be small enough");
+        Self {
+            start_line: to_u32(start.row + 1),
+            start_col: to_u32(start.column),
+            end_line: to_u32(end.row + 1),
+            end_col: to_u32(end.column),
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+        }
+    }
+}
+
 impl FromNode for Account {
     fn from_node(node: Node, s: &ConversionState) -> Self {
         debug_assert_eq!(node.kind(), "account",);
@@ -160,6 +224,13 @@ impl TryFromNode for Decimal {
     fn try_from_node(node: Node, s: &ConversionState) -> ConversionResult<Self> {
         match node.kind_id() {
             node_ids::NUMBER => {
+                if let Some(exponent) = s.exponent(node) {
+                    let contents = &s.string[node.start_byte()..exponent.end_byte()];
+                    return Decimal::from_scientific(&contents.replace(',', "")).map_err(|e| {
+                        let kind = InvalidDecimal(contents.into(), e.to_string());
+                        ConversionError::new(kind, &node, s)
+                    });
+                }
                 let contents = s.get_str(node);
                 Decimal::from_str_with_commas(contents).map_err(|e| {
                     ConversionError::new(InvalidDecimal(contents.into(), e.to_string()), &node, s)
@@ -175,9 +246,15 @@ impl TryFromNode for Decimal {
                 })
             }
             node_ids::BINARY_NUM_EXPR => {
-                let left = Self::try_from_node(node.required_child(0), s)?;
-                let right = Self::try_from_node(node.required_child(2), s)?;
-                let op = s.get_str(node.required_child(1));
+                // skip the exponent of a number on the left side
+                let mut cursor = node.walk();
+                let children = node
+                    .children(&mut cursor)
+                    .filter(|child| !s.is_exponent(*child))
+                    .collect::<Vec<_>>();
+                let left = Self::try_from_node(children[0], s)?;
+                let right = Self::try_from_node(children[2], s)?;
+                let op = s.get_str(children[1]);
                 match op {
                     "+" => Ok(left + right),
                     "-" => Ok(left - right),
@@ -199,9 +276,7 @@ impl TryFromNode for Decimal {
 impl TryFromNode for CostSpec {
     fn try_from_node(node: Node, s: &ConversionState) -> ConversionResult<Self> {
         debug_assert!(node.kind() == "cost" || node.kind() == "total_cost");
-        if node.kind_id() == node_ids::TOTAL_COST {
-            return Err(ConversionError::new(UnsupportedTotalCost, &node, s));
-        }
+        let total_cost = node.kind_id() == node_ids::TOTAL_COST;
         let merge = node.child_by_field_id(node_fields::MERGE).is_some();
         let date = node
             .child_by_field_id(node_fields::DATE)
@@ -229,6 +304,14 @@ impl TryFromNode for CostSpec {
                 .map(|m| Currency::from_node(m, s));
         }
 
+        // In the total cost syntax `{{1000 USD}}`, the single number is the total cost.
+        if total_cost {
+            if number_total.is_some() {
+                return Err(ConversionError::new(PerUnitCostInTotalCost, &node, s));
+            }
+            number_total = number_per.take();
+        }
+
         Ok(Self {
             number_per,
             number_total,
@@ -340,10 +423,24 @@ impl TryFromNode for MetaValue {
 impl TryFromNode for MetaKeyValuePair {
     fn try_from_node(node: Node, s: &ConversionState) -> ConversionResult<Self> {
         debug_assert_eq!(node.kind(), "key_value");
+        let key = s.get_key(node.required_child(0));
+        if matches!(key, "filename" | "lineno") {
+            s.warnings.borrow_mut().push(ConversionError::new(
+                ReservedMetadataKey(key.to_owned()),
+                &node,
+                s,
+            ));
+        }
         Ok(Self::new(
-            s.get_key(node.required_child(0)).into(),
+            key.into(),
             if let Some(n) = node.child(1) {
-                Some(MetaValue::try_from_node(n, s)?)
+                // Unlike in custom entries, whole numbers are kept as integers in metadata.
+                Some(match MetaValue::try_from_node(n, s)? {
+                    MetaValue::Decimal(d) => d
+                        .to_integer()
+                        .map_or(MetaValue::Decimal(d), MetaValue::Integer),
+                    value => value,
+                })
             } else {
                 None
             },
@@ -351,20 +448,18 @@ impl TryFromNode for MetaKeyValuePair {
     }
 }
 
+/// The pushed metadata comes first, followed by the metadata of the node. Values given on the node
+/// override pushed values (and earlier values on the node) for the same key, the key keeps the
+/// position where it was first seen.
 impl TryFromNode for Meta {
     fn try_from_node(node: Node, s: &ConversionState) -> ConversionResult<Self> {
         debug_assert_eq!(node.kind(), "metadata");
-        let mut res = node
-            .children(&mut node.walk())
-            .map(|n| MetaKeyValuePair::try_from_node(n, s))
-            .collect::<ConversionResult<_>>()?;
-        if s.pushed_meta.is_empty() {
-            Ok(res)
-        } else {
-            let mut meta = s.pushed_meta.clone();
-            meta.append(&mut res);
-            Ok(meta)
+        let mut meta = s.pushed_meta.clone();
+        for child in node.children(&mut node.walk()) {
+            let MetaKeyValuePair { key, value } = MetaKeyValuePair::try_from_node(child, s)?;
+            meta.insert(&key, value);
         }
+        Ok(meta)
     }
 }
 
@@ -399,10 +494,12 @@ impl TryFromNode for ParsedEntryCommon {
                 node.child_by_field_id(node_fields::METADATA)
                     .map(|n| Meta::try_from_node(n, s))
                     .transpose()?
-                    .unwrap_or_default(),
+                    // Entries without any metadata of their own still get the pushed metadata.
+                    .unwrap_or_else(|| s.pushed_meta.clone()),
                 s.filename.clone(),
                 node.line_number(),
-            ),
+            )
+            .with_span(SourceSpan::from_node(node, s)),
         })
     }
 }
@@ -465,6 +562,7 @@ impl TryFromNode for Custom {
             values: node
                 .children(&mut node.walk())
                 .skip(3)
+                .filter(|n| n.kind_id() != node_ids::METADATA)
                 .map(|n| MetaValue::try_from_node(n, s).map(CustomValue))
                 .collect::<ConversionResult<_>>()?,
         })
@@ -476,8 +574,11 @@ impl TryFromNode for Document {
         debug_assert_eq!(node.kind(), "document");
         let common = ParsedEntryCommon::try_from_node(node, s)?;
         let raw_path = String::from_node(node.required_child_by_id(node_fields::FILENAME), s);
-        let filename = AbsoluteUTF8Path::from_path_maybe_relative(&raw_path, s.filename)
-            .map_err(|e| ConversionError::new(InvalidDocumentFilename(e.to_string()), &node, s))?;
+        // Relative paths are resolved relative to the file containing the document directive.
+        let filename =
+            AbsoluteUTF8Path::from_path_maybe_relative(&raw_path, s.filename).map_err(|e| {
+                ConversionError::new(InvalidDocumentFilename(raw_path, e.to_string()), &node, s)
+            })?;
 
         Ok(Self {
             date: common.date,
@@ -1,8 +1,8 @@
 use crate::errors::UroError;
-use crate::types::{Decimal, Filename, LineNumber};
+use crate::types::{Decimal, Filename, LineNumber, SourceSpan};
 
 use super::NodeGetters;
-use super::convert::ConversionState;
+use super::convert::{ConversionState, FromNode};
 
 /// An error that might occur when trying to parse a string with tree-sitter.
 #[derive(Debug)]
@@ -27,6 +27,7 @@ impl std::fmt::Display for ParsingError {
 pub struct ConversionError {
     filename: Filename,
     lineno: LineNumber,
+    span: Option<SourceSpan>,
     kind: ConversionErrorKind,
 }
 
@@ -39,6 +40,27 @@ impl ConversionError {
         Self {
             filename: s.filename.clone(),
             lineno: node.line_number(),
+            span: Some(SourceSpan::from_node(*node, s)),
+            kind,
+        }
+    }
+
+    /// Use the position of the given node (e.g., a child of the node) as the span of this error.
+    pub(super) fn with_span_of(mut self, node: &tree_sitter::Node, s: &ConversionState) -> Self {
+        self.span = Some(SourceSpan::from_node(*node, s));
+        self
+    }
+
+    /// Create an error for the given line instead of a node.
+    pub(super) fn at_line(
+        kind: ConversionErrorKind,
+        lineno: LineNumber,
+        s: &ConversionState,
+    ) -> Self {
+        Self {
+            filename: s.filename.clone(),
+            lineno,
+            span: None,
             kind,
         }
     }
@@ -50,9 +72,14 @@ pub enum ConversionErrorKind {
     InvalidBookingMethod(String),
     InvalidDate(String),
     InvalidDecimal(String, String),
-    InvalidDocumentFilename(String),
-    UnsupportedTotalCost,
+    InvalidDocumentFilename(String, String),
+    PerUnitCostInTotalCost,
+    ReservedMetadataKey(String),
     SyntaxError(String),
+    UnbalancedPushedMeta(String),
+    UnbalancedPushedTag(String),
+    UnmatchedPopMeta(String),
+    UnmatchedPopTag(String),
     InternalError(String),
     DivisionFailed(Decimal, Decimal),
 }
@@ -69,14 +96,35 @@ impl std::fmt::Display for ConversionError {
             K::InvalidDecimal(m, decimal_error) => {
                 write!(f, "Invalid decimal number '{m}': {decimal_error}")
             }
-            K::InvalidDocumentFilename(m) => write!(f, "Invalid document filename: {m}"),
-            K::UnsupportedTotalCost => write!(
+            K::InvalidDocumentFilename(path, error) => {
+                write!(f, "Invalid document filename '{path}': {error}")
+            }
+            K::ReservedMetadataKey(key) => write!(
                 f,
-                "the deprecated total cost syntax '{{}}' brackets is not supported"
+                "Metadata key '{key}' is reserved for the source position and will be shadowed"
+            ),
+            K::PerUnitCostInTotalCost => write!(
+                f,
+                "Per-unit cost may not be specified using the total cost syntax '{{{{}}}}'"
             ),
             K::SyntaxError(s) => {
                 write!(f, "Invalid syntax: {s}")
             }
+            K::UnbalancedPushedMeta(key) => {
+                write!(
+                    f,
+                    "Unbalanced pushed metadata key '{key}' (missing popmeta)"
+                )
+            }
+            K::UnbalancedPushedTag(tag) => {
+                write!(f, "Unbalanced pushed tag '#{tag}' (missing poptag)")
+            }
+            K::UnmatchedPopMeta(key) => {
+                write!(f, "Attempting to pop absent metadata key '{key}'")
+            }
+            K::UnmatchedPopTag(tag) => {
+                write!(f, "Attempting to pop absent tag '#{tag}'")
+            }
             K::InternalError(s) => {
                 write!(f, "InternalError: {s}")
             }
@@ -89,6 +137,10 @@ impl std::fmt::Display for ConversionError {
 
 impl From<ConversionError> for UroError {
     fn from(e: ConversionError) -> Self {
-        Self::new(e.to_string()).with_position(e.filename.clone(), e.lineno)
+        let error = Self::new(e.to_string()).with_position(e.filename.clone(), e.lineno);
+        match e.span {
+            Some(span) => error.with_span(span),
+            None => error,
+        }
     }
 }
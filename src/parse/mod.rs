@@ -3,11 +3,17 @@
 //! This uses a `tree_sitter` parser to parse the file to an AST and then constructs Beancount
 //! directives from that AST.
 
+use std::borrow::Cow;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
 use serde::{Deserialize, Serialize};
-use tree_sitter::{Language, Node, Parser, Tree};
+use tree_sitter::{Language, Node, ParseOptions, ParseState, Parser, Tree, TreeCursor};
 
 use self::convert::{ConversionState, FromNode, TryFromNode};
-use self::errors::ConversionErrorKind::SyntaxError;
+use self::errors::ConversionErrorKind::{
+    SyntaxError, UnbalancedPushedMeta, UnbalancedPushedTag, UnmatchedPopMeta, UnmatchedPopTag,
+};
 use self::errors::{ConversionError, ParsingError};
 use crate::errors::UroError;
 use crate::types::{
@@ -53,8 +59,8 @@ fn init_parser() -> Parser {
 pub struct ParsedTree<'source> {
     /// A tree-sitter tree.
     tree: Tree,
-    /// The parsed string.
-    string: &'source str,
+    /// The parsed string (with `txn` keywords replaced, see [`replace_txn_keywords`]).
+    string: Cow<'source, str>,
 }
 
 /// Parse a string to a tree-sitter Tree.
@@ -63,15 +69,49 @@ pub struct ParsedTree<'source> {
 ///
 /// `ParsingError` if parsing times out.
 pub fn string_to_tree(string: &str) -> Result<ParsedTree<'_>, ParsingError> {
+    let string = replace_txn_keywords(string);
+    let mut parser = init_parser();
+    parser
+        .parse(string.as_ref(), None)
+        .map(|tree| ParsedTree { tree, string })
+        .ok_or(ParsingError::ParsingTimedOut)
+}
+
+/// Parse a string to a tree-sitter Tree, giving up after the given timeout.
+///
+/// tree-sitter regularly reports its progress while parsing, on which we check whether the
+/// timeout has been exceeded and cancel parsing if so.
+///
+/// # Errors
+///
+/// `ParsingError` if parsing times out. The partially parsed tree is discarded in this case.
+pub fn string_to_tree_with_timeout(
+    string: &str,
+    timeout: Duration,
+) -> Result<ParsedTree<'_>, ParsingError> {
+    let string = replace_txn_keywords(string);
+    let deadline = Instant::now().checked_add(timeout);
+    let mut check_deadline = |_: &ParseState| {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let bytes = string.as_bytes();
     let mut parser = init_parser();
     parser
-        .parse(string, None)
+        .parse_with_options(
+            &mut |offset, _| bytes.get(offset..).unwrap_or_default(),
+            None,
+            Some(ParseOptions::new().progress_callback(&mut check_deadline)),
+        )
         .map(|tree| ParsedTree { tree, string })
         .ok_or(ParsingError::ParsingTimedOut)
 }
 
 /// The raw result of parsing the code of a single Beancount file.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ParsedFile {
     /// The (raw) entries in the file.
     pub entries: Vec<RawEntry>,
@@ -79,6 +119,9 @@ pub struct ParsedFile {
     pub errors: Vec<UroError>,
     /// The directives (options, includes and plugins) in the file.
     pub directives: Vec<RawDirective>,
+    /// The line number of the first entry in the file (if there are any).
+    #[serde(default)]
+    pub first_entry_lineno: Option<LineNumber>,
 }
 
 impl ParsedFile {
@@ -128,10 +171,36 @@ impl NodeGetters for Node<'_> {
 }
 
 /// Parse a string to Beancount entries.
+///
+/// A leading UTF-8 byte order mark (BOM) is ignored.
 #[must_use]
 #[allow(clippy::module_name_repetitions)]
 pub fn parse_string(string: &str, filename: &Filename) -> ParsedFile {
-    match string_to_tree(string) {
+    parse_string_inner(string, filename, None)
+}
+
+/// Parse a string to Beancount entries, giving up if parsing takes longer than the timeout.
+///
+/// On a timeout, the partially parsed tree is discarded and the returned `ParsedFile` only
+/// contains a single error and no entries.
+#[must_use]
+pub fn parse_string_with_timeout(
+    string: &str,
+    filename: &Filename,
+    timeout: Duration,
+) -> ParsedFile {
+    parse_string_inner(string, filename, Some(timeout))
+}
+
+fn parse_string_inner(string: &str, filename: &Filename, timeout: Option<Duration>) -> ParsedFile {
+    // The tree-sitter lexer would skip the BOM as well, but we strip it explicitly so that we do
+    // not depend on that and node positions start at the actual content.
+    let string = string.strip_prefix('\u{feff}').unwrap_or(string);
+    let tree = match timeout {
+        Some(timeout) => string_to_tree_with_timeout(string, timeout),
+        None => string_to_tree(string),
+    };
+    match tree {
         Ok(tree) => convert_syntax_tree(&tree, filename),
         Err(err) => {
             let e = UroError::new(format!("Parsing file failed with an error: {err}"));
@@ -140,130 +209,285 @@ pub fn parse_string(string: &str, filename: &Filename) -> ParsedFile {
     }
 }
 
+/// Replace the `txn` keyword of transactions by the flag `*` that it is equivalent to.
+///
+/// The tree-sitter grammar does not know about this keyword, so it is replaced before parsing.
+/// The replacement has the same length, so all node positions are unchanged. Lines that start
+/// within a (multi-line) string are left as they are.
+fn replace_txn_keywords(string: &str) -> Cow<'_, str> {
+    fn is_date(bytes: &[u8]) -> bool {
+        bytes.len() == 10
+            && bytes.iter().enumerate().all(|(i, b)| match i {
+                4 | 7 => *b == b'-' || *b == b'/',
+                _ => b.is_ascii_digit(),
+            })
+    }
+
+    /// Whether a string is still open at the end of the line.
+    fn in_string_after(line: &[u8], mut in_string: bool) -> bool {
+        let mut bytes = line.iter();
+        while let Some(byte) = bytes.next() {
+            match (in_string, byte) {
+                (true, b'\\') => {
+                    bytes.next();
+                }
+                (_, b'"') => in_string = !in_string,
+                (false, b';') => break,
+                _ => {}
+            }
+        }
+        in_string
+    }
+
+    let mut keyword_offsets = Vec::new();
+    let mut line_start = 0;
+    let mut in_string = false;
+    for line in string.split_inclusive('\n') {
+        let bytes = line.as_bytes();
+        if !in_string && bytes.len() > 10 && is_date(&bytes[..10]) {
+            let rest = line[10..].trim_start_matches([' ', '\t']);
+            if rest.len() < line.len() - 10
+                && rest.strip_prefix("txn").is_some_and(|after| {
+                    after.is_empty() || after.starts_with([' ', '\t', '\r', '\n'])
+                })
+            {
+                keyword_offsets.push(line_start + line.len() - rest.len());
+            }
+        }
+        in_string = in_string_after(bytes, in_string);
+        line_start += line.len();
+    }
+
+    if keyword_offsets.is_empty() {
+        return Cow::Borrowed(string);
+    }
+    let mut replaced = string.to_owned();
+    for offset in keyword_offsets {
+        replaced.replace_range(offset..offset + 3, "*  ");
+    }
+    Cow::Owned(replaced)
+}
+
 /// Convert a tree-sitter AST to a list of (unbooked) Beancount entries.
 ///
 /// This, like the parser before it, operates on a single file. The results from multiple files
 /// can be combined in a subsequent step to obtain a single list of entries ready for booking.
 #[must_use]
-#[allow(clippy::too_many_lines)]
 pub fn convert_syntax_tree(parsed_tree: &ParsedTree, filename: &Filename) -> ParsedFile {
-    let state = &mut ConversionState::new(parsed_tree.string, filename);
-    // this is the cursor we use to iterate over all entries.
+    let mut entries = RawEntryIter::new(parsed_tree, filename);
     let root_node = parsed_tree.tree.root_node();
     let mut result = ParsedFile::with_entries_capacity(root_node.child_count());
+    result.directives = entries.take_directives();
 
-    for node in root_node.children(&mut root_node.walk()) {
-        if node.has_error() {
-            let err = ConversionError::new(SyntaxError(node.to_sexp()), &node, state);
-            result.errors.push(err.into());
-            continue;
+    for entry in entries {
+        match entry {
+            Ok(entry) => {
+                if result.first_entry_lineno.is_none() {
+                    result.first_entry_lineno = Some(entry.meta().lineno);
+                }
+                result.entries.push(entry);
+            }
+            Err(err) => result.errors.push(err),
         }
+    }
+    result
+}
 
-        let res: Result<(), UroError> = (|| {
-            match node.kind_id() {
-                node_ids::TRANSACTION => {
-                    result
-                        .entries
-                        .push(RawTransaction::try_from_node(node, state)?.into());
-                }
-                node_ids::PRICE => {
-                    result
-                        .entries
-                        .push(Price::try_from_node(node, state)?.into());
-                }
-                node_ids::BALANCE => {
-                    result
-                        .entries
-                        .push(Balance::try_from_node(node, state)?.into());
-                }
-                node_ids::CLOSE => {
-                    result
-                        .entries
-                        .push(Close::try_from_node(node, state)?.into());
-                }
-                node_ids::COMMODITY => {
-                    result
-                        .entries
-                        .push(Commodity::try_from_node(node, state)?.into());
-                }
-                node_ids::CUSTOM => {
-                    result
-                        .entries
-                        .push(Custom::try_from_node(node, state)?.into());
-                }
-                node_ids::DOCUMENT => {
-                    result
-                        .entries
-                        .push(Document::try_from_node(node, state)?.into());
-                }
-                node_ids::EVENT => {
-                    result
-                        .entries
-                        .push(Event::try_from_node(node, state)?.into());
-                }
-                node_ids::NOTE => {
-                    result
-                        .entries
-                        .push(Note::try_from_node(node, state)?.into());
-                }
-                node_ids::OPEN => {
-                    result
-                        .entries
-                        .push(Open::try_from_node(node, state)?.into());
-                }
-                node_ids::PAD => {
-                    result.entries.push(Pad::try_from_node(node, state)?.into());
-                }
-                node_ids::QUERY => {
-                    result
-                        .entries
-                        .push(Query::try_from_node(node, state)?.into());
-                }
-                node_ids::OPTION => {
-                    result.directives.push(RawDirective::Option {
-                        filename: filename.clone(),
-                        lineno: node.line_number(),
-                        key: String::from_node(node.required_child(1), state),
-                        value: String::from_node(node.required_child(2), state),
-                    });
-                }
-                node_ids::INCLUDE => {
-                    result.directives.push(RawDirective::Include {
-                        pattern: String::from_node(node.required_child(1), state),
-                    });
-                }
-                node_ids::PLUGIN => {
-                    result.directives.push(RawDirective::Plugin {
-                        name: String::from_node(node.required_child(1), state),
-                        config: node.child(2).map(|n| String::from_node(n, state)),
-                    });
-                }
-                node_ids::PUSHMETA => {
-                    let key_value = MetaKeyValuePair::try_from_node(node.required_child(1), state)?;
-                    state.pushed_meta.push(key_value);
-                }
-                node_ids::PUSHTAG => {
-                    let tag = state.get_tag_link(node.required_child(1));
-                    state.pushed_tags.insert(tag.into());
-                }
-                node_ids::POPMETA => {
-                    let key = state.get_key(node.required_child(1));
-                    state.pushed_meta.remove(key);
-                }
-                node_ids::POPTAG => {
-                    let tag = state.get_tag_link(node.required_child(1));
-                    state.pushed_tags.remove(tag);
-                }
-                _ => {
-                    println!("Unknown node kind: {}", node.kind());
-                }
+/// Convert a single node of an entry (or of a pushtag/pushmeta directive, which update the state).
+///
+/// Returns `None` for all nodes that are not entries.
+fn convert_entry_node(
+    node: Node,
+    state: &mut ConversionState,
+) -> Result<Option<RawEntry>, UroError> {
+    Ok(Some(match node.kind_id() {
+        node_ids::TRANSACTION => RawTransaction::try_from_node(node, state)?.into(),
+        node_ids::PRICE => Price::try_from_node(node, state)?.into(),
+        node_ids::BALANCE => Balance::try_from_node(node, state)?.into(),
+        node_ids::CLOSE => Close::try_from_node(node, state)?.into(),
+        node_ids::COMMODITY => Commodity::try_from_node(node, state)?.into(),
+        node_ids::CUSTOM => Custom::try_from_node(node, state)?.into(),
+        node_ids::DOCUMENT => Document::try_from_node(node, state)?.into(),
+        node_ids::EVENT => Event::try_from_node(node, state)?.into(),
+        node_ids::NOTE => Note::try_from_node(node, state)?.into(),
+        node_ids::OPEN => Open::try_from_node(node, state)?.into(),
+        node_ids::PAD => Pad::try_from_node(node, state)?.into(),
+        node_ids::QUERY => Query::try_from_node(node, state)?.into(),
+        node_ids::OPTION | node_ids::INCLUDE | node_ids::PLUGIN => return Ok(None),
+        node_ids::PUSHMETA => {
+            let key_value = MetaKeyValuePair::try_from_node(node.required_child(1), state)?;
+            let key = state.get_key(node.required_child(1).required_child(0));
+            state.pushed_meta_lines.insert(key, node.line_number());
+            state.pushed_meta.insert(&key_value.key, key_value.value);
+            return Ok(None);
+        }
+        node_ids::PUSHTAG => {
+            let tag = state.get_tag_link(node.required_child(1));
+            state.pushed_tags_lines.insert(tag, node.line_number());
+            state.pushed_tags.insert(tag.into());
+            return Ok(None);
+        }
+        node_ids::POPMETA => {
+            let key = state.get_key(node.required_child(1));
+            if state.pushed_meta_lines.shift_remove(key).is_none() {
+                let kind = UnmatchedPopMeta(key.to_owned());
+                let error = ConversionError::at_line(kind, node.line_number(), state);
+                state.warnings.borrow_mut().push(error);
             }
-            Ok(())
-        })();
-        if let Err(err) = res {
-            result.errors.push(err);
+            state.pushed_meta.remove(key);
+            return Ok(None);
         }
+        node_ids::POPTAG => {
+            let tag = state.get_tag_link(node.required_child(1));
+            if state.pushed_tags_lines.shift_remove(tag).is_none() {
+                let kind = UnmatchedPopTag(tag.to_owned());
+                let error = ConversionError::at_line(kind, node.line_number(), state);
+                state.warnings.borrow_mut().push(error);
+            }
+            state.pushed_tags.remove(tag);
+            return Ok(None);
+        }
+        _ => {
+            println!("Unknown node kind: {}", node.kind());
+            return Ok(None);
+        }
+    }))
+}
+
+/// Convert a single node of an option, include or plugin directive.
+///
+/// Returns `None` for all other nodes.
+fn convert_directive_node(node: Node, state: &ConversionState) -> Option<RawDirective> {
+    match node.kind_id() {
+        node_ids::OPTION => Some(RawDirective::Option {
+            filename: state.filename.clone(),
+            lineno: node.line_number(),
+            key: String::from_node(node.required_child(1), state),
+            value: String::from_node(node.required_child(2), state),
+        }),
+        node_ids::INCLUDE => Some(RawDirective::Include {
+            pattern: String::from_node(node.required_child(1), state),
+        }),
+        node_ids::PLUGIN => Some(RawDirective::Plugin {
+            name: String::from_node(node.required_child(1), state),
+            config: node.child(2).map(|n| String::from_node(n, state)),
+        }),
+        _ => None,
     }
+}
 
-    result
+/// Whether the node contains a syntax error (other than exponents of numbers).
+fn has_syntax_error(node: Node, s: &ConversionState) -> bool {
+    if s.is_exponent(node) {
+        return false;
+    }
+    if node.is_error() || node.is_missing() {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.has_error()
+        && node
+            .children(&mut cursor)
+            .any(|child| has_syntax_error(child, s))
+}
+
+/// Find the first (innermost) node with a syntax error, or fall back to the given node.
+fn first_error_node<'tree>(node: Node<'tree>, s: &ConversionState) -> Node<'tree> {
+    if node.is_error() || node.is_missing() {
+        return node;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .find(|child| has_syntax_error(*child, s))
+        .map_or(node, |child| first_error_node(child, s))
+}
+
+/// A lazy iterator over the (unbooked) entries of a tree-sitter AST.
+///
+/// The entries are only converted once they are requested, so this can be used to scan a file
+/// for some entries without building the whole list of entries. Errors are interleaved with the
+/// entries, except for warnings and errors for unbalanced pushed tags or metadata, which are
+/// produced after all entries. The directives (options, includes and plugins) are collected on
+/// creation and can be obtained with [`RawEntryIter::take_directives`].
+pub struct RawEntryIter<'tree> {
+    /// The conversion state (pushed tags and metadata and warnings).
+    state: ConversionState<'tree>,
+    /// The cursor on the next top-level node to convert, if there is one.
+    cursor: Option<TreeCursor<'tree>>,
+    /// The errors that are produced once all nodes are converted.
+    trailing_errors: Option<std::vec::IntoIter<UroError>>,
+    /// The directives in the file.
+    directives: Vec<RawDirective>,
+}
+
+impl<'tree> RawEntryIter<'tree> {
+    /// Create the iterator for the given tree, collecting the directives.
+    #[must_use]
+    pub fn new(parsed_tree: &'tree ParsedTree, filename: &'tree Filename) -> Self {
+        let state = ConversionState::new(&parsed_tree.string, filename);
+        let root_node = parsed_tree.tree.root_node();
+        // Syntax errors of directives are reported when iterating over the entries.
+        let directives = root_node
+            .children(&mut root_node.walk())
+            .filter(|node| !has_syntax_error(*node, &state))
+            .filter_map(|node| convert_directive_node(node, &state))
+            .collect();
+        let mut cursor = root_node.walk();
+        let has_children = cursor.goto_first_child();
+        Self {
+            state,
+            cursor: has_children.then_some(cursor),
+            trailing_errors: None,
+            directives,
+        }
+    }
+
+    /// Take the directives (options, includes and plugins) in the file.
+    pub fn take_directives(&mut self) -> Vec<RawDirective> {
+        std::mem::take(&mut self.directives)
+    }
+
+    /// The errors for any tags or metadata that are still pushed at the end of the file and
+    /// the warnings.
+    fn final_errors(&self) -> Vec<UroError> {
+        let state = &self.state;
+        let mut errors = Vec::new();
+        for (tag, lineno) in &state.pushed_tags_lines {
+            let kind = UnbalancedPushedTag((*tag).to_owned());
+            errors.push(ConversionError::at_line(kind, *lineno, state).into());
+        }
+        for (key, lineno) in &state.pushed_meta_lines {
+            let kind = UnbalancedPushedMeta((*key).to_owned());
+            errors.push(ConversionError::at_line(kind, *lineno, state).into());
+        }
+        errors.extend(state.warnings.take().into_iter().map(UroError::from));
+        errors
+    }
+}
+
+impl Iterator for RawEntryIter<'_> {
+    type Item = Result<RawEntry, UroError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(cursor) = &mut self.cursor {
+            let node = cursor.node();
+            if !cursor.goto_next_sibling() {
+                self.cursor = None;
+            }
+            if has_syntax_error(node, &self.state) {
+                let err = ConversionError::new(SyntaxError(node.to_sexp()), &node, &self.state)
+                    .with_span_of(&first_error_node(node, &self.state), &self.state);
+                return Some(Err(err.into()));
+            }
+            match convert_entry_node(node, &mut self.state) {
+                Ok(Some(entry)) => return Some(Ok(entry)),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        if self.trailing_errors.is_none() {
+            self.trailing_errors = Some(self.final_errors().into_iter());
+        }
+        self.trailing_errors.as_mut()?.next().map(Err)
+    }
 }
@@ -15,6 +15,7 @@ pub const DATE: u16 = 37;
 pub const DOCUMENT: u16 = 62;
 pub const EVENT: u16 = 63;
 pub const INCLUDE: u16 = 50;
+pub const METADATA: u16 = 82;
 pub const NOTE: u16 = 64;
 pub const NUMBER: u16 = 43;
 pub const OPEN: u16 = 65;
@@ -55,6 +56,7 @@ mod tests {
         assert_eq!(get_id(l, "custom"), CUSTOM);
         assert_eq!(get_id(l, "document"), DOCUMENT);
         assert_eq!(get_id(l, "event"), EVENT);
+        assert_eq!(get_id(l, "metadata"), METADATA);
         assert_eq!(get_id(l, "note"), NOTE);
         assert_eq!(get_id(l, "open"), OPEN);
         assert_eq!(get_id(l, "pad"), PAD);
@@ -1,7 +1,9 @@
 use std::path::Path;
+use std::time::Duration;
 
 use crate::errors::UroError;
 use crate::test_utils::BeancountSnapshot;
+use crate::types::{Filename, RawEntry, SourceSpan};
 
 fn run_parser_snapshot_test(path: &Path) {
     let mut snapshot = BeancountSnapshot::load(path);
@@ -34,3 +36,358 @@ fn parser_snapshot_tests() {
         run_parser_snapshot_test(path);
     });
 }
+
+#[test]
+fn parse_string_with_bom() {
+    let parsed = super::parse_string(
+        "\u{feff}option \"title\" \"BOM\"\n2000-01-01 open Assets:Cash\n",
+        &Filename::new_dummy("string"),
+    );
+    assert!(parsed.errors.is_empty());
+    assert_eq!(parsed.entries.len(), 1);
+    assert_eq!(parsed.directives.len(), 1);
+}
+
+#[test]
+fn parse_string_with_timeout() {
+    let filename = Filename::new_dummy("string");
+    let entry = "2020-01-01 open Assets:Cash\n";
+
+    let parsed = super::parse_string_with_timeout(entry, &filename, Duration::from_mins(1));
+    assert!(parsed.errors.is_empty());
+    assert_eq!(parsed.entries.len(), 1);
+
+    let parsed = super::parse_string_with_timeout(&entry.repeat(10_000), &filename, Duration::ZERO);
+    assert!(parsed.entries.is_empty());
+    assert_eq!(
+        parsed.errors[0].message(),
+        "Parsing file failed with an error: Parsing with tree-sitter timed out."
+    );
+}
+
+#[test]
+fn relative_document_paths_are_resolved_relative_to_the_file() {
+    let input = r#"2021-01-01 document Assets:Cash "statements/2021-01.pdf""#;
+    let filename = Filename::try_from("/home/user/ledger/main.beancount").unwrap();
+    let parsed = super::parse_string(input, &filename);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let RawEntry::Document(document) = &parsed.entries[0] else {
+        panic!("expected a document");
+    };
+    assert_eq!(
+        document.filename.to_string(),
+        "/home/user/ledger/statements/2021-01.pdf"
+    );
+
+    // a relative path cannot be resolved if the file has no real path.
+    let parsed = super::parse_string(input, &Filename::new_dummy("string"));
+    assert!(parsed.entries.is_empty());
+    assert_eq!(
+        parsed.errors[0].message(),
+        "Invalid document filename 'statements/2021-01.pdf': \
+         String is no valid file path: '<string>'"
+    );
+}
+
+#[test]
+fn txn_keyword_is_parsed_as_the_okay_flag() {
+    let parsed = super::parse_string(
+        r#"
+2021-01-01 txn "p" "n"
+  Assets:Cash  1 USD
+  Assets:Bank
+2021/01/02  txn
+  Assets:Cash  1 USD
+  Assets:Bank
+2021-01-03 ! "txn"
+  Assets:Cash  1 USD
+  Assets:Bank
+2021-01-04 * "multi-line ; \" narration
+2021-01-04 txn in a string"
+  Assets:Cash  1 USD
+  Assets:Bank
+"#,
+        &Filename::new_dummy("string"),
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let transactions = parsed
+        .entries
+        .iter()
+        .map(|entry| {
+            let RawEntry::RawTransaction(txn) = entry else {
+                panic!("expected a transaction");
+            };
+            (
+                txn.flag.to_string(),
+                txn.payee.as_deref().map(str::to_owned),
+                txn.narration.to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        transactions,
+        [
+            ("*".into(), Some("p".into()), "n".into()),
+            ("*".into(), None, String::new()),
+            ("!".into(), None, "txn".into()),
+            (
+                "*".into(),
+                None,
+                "multi-line ; \" narration\n2021-01-04 txn in a string".into()
+            ),
+        ]
+    );
+    let span = parsed.entries[0].meta().span.unwrap();
+    assert_eq!((span.start_line, span.end_line), (2, 5));
+
+    // the keyword is also replaced when using the syntax tree directly
+    let tree =
+        super::string_to_tree("2021-01-01 txn\n  Assets:Cash  1 USD\n  Assets:Bank\n").unwrap();
+    let entries = super::RawEntryIter::new(&tree, &Filename::new_dummy("string"))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        entries[0].as_raw_transaction().unwrap().flag.to_string(),
+        "*"
+    );
+}
+
+#[test]
+fn tags_and_links_keep_their_order() {
+    let parsed = super::parse_string(
+        r#"
+pushtag #pushed
+2021-01-01 * "payee" "narration" #b ^y #a #c ^x #b
+  Assets:Cash  1 USD
+  Assets:Bank
+poptag #pushed
+"#,
+        &Filename::new_dummy("string"),
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let RawEntry::RawTransaction(txn) = &parsed.entries[0] else {
+        panic!("expected a transaction");
+    };
+    assert_eq!(
+        txn.tags.iter().collect::<Vec<_>>(),
+        ["pushed", "b", "a", "c"]
+    );
+    assert_eq!(txn.links.iter().collect::<Vec<_>>(), ["y", "x"]);
+    assert_eq!(
+        serde_json::to_string(&txn.tags).unwrap(),
+        r#"["pushed","b","a","c"]"#
+    );
+}
+
+#[test]
+fn inline_metadata_overrides_pushed_metadata() {
+    let parsed = super::parse_string(
+        r#"
+pushmeta project: "pushed"
+pushmeta client: "pushed"
+2021-01-01 * "narration"
+  note: "inline"
+  project: "inline"
+  Assets:Cash  1 USD
+  Assets:Bank
+2021-01-02 * "narration"
+  Assets:Cash  1 USD
+  Assets:Bank
+popmeta project:
+popmeta client:
+"#,
+        &Filename::new_dummy("string"),
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let meta = |entry: &RawEntry| {
+        let RawEntry::RawTransaction(txn) = entry else {
+            panic!("expected a transaction");
+        };
+        ["project", "client", "note"].map(|key| txn.meta.get(key).map(|value| value.to_string()))
+    };
+    assert_eq!(
+        meta(&parsed.entries[0]),
+        [
+            Some("inline".into()),
+            Some("pushed".into()),
+            Some("inline".into())
+        ]
+    );
+    assert_eq!(
+        meta(&parsed.entries[1]),
+        [Some("pushed".into()), Some("pushed".into()), None]
+    );
+}
+
+#[test]
+fn pushed_metadata_applies_to_entries_without_metadata() {
+    let parsed = super::parse_string(
+        r#"
+pushmeta source: "pushed"
+2021-01-01 open Assets:Cash
+2021-01-01 price EUR 1.10 USD
+2021-01-01 note Assets:Cash "note"
+popmeta source:
+2021-01-02 open Assets:Bank
+"#,
+        &Filename::new_dummy("string"),
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let sources = parsed
+        .entries
+        .iter()
+        .map(|entry| entry.meta().get("source").map(|value| value.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sources,
+        [
+            Some("pushed".into()),
+            Some("pushed".into()),
+            Some("pushed".into()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn unbalanced_pushes_point_to_the_push_directive() {
+    let filename = Filename::new_dummy("string");
+    let parsed = super::parse_string(
+        "\npushtag #trip\npushmeta location: \"Berlin\"\n2000-01-01 open Assets:Cash\n",
+        &filename,
+    );
+    assert_eq!(
+        parsed.errors,
+        vec![
+            UroError::new("Unbalanced pushed tag '#trip' (missing poptag)")
+                .with_position(filename.clone(), 2),
+            UroError::new("Unbalanced pushed metadata key 'location' (missing popmeta)")
+                .with_position(filename, 3),
+        ]
+    );
+}
+
+#[test]
+fn unmatched_pops_are_reported_with_their_line() {
+    let filename = Filename::new_dummy("string");
+    let parsed = super::parse_string(
+        "\npushtag #trip\npoptag #trip\npoptag #trip\npopmeta location:\n",
+        &filename,
+    );
+    assert_eq!(
+        parsed.errors,
+        vec![
+            UroError::new("Attempting to pop absent tag '#trip'")
+                .with_position(filename.clone(), 4),
+            UroError::new("Attempting to pop absent metadata key 'location'")
+                .with_position(filename, 5),
+        ]
+    );
+}
+
+#[test]
+fn entry_spans_only_cover_the_directive() {
+    let string =
+        "pushmeta source: \"pushed\"\n\n2000-01-01 *\n  Assets:Cash  1 USD\n  Assets:Other\n";
+    let parsed = super::parse_string(string, &Filename::new_dummy("string"));
+    let meta = &parsed.entries[0].as_raw_transaction().unwrap().meta;
+    assert_eq!(meta.lineno, 3);
+    assert_eq!(
+        meta.span,
+        Some(SourceSpan {
+            start_line: 3,
+            start_col: 0,
+            end_line: 6,
+            end_col: 0,
+            start_byte: 27,
+            end_byte: string.len(),
+        })
+    );
+}
+
+#[test]
+fn syntax_errors_have_the_span_of_the_erroring_node() {
+    let string = "2000-01-01 *\n  Assets:Cash  1 USD USD\n  Assets:Other\n";
+    let parsed = super::parse_string(string, &Filename::new_dummy("string"));
+    assert_eq!(parsed.errors.len(), 1);
+    let error = &parsed.errors[0];
+    assert!(error.message().starts_with("Invalid syntax"));
+    let span = error.span().unwrap();
+    assert_eq!(
+        (span.start_line, span.start_col, span.end_line, span.end_col),
+        (2, 21, 2, 24)
+    );
+    assert_eq!(&string[span.start_byte..span.end_byte], "USD");
+
+    // the span is not serialized, so it is also not considered for equality
+    let json = serde_json::to_string(error).unwrap();
+    let deserialized: crate::errors::UroError = serde_json::from_str(&json).unwrap();
+    assert!(deserialized.span().is_none());
+    assert_eq!(&deserialized, error);
+}
+
+#[test]
+fn iterate_lazily_over_entries() {
+    let path = std::env::current_dir()
+        .unwrap()
+        .join("tests/ledgers/example.beancount");
+    let string = std::fs::read_to_string(&path).unwrap();
+    let filename: Filename = path.as_path().try_into().unwrap();
+    let tree = super::string_to_tree(&string).unwrap();
+
+    let mut entries = super::RawEntryIter::new(&tree, &filename);
+    let directives = entries.take_directives();
+    let commodities = entries
+        .filter_map(Result::ok)
+        .filter(|e| matches!(e, RawEntry::Commodity(..)))
+        .count();
+
+    let parsed = super::convert_syntax_tree(&tree, &filename);
+    assert_eq!(directives, parsed.directives);
+    assert!(commodities > 0);
+    assert_eq!(
+        commodities,
+        parsed
+            .entries
+            .iter()
+            .filter(|e| matches!(e, RawEntry::Commodity(..)))
+            .count()
+    );
+
+    // errors are produced alongside the entries, and the unbalanced pushes at the end.
+    let string =
+        "pushtag #trip\n2000-01-01 open\n2000-01-01 open Assets:Cash\noption \"title\" \"T\"\n";
+    let tree = super::string_to_tree(string).unwrap();
+    let mut entries = super::RawEntryIter::new(&tree, &filename);
+    assert_eq!(entries.take_directives().len(), 1);
+    let results = entries.collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_err());
+    assert!(results[1].is_ok());
+    assert!(results[2].as_ref().unwrap_err().message().contains("#trip"));
+}
+
+#[test]
+fn unsupported_scientific_notation_is_an_error() {
+    let errors = [
+        // an uppercase `E` starts a currency
+        "2024-01-01 price HOOL 1.5E3 USD",
+        "2024-01-01 price HOOL 1.5e-3 USD",
+        "2024-01-01 price HOOL (1.5)e3 USD",
+    ]
+    .map(|input| {
+        let parsed = super::parse_string(input, &Filename::new_dummy("string"));
+        assert!(parsed.entries.is_empty());
+        parsed.errors[0].message().starts_with("Invalid syntax")
+    });
+    assert_eq!(errors, [true; 3]);
+
+    let parsed = super::parse_string(
+        "2024-01-01 price HOOL 1.5e29 USD",
+        &Filename::new_dummy("string"),
+    );
+    assert_eq!(
+        parsed.errors[0].message(),
+        "Invalid decimal number '1.5e29': Scale exceeds the maximum precision allowed: 29 > 28"
+    );
+}
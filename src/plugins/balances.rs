@@ -1,17 +1,17 @@
-use hashbrown::{HashMap, HashSet};
+use hashbrown::HashMap;
 
 use crate::Ledger;
 use crate::errors::UroError;
-use crate::inventory::Inventory;
-use crate::tolerances::balance_tolerance;
-use crate::types::{Account, Balance, Decimal, Entry, Posting};
+use crate::inventory::{Inventory, Position};
+use crate::tolerances::balance_diff_within_tolerance;
+use crate::types::{Account, Balance, BalanceTarget, Decimal, Entry, Posting};
 
 /// A balance assertion failed.
-struct BalanceCheckError<'a>(&'a Account, &'a Balance, Decimal);
+struct BalanceCheckError<'a>(&'a Account, &'a Balance, &'a BalanceTarget, Decimal);
 
 impl From<BalanceCheckError<'_>> for crate::errors::UroError {
     fn from(e: BalanceCheckError) -> Self {
-        let BalanceCheckError(account, balance_entry, diff_amount) = &e;
+        let BalanceCheckError(account, balance_entry, target, diff_amount) = &e;
         let diff_msg = if *diff_amount > Decimal::ZERO {
             format!("{diff_amount} too much")
         } else {
@@ -20,8 +20,17 @@ impl From<BalanceCheckError<'_>> for crate::errors::UroError {
         let expected_amount = &balance_entry.amount;
         let currency = &expected_amount.currency;
         let balance = expected_amount.number + diff_amount;
+        let accumulated = if *target == &BalanceTarget::CostBasis {
+            "accumulated cost basis"
+        } else {
+            "accumulated"
+        };
+        let lots = match target {
+            BalanceTarget::Lots(cost, _) => format!(" in lots at {cost}"),
+            _ => String::new(),
+        };
         let msg = format!(
-            "Balance failed for '{account}': expected {expected_amount} != accumulated {balance} {currency} ({diff_msg})"
+            "Balance failed for '{account}': expected {expected_amount} != {accumulated} {balance} {currency}{lots} ({diff_msg})"
         );
         Self::new(msg).with_entry(*balance_entry)
     }
@@ -34,6 +43,10 @@ impl From<BalanceCheckError<'_>> for crate::errors::UroError {
 struct BalanceChecker<'ledger> {
     ledger: &'ledger Ledger,
     balance: Inventory,
+    /// The total cost of all postings held at cost, if there are assertions on the cost basis.
+    cost_basis: Option<Inventory>,
+    /// All positions held at cost, if there are assertions on specific lots.
+    lots: Option<Inventory>,
     errors: Vec<UroError>,
 }
 
@@ -42,33 +55,142 @@ impl<'ledger> BalanceChecker<'ledger> {
         Self {
             ledger,
             balance: Inventory::new(),
+            cost_basis: None,
+            lots: None,
             errors: Vec::new(),
         }
     }
 
+    /// Start tracking what is needed to check the given balance assertion.
+    fn track(&mut self, entry: &Balance) {
+        match entry.target() {
+            Ok(BalanceTarget::CostBasis) => {
+                self.cost_basis.get_or_insert_with(Inventory::new);
+            }
+            Ok(BalanceTarget::Lots(..)) => {
+                self.lots.get_or_insert_with(Inventory::new);
+            }
+            Ok(BalanceTarget::Units) | Err(_) => {}
+        }
+    }
+
     fn posting(&mut self, posting: &Posting) {
         // we only add the units here, we do not care about the cost for balance assertions.
         self.balance.add_position(&posting.units);
+        if posting.cost.is_some() {
+            if let Some(cost_basis) = &mut self.cost_basis {
+                cost_basis.add_position(&posting.total_cost());
+            }
+            if let Some(lots) = &mut self.lots {
+                lots.add_position(posting);
+            }
+        }
     }
 
-    fn balance(&mut self, entry: &'ledger Balance) {
+    /// Check a single balance assertion against the current running balance.
+    fn check(&self, entry: &Balance) -> Option<UroError> {
         let account = &entry.account;
         let expected_amount = &entry.amount;
-        let current_balance = self
-            .balance
-            .get(&expected_amount.currency, None)
-            .unwrap_or(Decimal::ZERO);
+        let target = match entry.target() {
+            Ok(target) => target,
+            Err(error) => return Some(UroError::new(error.to_string()).with_entry(entry)),
+        };
+        let current_balance = if let BalanceTarget::Lots(lot_cost, lot_date) = &target {
+            self.lots
+                .as_ref()
+                .expect("lots to be tracked for lot assertions")
+                .iter_with_cost()
+                .filter(|pos| {
+                    *pos.currency == expected_amount.currency
+                        && pos.cost.number == lot_cost.number
+                        && pos.cost.currency == lot_cost.currency
+                        && lot_date.is_none_or(|date| pos.cost.date == date)
+                })
+                .map(|pos| *pos.number)
+                .sum()
+        } else {
+            let inventory = if target == BalanceTarget::CostBasis {
+                self.cost_basis
+                    .as_ref()
+                    .expect("cost basis to be tracked for cost basis assertions")
+            } else {
+                &self.balance
+            };
+            inventory
+                .get(&expected_amount.currency, None)
+                .unwrap_or(Decimal::ZERO)
+        };
 
         let diff = current_balance - expected_amount.number;
-        let diff_abs = diff.abs();
 
-        if diff_abs > balance_tolerance(entry, &self.ledger.options) {
-            self.errors
-                .push(BalanceCheckError(account, entry, diff).into());
+        if balance_diff_within_tolerance(entry, diff, &self.ledger.options) {
+            None
+        } else {
+            Some(BalanceCheckError(account, entry, &target, diff).into())
+        }
+    }
+
+    fn balance(&mut self, entry: &Balance) {
+        if let Some(error) = self.check(entry) {
+            self.errors.push(error);
         }
     }
 }
 
+/// Balance checkers for a set of accounts.
+///
+/// Postings are passed on to the checkers of the posting account and all of its ancestors.
+struct BalanceCheckers<'ledger> {
+    checkers: HashMap<Account, BalanceChecker<'ledger>>,
+    active_ancestors_by_account: HashMap<&'ledger Account, Vec<Account>>,
+}
+
+impl<'ledger> BalanceCheckers<'ledger> {
+    fn new<'a>(ledger: &'ledger Ledger, balances: impl Iterator<Item = &'a Balance>) -> Self {
+        let mut checkers = HashMap::<Account, BalanceChecker>::new();
+        for balance in balances {
+            checkers
+                .entry_ref(&balance.account)
+                .or_insert_with(|| BalanceChecker::new(ledger))
+                .track(balance);
+        }
+        Self {
+            checkers,
+            active_ancestors_by_account: HashMap::new(),
+        }
+    }
+
+    fn posting(&mut self, posting: &'ledger Posting) {
+        let checkers = &mut self.checkers;
+        let active_ancestors = self
+            .active_ancestors_by_account
+            .entry(&posting.account)
+            .or_insert_with(|| {
+                let mut active = Vec::new();
+                let mut account = Some(posting.account.clone());
+                while let Some(a) = account {
+                    account = a.parent();
+                    if checkers.contains_key(&a) {
+                        active.push(a);
+                    }
+                }
+                active
+            });
+        for ancestor in active_ancestors {
+            checkers
+                .get_mut(ancestor)
+                .expect("balance_checker to be created above")
+                .posting(posting);
+        }
+    }
+
+    fn get_mut(&mut self, account: &Account) -> &mut BalanceChecker<'ledger> {
+        self.checkers
+            .get_mut(account)
+            .expect("balance_checker to be created above")
+    }
+}
+
 /// Check balance assertions.
 pub fn check_balance_assertions(ledger: &Ledger) -> Vec<UroError> {
     let balance_entries = ledger
@@ -81,61 +203,66 @@ pub fn check_balance_assertions(ledger: &Ledger) -> Vec<UroError> {
         return Vec::new();
     }
 
-    let checked_accounts = balance_entries
-        .iter()
-        .map(|p| &p.account)
-        .collect::<HashSet<_>>();
-    let mut balance_checkers = checked_accounts
-        .into_iter()
-        .map(|a| (a, BalanceChecker::new(ledger)))
-        .collect::<HashMap<_, _>>();
-    let mut active_ancestors_by_account = HashMap::new();
+    let mut balance_checkers = BalanceCheckers::new(ledger, balance_entries.into_iter());
 
     for entry in &ledger.entries {
         match entry {
             Entry::Transaction(e) => {
                 for posting in &e.postings {
-                    let active_ancestors = active_ancestors_by_account
-                        .entry(&posting.account)
-                        .or_insert_with(|| {
-                            let mut active = Vec::new();
-                            let mut account = Some(posting.account.clone());
-                            while let Some(a) = account {
-                                account = a.parent();
-                                if balance_checkers.contains_key(&a) {
-                                    active.push(a);
-                                }
-                            }
-                            active
-                        });
-                    for ancestor in active_ancestors {
-                        balance_checkers
-                            .get_mut(ancestor)
-                            .expect("balance_checker to be created above")
-                            .posting(posting);
-                    }
+                    balance_checkers.posting(posting);
                 }
             }
-            Entry::Balance(e) => {
-                let state = balance_checkers
-                    .get_mut(&e.account)
-                    .expect("balance_checker to be created above");
-                state.balance(e);
-            }
+            Entry::Balance(e) => balance_checkers.get_mut(&e.account).balance(e),
             _ => {}
         }
     }
 
-    let mut sorted_checkers = balance_checkers.into_iter().collect::<Vec<_>>();
-    sorted_checkers.sort_unstable_by_key(|v| v.0);
+    let mut sorted_checkers = balance_checkers.checkers.into_iter().collect::<Vec<_>>();
+    sorted_checkers.sort_unstable_by(|a, b| a.0.cmp(&b.0));
     sorted_checkers
         .into_iter()
         .flat_map(|s| s.1.errors)
         .collect::<Vec<_>>()
 }
 
+/// Check the given balance assertions against the entries of the ledger.
+///
+/// The assertions do not need to be part of the ledger, they are checked just like balance
+/// entries at the start of their date would be. The result contains an error for each failing
+/// assertion (and `None` for the passing ones), in the order of the given assertions.
+pub fn check_assertions(ledger: &Ledger, assertions: &[Balance]) -> Vec<Option<UroError>> {
+    let mut results = vec![None; assertions.len()];
+    let mut pending = (0..assertions.len()).collect::<Vec<_>>();
+    pending.sort_by_key(|i| assertions[*i].date);
+    let mut pending = pending.into_iter().peekable();
+
+    let mut balance_checkers = BalanceCheckers::new(ledger, assertions.iter());
+
+    for transaction in ledger.entries.iter().filter_map(|e| e.as_transaction()) {
+        while let Some(i) = pending.next_if(|i| assertions[*i].date <= transaction.date) {
+            let assertion = &assertions[i];
+            results[i] = balance_checkers
+                .get_mut(&assertion.account)
+                .check(assertion);
+        }
+        for posting in &transaction.postings {
+            balance_checkers.posting(posting);
+        }
+    }
+    for i in pending {
+        let assertion = &assertions[i];
+        results[i] = balance_checkers
+            .get_mut(&assertion.account)
+            .check(assertion);
+    }
+
+    results
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::test_utils::a;
+    use crate::types::{Date, EntryMeta, TagsLinks};
     use crate::{load_string, types::Filename};
 
     use super::*;
@@ -233,6 +360,177 @@ mod tests {
 "), @"[]");
     }
 
+    #[test]
+    fn test_multiple_lots() {
+        insta::assert_json_snapshot!(check(r"
+2013-05-01 open Assets:Invest
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-01 *
+  Assets:Invest                60 HOOL {14.33 USD}
+  Assets:Invest                40 HOOL {15.00 USD, 2013-04-01}
+  Equity:Opening-Balances
+
+2013-05-02 *
+  Assets:Invest                10 HOOL {16.00 USD}
+  Equity:Opening-Balances
+
+2013-05-10 balance Assets:Invest   110 HOOL
+2013-05-10 balance Assets:Invest   100 HOOL
+"), @r#"
+        [
+          "Balance failed for 'Assets:Invest': expected 100 HOOL != accumulated 110 HOOL (10 too much)"
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_cost_basis() {
+        insta::assert_json_snapshot!(check(r"
+2013-05-01 open Assets:Invest
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-01 *
+  Assets:Invest                100 HOOL {14.33 USD}
+  Assets:Invest                 10 USD
+  Equity:Opening-Balances
+
+2013-05-02 balance Assets:Invest   1433 USD
+  uromyces_cost_basis: TRUE
+2013-05-02 balance Assets:Invest   1433.00 USD
+  uromyces_cost_basis: TRUE
+2013-05-02 balance Assets:Invest   10 USD
+;; The USD that are not held at cost are not part of the cost basis
+2013-05-02 balance Assets:Invest   1443 USD
+  uromyces_cost_basis: TRUE
+
+2013-05-03 *
+  Assets:Invest                -50 HOOL {14.33 USD}
+  Equity:Opening-Balances
+
+2013-05-04 balance Assets:Invest   716.50 USD
+  uromyces_cost_basis: TRUE
+"), @r#"
+        [
+          "Balance failed for 'Assets:Invest': expected 1443 USD != accumulated cost basis 1433.00 USD (10.00 too little)"
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_cost_basis_invalid_metadata() {
+        insta::assert_json_snapshot!(check(r#"
+2013-05-01 open Assets:Invest
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-01 *
+  Assets:Invest                100 HOOL {14.33 USD}
+  Equity:Opening-Balances
+
+2013-05-02 balance Assets:Invest   1433 USD
+  uromyces_cost_basis: "TRUE"
+2013-05-02 balance Assets:Invest   100 HOOL
+  uromyces_cost_basis: TRUE
+  uromyces_lot_cost: 14.33 USD
+;; the Beancount-compatible metadata key is not special
+2013-05-02 balance Assets:Invest   0 USD
+  cost_basis: TRUE
+"#), @r#"
+        [
+          "Invalid uromyces_cost_basis 'TRUE' (expected a boolean)",
+          "uromyces_cost_basis cannot be combined with uromyces_lot_cost"
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_costs_are_only_tracked_if_needed() {
+        let ledger = load_string(
+            r"
+2013-05-01 open Assets:Invest
+2013-05-01 open Assets:Invest:Sub
+2013-05-02 balance Assets:Invest   0 HOOL
+2013-05-02 balance Assets:Invest:Sub   0 USD
+  uromyces_cost_basis: TRUE
+",
+            Filename::new_dummy("string"),
+        );
+        let balances = ledger.entries.iter().filter_map(Entry::as_balance);
+        let checkers = BalanceCheckers::new(&ledger, balances).checkers;
+        let checker = &checkers[&Account::from("Assets:Invest")];
+        assert!(checker.cost_basis.is_none() && checker.lots.is_none());
+        let checker = &checkers[&Account::from("Assets:Invest:Sub")];
+        assert!(checker.cost_basis.is_some() && checker.lots.is_none());
+    }
+
+    #[test]
+    fn test_lot_cost() {
+        insta::assert_json_snapshot!(check(r"
+2013-05-01 open Assets:Invest
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-01 *
+  Assets:Invest                60 HOOL {14.33 USD}
+  Assets:Invest                40 HOOL {15.00 USD, 2013-04-01}
+  Assets:Invest                10 HOOL {15.00 USD}
+  Equity:Opening-Balances
+
+2013-05-02 balance Assets:Invest   60 HOOL
+  uromyces_lot_cost: 14.33 USD
+2013-05-02 balance Assets:Invest   50 HOOL
+  uromyces_lot_cost: 15.00 USD
+2013-05-02 balance Assets:Invest   40 HOOL
+  uromyces_lot_cost: 15.00 USD
+  uromyces_lot_date: 2013-04-01
+2013-05-02 balance Assets:Invest   0 HOOL
+  uromyces_lot_cost: 16.00 USD
+2013-05-02 balance Assets:Invest   110 HOOL
+2013-05-02 balance Assets:Invest   60 HOOL
+  uromyces_lot_cost: 15.00 USD
+
+2013-05-03 *
+  Assets:Invest                -20 HOOL {14.33 USD}
+  Equity:Opening-Balances
+
+2013-05-04 balance Assets:Invest   40 HOOL
+  uromyces_lot_cost: 14.33 USD
+"), @r#"
+        [
+          "Balance failed for 'Assets:Invest': expected 60 HOOL != accumulated 50 HOOL in lots at 15.00 USD (10 too little)"
+        ]
+        "#);
+    }
+
+    #[test]
+    fn test_lot_cost_invalid_metadata() {
+        insta::assert_json_snapshot!(check(r#"
+2013-05-01 open Assets:Invest
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-01 *
+  Assets:Invest                60 HOOL {14.33 USD, 2013-04-01}
+  Equity:Opening-Balances
+
+2013-05-02 balance Assets:Invest   60 HOOL
+  uromyces_lot_cost: "14.33 USD"
+2013-05-02 balance Assets:Invest   60 HOOL
+  uromyces_lot_cost: 14.33 USD
+  uromyces_lot_date: "2013-04-01"
+2013-05-02 balance Assets:Invest   60 HOOL
+  uromyces_lot_date: 2013-04-01
+;; the Beancount-compatible metadata keys are not special
+2013-05-02 balance Assets:Invest   10 HOOL
+  lot_cost: 14.33 USD
+"#), @r#"
+        [
+          "Invalid uromyces_lot_cost '14.33 USD' (expected an amount)",
+          "Invalid uromyces_lot_date '2013-04-01' (expected a date)",
+          "uromyces_lot_date is only valid together with uromyces_lot_cost",
+          "Balance failed for 'Assets:Invest': expected 10 HOOL != accumulated 60 HOOL (50 too much)"
+        ]
+        "#);
+    }
+
     #[test]
     fn test_balance_with_tolerance() {
         insta::assert_json_snapshot!(check(r"
@@ -257,4 +555,57 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn test_check_assertions() {
+        let ledger = load_string(
+            r"
+2013-05-01 open Assets:Bank:Checking
+2013-05-01 open Equity:Opening-Balances
+
+2013-05-03 *
+  Assets:Bank:Checking                100.00 USD
+  Equity:Opening-Balances
+",
+            Filename::new_dummy("string"),
+        );
+        let assertion = |account: &str, date: (i32, u32, u32), amount: &str| Balance {
+            meta: EntryMeta::empty(Filename::new_dummy("assertion"), 0),
+            date: Date::from_ymd_opt(date.0, date.1, date.2).unwrap(),
+            tags: TagsLinks::default(),
+            links: TagsLinks::default(),
+            account: account.into(),
+            amount: a(amount),
+            tolerance: None,
+        };
+        let results = check_assertions(
+            &ledger,
+            &[
+                assertion("Assets:Bank:Checking", (2013, 5, 4), "100.00 USD"),
+                // balances are checked at the beginning of the day
+                assertion("Assets:Bank:Checking", (2013, 5, 3), "100.00 USD"),
+                assertion("Assets:Bank:Checking", (2013, 5, 3), "0 USD"),
+                assertion("Assets:Bank", (2013, 5, 4), "100.00 USD"),
+                // a currency that the account never held
+                assertion("Assets:Bank:Checking", (2013, 5, 4), "10 EUR"),
+                assertion("Assets:Bank:Checking", (2013, 5, 4), "0 EUR"),
+                assertion("Assets:Unknown", (2013, 5, 4), "0 EUR"),
+            ],
+        );
+        let messages = results
+            .iter()
+            .map(|r| r.as_ref().map(|e| e.message().to_string()))
+            .collect::<Vec<_>>();
+        insta::assert_json_snapshot!(messages, @r#"
+        [
+          null,
+          "Balance failed for 'Assets:Bank:Checking': expected 100.00 USD != accumulated 0.00 USD (100.00 too little)",
+          null,
+          null,
+          "Balance failed for 'Assets:Bank:Checking': expected 10 EUR != accumulated 0 EUR (10 too little)",
+          null,
+          null
+        ]
+        "#);
+    }
 }
@@ -0,0 +1,116 @@
+use hashbrown::{HashMap, HashSet};
+
+use crate::Ledger;
+use crate::errors::UroError;
+use crate::inventory::Inventory;
+use crate::types::{Account, Close, Date, Entry, EntryMeta, Open, TagsLinks};
+
+/// Close all open accounts that have an empty inventory on the date given in the config.
+///
+/// Accounts that are used by any entries after that date are kept open.
+pub fn close(ledger: &Ledger, config: Option<&str>) -> (Vec<Entry>, Vec<UroError>) {
+    let Some(date) = config.and_then(|c| Date::try_from_str(c.trim()).ok()) else {
+        let error = UroError::new(format!(
+            "Plugin 'close_zero_balance' needs a date as config, got {config:?}"
+        ));
+        return (Vec::new(), vec![error]);
+    };
+
+    let mut opened: Vec<&Open> = Vec::new();
+    let mut closed: HashSet<&Account> = HashSet::new();
+    let mut used_later: HashSet<&Account> = HashSet::new();
+    let mut balances: HashMap<&Account, Inventory> = HashMap::new();
+
+    for entry in &ledger.entries {
+        if entry.date() > date {
+            used_later.extend(entry.accounts());
+            continue;
+        }
+        match entry {
+            Entry::Open(open) => opened.push(open),
+            Entry::Close(close) => {
+                closed.insert(&close.account);
+            }
+            Entry::Transaction(transaction) => {
+                for posting in &transaction.postings {
+                    balances
+                        .entry(&posting.account)
+                        .or_insert_with(Inventory::new)
+                        .add_position(posting);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let close_entries = opened
+        .into_iter()
+        .filter(|open| {
+            let account = &open.account;
+            !closed.contains(account)
+                && !used_later.contains(account)
+                && balances.get(account).is_none_or(Inventory::is_empty)
+        })
+        .map(|open| {
+            Close {
+                meta: EntryMeta::from_existing(&open.meta),
+                date,
+                tags: TagsLinks::default(),
+                links: TagsLinks::default(),
+                account: open.account.clone(),
+            }
+            .into()
+        })
+        .collect();
+
+    (close_entries, Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load_string;
+    use crate::types::Filename;
+
+    use super::*;
+
+    #[test]
+    fn test_close_zero_balance() {
+        let ledger = load_string(
+            r#"
+2024-01-01 open Assets:Zeroed
+2024-01-01 open Assets:NonZero
+2024-01-01 open Assets:UsedLater
+2024-01-01 open Assets:AlreadyClosed
+2024-01-01 open Equity:Opening
+
+2024-01-02 * "Deposit"
+  Assets:Zeroed       10 EUR
+  Assets:NonZero      10 EUR
+  Equity:Opening
+
+2024-01-03 * "Withdraw"
+  Assets:Zeroed      -10 EUR
+  Equity:Opening
+
+2024-01-04 close Assets:AlreadyClosed
+
+2024-03-01 * "Deposit"
+  Assets:UsedLater     5 EUR
+  Equity:Opening
+"#,
+            Filename::new_dummy("string"),
+        );
+        let (entries, errors) = close(&ledger, Some("2024-02-01"));
+        assert!(errors.is_empty());
+        let closes = entries
+            .iter()
+            .filter_map(Entry::as_close)
+            .map(|c| (c.date.to_string(), c.account.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(closes, [("2024-02-01".into(), "Assets:Zeroed".into())]);
+
+        let (entries, errors) = close(&ledger, None);
+        assert!(entries.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+}
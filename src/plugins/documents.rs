@@ -1,5 +1,7 @@
 //! Finding documents.
 
+use hashbrown::HashSet;
+
 use crate::errors::UroError;
 use crate::ledgers::Ledger;
 use crate::types::{AbsoluteUTF8Path, Account, Date, Document, Entry, EntryMeta, TagsLinks};
@@ -47,9 +49,23 @@ pub fn find(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
     let mut new_errors = Vec::new();
 
     let all_accounts = get_all_open_accounts(ledger);
+    // Files that already have a document entry are skipped.
+    let existing_documents = ledger
+        .entries
+        .iter()
+        .filter_map(|e| e.as_document().map(|d| &d.filename))
+        .collect::<HashSet<_>>();
 
-    for document_path in document_paths {
-        let documents_dir = base_path.join_relative_to_file(document_path);
+    // The same folder might be given multiple times (possibly written differently), so
+    // deduplicate the resolved paths, keeping the order of the options.
+    let mut seen_dirs = HashSet::new();
+    let documents_dirs = document_paths
+        .iter()
+        .map(|p| base_path.join_relative_to_file(p))
+        .filter(|dir| seen_dirs.insert(dir.clone()))
+        .collect::<Vec<_>>();
+
+    for documents_dir in documents_dirs {
         if !documents_dir.as_ref().is_dir() {
             new_errors.push(DocumentsDirectoryReadError(ledger, &documents_dir).into());
             continue;
@@ -86,18 +102,19 @@ pub fn find(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
             account_files.sort_unstable();
 
             new_documents.extend(&mut account_files.iter().filter_map(|file_name| {
-                if let Ok(date) = Date::try_from_str(file_name) {
-                    Some(Document {
-                        date,
-                        tags: TagsLinks::default(),
-                        links: TagsLinks::default(),
-                        meta: EntryMeta::empty(ledger.filename.clone(), 0),
-                        account: (*account).clone(),
-                        filename: account_dir.join(file_name),
-                    })
-                } else {
-                    None
+                let date = Date::try_from_str(file_name).ok()?;
+                let filename = account_dir.join(file_name);
+                if existing_documents.contains(&filename) {
+                    return None;
                 }
+                Some(Document {
+                    date,
+                    tags: TagsLinks::default(),
+                    links: TagsLinks::default(),
+                    meta: EntryMeta::empty(ledger.filename.clone(), 0),
+                    account: (*account).clone(),
+                    filename,
+                })
             }));
         }
     }
@@ -1,18 +1,52 @@
-use hashbrown::{HashMap, HashSet};
+use hashbrown::HashMap;
+use indexmap::IndexMap;
 
 use crate::Ledger;
 use crate::errors::UroError;
 use crate::inventory::{BookingResult, Inventory};
-use crate::types::{Amount, Entry, EntryMeta, Price, TagsLinks};
+use crate::types::{Amount, Currency, Date, Decimal, Entry, EntryMeta, Price, TagsLinks};
 
 const META_KEY: &str = "__implicit_prices__";
 
 /// Add implicitly defined prices.
-pub fn add(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
-    let mut new_prices = Vec::new();
+///
+/// Like the Beancount plugin, identical prices on the same day are only added once. The config is
+/// a comma-separated list of flags: with `last`, only the last price per day for each currency pair
+/// is kept (which is the one the price map of Beancount would use) and with `inverse`, the inverse
+/// prices are added as well.
+pub fn add(ledger: &Ledger, config: Option<&str>) -> (Vec<Entry>, Vec<UroError>) {
+    let mut last = false;
+    let mut inverse = false;
+    for flag in config.unwrap_or_default().split(',').map(str::trim) {
+        match flag {
+            "" => {}
+            "last" => last = true,
+            "inverse" => inverse = true,
+            _ => {
+                let error = UroError::new(format!(
+                    "Plugin 'beancount.plugins.implicit_prices' got invalid config flag '{flag}'"
+                ));
+                return (Vec::new(), vec![error]);
+            }
+        }
+    }
 
     let mut balances = HashMap::new();
-    let mut unique_prices = HashSet::new();
+    let mut prices: IndexMap<(Date, Currency, Option<Decimal>, Currency), Price> = IndexMap::new();
+    let mut add_price = |price: Price| {
+        // without `last`, the number is part of the key so only identical prices are deduplicated
+        let key = (
+            price.date,
+            price.currency.clone(),
+            (!last).then_some(price.amount.number),
+            price.amount.currency.clone(),
+        );
+        if last {
+            prices.insert(key, price);
+        } else {
+            prices.entry(key).or_insert(price);
+        }
+    };
 
     for transaction in ledger.entries.iter().filter_map(|e| e.as_transaction()) {
         for posting in &transaction.postings {
@@ -44,6 +78,7 @@ pub fn add(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
                         links: TagsLinks::default(),
                         meta: header,
                         currency: posting.units.currency.clone(),
+                        // a total cost has been converted to a per-unit cost on booking
                         amount: Amount::from_cost(cost),
                     })
                 }
@@ -51,22 +86,30 @@ pub fn add(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
                 None
             };
 
-            if let Some(p) = price_entry {
-                let key = (
-                    p.date,
-                    p.currency.clone(),
-                    p.amount.number,
-                    p.amount.currency.clone(),
-                );
-                if !unique_prices.contains(&key) {
-                    unique_prices.insert(key);
-                    new_prices.push(p.into());
+            if let Some(price) = price_entry {
+                let inverse_price = if inverse { invert(&price) } else { None };
+                add_price(price);
+                if let Some(inverse_price) = inverse_price {
+                    add_price(inverse_price);
                 }
             }
         }
     }
 
-    (new_prices, Vec::new())
+    (prices.into_values().map(Entry::from).collect(), Vec::new())
+}
+
+/// The inverse of the given price (`None` for a price of zero).
+fn invert(price: &Price) -> Option<Price> {
+    let number = Decimal::new(1, 0).checked_div(price.amount.number)?;
+    Some(Price {
+        date: price.date,
+        tags: TagsLinks::default(),
+        links: TagsLinks::default(),
+        meta: price.meta.clone(),
+        currency: price.amount.currency.clone(),
+        amount: Amount::new(number, price.currency.clone()),
+    })
 }
 
 #[cfg(test)]
@@ -75,13 +118,19 @@ mod tests {
 
     use crate::load_string;
     use crate::test_utils::BeancountSnapshot;
+    use crate::types::Filename;
 
     use super::*;
 
     fn run_implicit_prices_test(path: &Path) {
         let mut snapshot = BeancountSnapshot::load(path);
         let ledger = load_string(snapshot.input(), path.try_into().unwrap());
-        let (new_prices, errors) = add(&ledger);
+        let config = ledger
+            .plugins
+            .iter()
+            .find(|p| p.name == "beancount.plugins.implicit_prices")
+            .and_then(|p| p.config.as_deref());
+        let (new_prices, errors) = add(&ledger, config);
 
         assert!(errors.is_empty());
 
@@ -111,4 +160,52 @@ mod tests {
             run_implicit_prices_test(path);
         });
     }
+
+    #[test]
+    fn test_inverse_prices() {
+        let ledger = load_string(
+            r#"
+2013-01-01 open Assets:Account1
+2013-01-01 open Assets:Other
+
+2013-04-01 * "Two postings with a price on the same day"
+  Assets:Account1             1500 HOOL @ 400 USD
+  Assets:Account1              500 HOOL @ 500 USD
+  Assets:Other
+"#,
+            Filename::new_dummy("string"),
+        );
+        let prices = |config| {
+            let (entries, errors) = add(&ledger, config);
+            assert!(errors.is_empty());
+            entries
+                .iter()
+                .filter_map(Entry::as_price)
+                .map(|p| format!("{} {} {}", p.date, p.currency, p.amount))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            prices(None),
+            ["2013-04-01 HOOL 400 USD", "2013-04-01 HOOL 500 USD"]
+        );
+        assert_eq!(
+            prices(Some("inverse")),
+            [
+                "2013-04-01 HOOL 400 USD",
+                "2013-04-01 USD 0.0025 HOOL",
+                "2013-04-01 HOOL 500 USD",
+                "2013-04-01 USD 0.002 HOOL"
+            ]
+        );
+        assert_eq!(prices(Some("last")), ["2013-04-01 HOOL 500 USD"]);
+        assert_eq!(
+            prices(Some("last, inverse")),
+            ["2013-04-01 HOOL 500 USD", "2013-04-01 USD 0.002 HOOL"]
+        );
+
+        let (entries, errors) = add(&ledger, Some("invalid"));
+        assert!(entries.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
 }
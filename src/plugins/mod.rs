@@ -4,14 +4,20 @@ use crate::types::Entry;
 use crate::util::timer::SimpleTimer;
 
 mod balances;
+mod close_zero_balance;
 mod documents;
 mod implicit_prices;
 mod pad;
 mod validation;
 
+pub use balances::check_assertions;
+
 // A plugin that extends the list of entries (and might emit some errors).
 type ExtendPlugin = fn(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>);
 
+// A named plugin, which also gets the config string of the plugin directive.
+type NamedPlugin = fn(ledger: &Ledger, config: Option<&str>) -> (Vec<Entry>, Vec<UroError>);
+
 // A validator is a read-only function that might emit some errors.
 type Validator = fn(ledger: &Ledger) -> Vec<UroError>;
 
@@ -43,19 +49,21 @@ pub fn run_pre(ledger: &mut Ledger) {
     log::info!("{}", t.elapsed("pre_plugin"));
 }
 
-const NAMED_PLUGINS: [(&str, ExtendPlugin); 1] =
-    [("beancount.plugins.implicit_prices", implicit_prices::add)];
+const NAMED_PLUGINS: [(&str, NamedPlugin); 2] = [
+    ("beancount.plugins.implicit_prices", implicit_prices::add),
+    ("close_zero_balance", close_zero_balance::close),
+];
 
-pub fn get_named_plugin(plugin: &str) -> Option<ExtendPlugin> {
+pub fn get_named_plugin(plugin: &str) -> Option<NamedPlugin> {
     NAMED_PLUGINS.iter().find(|n| n.0 == plugin).map(|n| n.1)
 }
 
 /// Run a named plugin.
-pub fn run_named_plugin(ledger: &mut Ledger, plugin: &str) -> bool {
+pub fn run_named_plugin(ledger: &mut Ledger, plugin: &str, config: Option<&str>) -> bool {
     let func = get_named_plugin(plugin);
     let Some(func) = func else { return false };
     let mut t = SimpleTimer::new();
-    let (mut entries, mut errors) = func(ledger);
+    let (mut entries, mut errors) = func(ledger, config);
     ledger.entries.append(&mut entries);
     ledger.errors.append(&mut errors);
     ledger.entries.sort();
@@ -64,21 +72,30 @@ pub fn run_named_plugin(ledger: &mut Ledger, plugin: &str) -> bool {
 }
 
 // The validations to run after all other plugins.
-const VALIDATORS: [(&str, Validator); 9] = [
+const VALIDATORS: [(&str, Validator); 15] = [
     ("account_names", validation::account_names),
+    ("commodity_names", validation::commodity_names),
     ("open_close", validation::open_close),
+    ("balances_after_close", validation::balances_after_close),
     ("duplicate_balances", validation::duplicate_balances),
     ("duplicate_commodities", validation::duplicate_commodities),
     ("active_accounts", validation::active_accounts),
     ("currency_constraints", validation::currency_constraints),
+    ("unused_open_currencies", validation::unused_open_currencies),
+    (
+        "income_statement_postings_at_cost",
+        validation::income_statement_postings_at_cost,
+    ),
     ("transaction_balances", validation::transaction_balances),
     (
         "check_balance_assertions",
         balances::check_balance_assertions,
     ),
+    ("unused_pads", pad::unused_pads),
     // All `FilePath`s are absolute, so we do not need to validate this here :)
     // however, we do the validation that all of them exist
     ("document_files_exist", validation::document_files_exist),
+    ("duplicate_documents", validation::duplicate_documents),
 ];
 
 /// Run validations for a ledger and return any validation errors.
@@ -3,8 +3,17 @@ use hashbrown::{HashMap, HashSet};
 use crate::Ledger;
 use crate::errors::UroError;
 use crate::inventory::Inventory;
-use crate::tolerances::balance_tolerance;
-use crate::types::{Amount, Balance, Currency, Decimal, Entry, Flag, Pad, Posting, Transaction};
+use crate::tolerances::balance_diff_within_tolerance;
+use crate::types::{
+    Account, Amount, Balance, BalanceTarget, Currency, Decimal, Entry, Flag, Pad, Posting,
+    Transaction,
+};
+
+/// Only the units can be padded, so assertions on the cost basis or on lots at a specific cost are
+/// skipped for padding.
+fn is_paddable(entry: &Balance) -> bool {
+    matches!(entry.target(), Ok(BalanceTarget::Units))
+}
 
 /// This is the state that we need to carry along for each account that we want to pad.
 ///
@@ -43,6 +52,9 @@ impl<'ledger> AccountPadder<'ledger> {
     }
 
     fn balance(&mut self, entry: &'ledger Balance) {
+        if !is_paddable(entry) {
+            return;
+        }
         let check_amount = &entry.amount;
         let currency = &check_amount.currency;
         let current_balance = self.balance.get(currency, None).unwrap_or(Decimal::ZERO);
@@ -52,7 +64,8 @@ impl<'ledger> AccountPadder<'ledger> {
 
         let Some(pad) = &self.active_pad else { return };
 
-        if diff.abs() > balance_tolerance(entry, &self.ledger.options) && !padded_already {
+        // Nothing to pad if the balance check would pass anyway.
+        if !balance_diff_within_tolerance(entry, diff, &self.ledger.options) && !padded_already {
             let diff_units = Amount::new(-diff, currency.clone());
             let txn = Transaction::new(
                 pad.meta.clone(),
@@ -83,6 +96,66 @@ impl<'ledger> AccountPadder<'ledger> {
     }
 }
 
+struct UnusedPadEntry<'a>(&'a Pad);
+impl From<UnusedPadEntry<'_>> for UroError {
+    fn from(val: UnusedPadEntry) -> Self {
+        UroError::new(format!("Unused Pad entry for account {}.", val.0.account)).with_entry(val.0)
+    }
+}
+
+/// Check that:
+///
+/// - Each pad entry inserted at least one padding transaction.
+///
+/// A pad entry is unused if all following balance assertions for its account already passed
+/// without padding. Like for padding, assertions on the cost basis or on lots at a specific cost
+/// are skipped, so a pad entry that is only followed by such assertions is not reported.
+pub fn unused_pads(ledger: &Ledger) -> Vec<UroError> {
+    let padding_transactions = ledger
+        .entries
+        .iter()
+        .filter_map(|e| e.as_transaction())
+        .filter(|t| t.flag == Flag::PADDING)
+        .map(|t| (&t.meta, t.date))
+        .collect::<HashSet<_>>();
+
+    // For the currently active pad of each account, whether any and whether any paddable balance
+    // assertions followed it.
+    let mut active_pads: HashMap<&Account, (&Pad, bool, bool)> = HashMap::new();
+    let mut finished_pads = Vec::new();
+    for entry in &ledger.entries {
+        match entry {
+            Entry::Pad(p) => {
+                finished_pads.extend(active_pads.insert(&p.account, (p, false, false)));
+            }
+            Entry::Balance(b) => {
+                if let Some((_, any_balance, paddable_balance)) = active_pads.get_mut(&b.account) {
+                    *any_balance = true;
+                    *paddable_balance |= is_paddable(b);
+                }
+            }
+            _ => {}
+        }
+    }
+    let only_cost_assertions = finished_pads
+        .into_iter()
+        .chain(active_pads.into_values())
+        .filter(|(_, any_balance, paddable_balance)| *any_balance && !paddable_balance)
+        .map(|(p, _, _)| (&p.meta, p.date))
+        .collect::<HashSet<_>>();
+
+    ledger
+        .entries
+        .iter()
+        .filter_map(|e| e.as_pad())
+        .filter(|p| {
+            let key = (&p.meta, p.date);
+            !padding_transactions.contains(&key) && !only_cost_assertions.contains(&key)
+        })
+        .map(|p| UnusedPadEntry(p).into())
+        .collect()
+}
+
 /// Insert transactions for pad entries.
 pub fn transactions_for_pad_entries(ledger: &Ledger) -> (Vec<Entry>, Vec<UroError>) {
     let pad_entries = ledger
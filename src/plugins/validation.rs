@@ -1,10 +1,8 @@
 use hashbrown::{HashMap, HashSet};
 
 use crate::Ledger;
-use crate::conversions::get_weight;
+use crate::booking::is_balanced;
 use crate::errors::UroError;
-use crate::inventory::Inventory;
-use crate::tolerances::Tolerances;
 use crate::types::{
     Account, Balance, Close, Commodity, Currency, Date, Document, Entry, Open, Transaction,
 };
@@ -54,6 +52,31 @@ pub fn account_names(ledger: &Ledger) -> Vec<UroError> {
     errors
 }
 
+struct InvalidCurrencyName<'a>(&'a Currency, &'a Entry);
+impl From<InvalidCurrencyName<'_>> for UroError {
+    fn from(val: InvalidCurrencyName) -> Self {
+        UroError::new(format!("Invalid currency name '{}'.", val.0)).with_entry(val.1)
+    }
+}
+
+/// Check that all currencies used in the entries have a valid name.
+///
+/// Each invalid currency is only reported once, for the first entry that uses it.
+pub fn commodity_names(ledger: &Ledger) -> Vec<UroError> {
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+
+    for entry in &ledger.entries {
+        for currency in entry.currencies() {
+            if seen.insert(currency) && !currency.is_valid() {
+                errors.push(InvalidCurrencyName(currency, entry).into());
+            }
+        }
+    }
+
+    errors
+}
+
 struct DuplicateOpenDirective<'a>(&'a Open);
 impl From<DuplicateOpenDirective<'_>> for UroError {
     fn from(val: DuplicateOpenDirective) -> Self {
@@ -83,50 +106,114 @@ impl From<ClosingUnopenedAccount<'_>> for UroError {
     }
 }
 
+struct CloseBeforeOpen<'a>(&'a Close, Date);
+impl From<CloseBeforeOpen<'_>> for UroError {
+    fn from(val: CloseBeforeOpen) -> Self {
+        UroError::new(format!(
+            "Account {} is closed on {} before it is opened on {}.",
+            val.0.account, val.0.date, val.1
+        ))
+        .with_entry(val.0)
+    }
+}
+
 /// Check that:
 ///
 /// - Each account is opened at most once and closed at most once.
 /// - Only open accounts are closed.
+/// - Accounts are not closed before they are opened.
 pub fn open_close(ledger: &Ledger) -> Vec<UroError> {
     let mut errors = Vec::new();
-    let mut open_accounts = HashSet::new();
+    let mut open_accounts = HashMap::new();
     let mut closed_accounts = HashSet::new();
 
-    for entry in &ledger.entries {
-        match entry {
-            Entry::Open(e) => {
-                if open_accounts.contains(&e.account) {
-                    errors.push(DuplicateOpenDirective(e).into());
-                } else {
-                    open_accounts.insert(&e.account);
-                }
-            }
-            Entry::Close(e) => {
-                if closed_accounts.contains(&e.account) {
-                    errors.push(DuplicateCloseDirective(e).into());
-                } else {
-                    if !open_accounts.contains(&e.account) {
-                        errors.push(ClosingUnopenedAccount(e).into());
-                    }
-                    closed_accounts.insert(&e.account);
+    for open in ledger.entries.iter().filter_map(Entry::as_open) {
+        if open_accounts.contains_key(&open.account) {
+            errors.push(DuplicateOpenDirective(open).into());
+        } else {
+            open_accounts.insert(&open.account, open.date);
+        }
+    }
+
+    for close in ledger.entries.iter().filter_map(Entry::as_close) {
+        if closed_accounts.contains(&close.account) {
+            errors.push(DuplicateCloseDirective(close).into());
+        } else {
+            match open_accounts.get(&close.account) {
+                None => errors.push(ClosingUnopenedAccount(close).into()),
+                Some(&open_date) if close.date < open_date => {
+                    errors.push(CloseBeforeOpen(close, open_date).into());
                 }
+                Some(_) => {}
             }
-            _ => {}
+            closed_accounts.insert(&close.account);
         }
     }
     errors
 }
 
-struct DuplicateDifferingBalanceDirective<'a>(&'a Balance);
+struct BalanceAfterClose<'a>(&'a Balance, Date);
+impl From<BalanceAfterClose<'_>> for UroError {
+    fn from(val: BalanceAfterClose) -> Self {
+        UroError::new(format!(
+            "Balance assertion for account {} after it was closed on {}.",
+            val.0.account, val.1
+        ))
+        .with_entry(val.0)
+    }
+}
+
+/// Check that:
+///
+/// - No balance assertions come after the closing date of their account.
+///
+/// Balance assertions on the closing date itself are fine, they are checked before the close.
+pub fn balances_after_close(ledger: &Ledger) -> Vec<UroError> {
+    let close_dates = ledger
+        .entries
+        .iter()
+        .filter_map(|e| e.as_close())
+        .map(|c| (&c.account, c.date))
+        .collect::<HashMap<_, _>>();
+
+    ledger
+        .entries
+        .iter()
+        .filter_map(|e| e.as_balance())
+        .filter_map(|b| {
+            let close_date = *close_dates.get(&b.account)?;
+            (b.date > close_date).then(|| BalanceAfterClose(b, close_date).into())
+        })
+        .collect()
+}
+
+struct DuplicateDifferingBalanceDirective<'a> {
+    previous: &'a Balance,
+    balance: &'a Balance,
+}
 impl From<DuplicateDifferingBalanceDirective<'_>> for UroError {
     fn from(val: DuplicateDifferingBalanceDirective) -> Self {
-        UroError::new("Duplicate balance assertions with different amounts.").with_entry(val.0)
+        let balance = val.balance;
+        UroError::new(format!(
+            "Duplicate balance assertions with different amounts: {} vs {} on {} for {}.",
+            val.previous.amount, balance.amount, balance.date, balance.account
+        ))
+        .with_entry(balance)
+    }
+}
+
+struct RedundantBalanceDirective<'a>(&'a Balance);
+impl From<RedundantBalanceDirective<'_>> for UroError {
+    fn from(val: RedundantBalanceDirective) -> Self {
+        UroError::new("Redundant duplicate balance assertion.").with_entry(val.0)
     }
 }
 
 /// Check that:
 ///
 /// - No duplicate balances (same account, date and currency) exist with different amounts.
+/// - No duplicate balances exist with identical amounts (only if the uromyces-specific option
+///   `uromyces_warn_redundant_balances` is set).
 pub fn duplicate_balances(ledger: &Ledger) -> Vec<UroError> {
     let mut errors = Vec::new();
     let mut balances: HashMap<(&Account, &Date, &Currency), &Balance> = HashMap::new();
@@ -136,7 +223,15 @@ pub fn duplicate_balances(ledger: &Ledger) -> Vec<UroError> {
         match balances.get(&key) {
             Some(b) => {
                 if b.amount != balance.amount {
-                    errors.push(DuplicateDifferingBalanceDirective(balance).into());
+                    errors.push(
+                        DuplicateDifferingBalanceDirective {
+                            previous: b,
+                            balance,
+                        }
+                        .into(),
+                    );
+                } else if ledger.options.warn_redundant_balances {
+                    errors.push(RedundantBalanceDirective(balance).into());
                 }
             }
             None => {
@@ -182,13 +277,52 @@ impl From<InvalidReferenceToInactiveAccount<'_>> for UroError {
     }
 }
 
-struct InvalidReferenceToUnknownAccount<'a>(&'a Account, &'a Entry);
+struct InvalidReferenceToUnknownAccount<'a>(&'a Account, &'a Entry, Option<&'a Account>);
 impl From<InvalidReferenceToUnknownAccount<'_>> for UroError {
     fn from(val: InvalidReferenceToUnknownAccount) -> Self {
-        UroError::new(format!("Invalid reference to unknown account {}.", val.0)).with_entry(val.1)
+        let message = match val.2 {
+            Some(suggestion) => format!(
+                "Invalid reference to unknown account {}, did you mean '{suggestion}'?",
+                val.0
+            ),
+            None => format!("Invalid reference to unknown account {}.", val.0),
+        };
+        UroError::new(message).with_entry(val.1)
     }
 }
 
+/// The maximum edit distance for an account to be suggested for an unknown one.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// The Levenshtein distance between the two strings (number of single character edits).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Find the closest of the given accounts to an unknown account (if any is close enough).
+fn suggest_account<'a>(
+    unknown: &Account,
+    accounts: impl Iterator<Item = &'a Account>,
+) -> Option<&'a Account> {
+    let unknown = unknown.to_string();
+    accounts
+        .map(|account| (edit_distance(&unknown, &account.to_string()), account))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min()
+        .map(|(_, account)| account)
+}
+
 /// Check that:
 ///
 /// - Only active (opened) accounts are used. Notes, balances and documents are allowed to occur
@@ -229,7 +363,8 @@ pub fn active_accounts(ledger: &Ledger) -> Vec<UroError> {
         errors.push(if opened_accounts.contains(account) {
             InvalidReferenceToInactiveAccount(account, entry).into()
         } else {
-            InvalidReferenceToUnknownAccount(account, entry).into()
+            let suggestion = suggest_account(account, opened_accounts.iter().copied());
+            InvalidReferenceToUnknownAccount(account, entry, suggestion).into()
         });
     }
     errors
@@ -249,13 +384,7 @@ pub fn transaction_balances(ledger: &Ledger) -> Vec<UroError> {
     let mut errors = Vec::new();
 
     for transaction in ledger.entries.iter().filter_map(|e| e.as_transaction()) {
-        let residual = transaction
-            .postings
-            .iter()
-            .map(get_weight)
-            .collect::<Inventory>();
-        let tolerances = Tolerances::infer_from_booked(&transaction.postings, &ledger.options);
-        if !tolerances.is_small(&residual) {
+        if !is_balanced(&transaction.postings, &ledger.options) {
             errors.push(TransactionDoesNotBalance(transaction).into());
         }
     }
@@ -329,6 +458,79 @@ pub fn currency_constraints(ledger: &Ledger) -> Vec<UroError> {
     errors
 }
 
+struct UnusedOpenCurrency<'a>(&'a Currency, &'a Open);
+impl From<UnusedOpenCurrency<'_>> for UroError {
+    fn from(val: UnusedOpenCurrency) -> Self {
+        UroError::new(format!(
+            "Currency '{0}' is declared for account '{1}' but never used",
+            val.0, val.1.account
+        ))
+        .with_entry(val.1)
+    }
+}
+
+/// Check that (only if the uromyces-specific option `uromyces_warn_unused_open_currencies` is
+/// set):
+///
+/// - All currencies that are declared on an open entry are used in a posting to the account.
+pub fn unused_open_currencies(ledger: &Ledger) -> Vec<UroError> {
+    if !ledger.options.warn_unused_open_currencies {
+        return Vec::new();
+    }
+    let mut used_currencies: HashMap<&Account, HashSet<&Currency>> = HashMap::new();
+    for transaction in ledger.entries.iter().filter_map(Entry::as_transaction) {
+        for posting in &transaction.postings {
+            used_currencies
+                .entry(&posting.account)
+                .or_default()
+                .insert(&posting.units.currency);
+        }
+    }
+
+    let mut errors = Vec::new();
+    for open in ledger.entries.iter().filter_map(Entry::as_open) {
+        let used = used_currencies.get(&open.account);
+        for currency in &open.currencies {
+            if !used.is_some_and(|u| u.contains(currency)) {
+                errors.push(UnusedOpenCurrency(currency, open).into());
+            }
+        }
+    }
+    errors
+}
+
+struct PostingAtCostInIncomeStatementAccount<'a>(&'a Account, &'a Transaction);
+impl From<PostingAtCostInIncomeStatementAccount<'_>> for UroError {
+    fn from(val: PostingAtCostInIncomeStatementAccount) -> Self {
+        UroError::new(format!(
+            "Posting at cost to income statement account '{}'",
+            val.0
+        ))
+        .with_entry(val.1)
+    }
+}
+
+/// Check that:
+///
+/// - No positions are held at cost in income statement accounts (income and expenses), since
+///   these should only track flows and not hold any investments.
+pub fn income_statement_postings_at_cost(ledger: &Ledger) -> Vec<UroError> {
+    let mut errors = Vec::new();
+    let roots = &ledger.options.root_accounts;
+
+    for transaction in ledger.entries.iter().filter_map(|e| e.as_transaction()) {
+        for posting in &transaction.postings {
+            if posting.cost.is_some() && roots.is_income_statement_account(&posting.account) {
+                errors.push(
+                    PostingAtCostInIncomeStatementAccount(&posting.account, transaction).into(),
+                );
+            }
+        }
+    }
+
+    errors
+}
+
 struct DocumentFileDoesNotExist<'a>(&'a Document);
 impl From<DocumentFileDoesNotExist<'_>> for UroError {
     fn from(val: DocumentFileDoesNotExist) -> Self {
@@ -351,6 +553,40 @@ pub fn document_files_exist(ledger: &Ledger) -> Vec<UroError> {
     errors
 }
 
+struct DuplicateDocument<'a>(&'a Document, &'a Document);
+impl From<DuplicateDocument<'_>> for UroError {
+    fn from(val: DuplicateDocument) -> Self {
+        UroError::new(format!(
+            "Duplicate document '{}' for accounts {} and {}",
+            val.1.filename, val.0.account, val.1.account
+        ))
+        .with_entry(val.1)
+    }
+}
+
+/// Check that:
+///
+/// - No two documents refer to the same file.
+///
+/// The paths of existing files are resolved, so that documents that are found via symlinked
+/// directories are detected as well.
+pub fn duplicate_documents(ledger: &Ledger) -> Vec<UroError> {
+    let mut errors = Vec::new();
+    let mut documents = HashMap::new();
+
+    for document in ledger.entries.iter().filter_map(|e| e.as_document()) {
+        let path = dunce::canonicalize(&document.filename)
+            .unwrap_or_else(|_| document.filename.as_ref().to_path_buf());
+        if let Some(&first) = documents.get(&path) {
+            errors.push(DuplicateDocument(first, document).into());
+        } else {
+            documents.insert(path, document);
+        }
+    }
+
+    errors
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
@@ -371,6 +607,107 @@ mod tests {
         snapshot.write();
     }
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(super::edit_distance("", ""), 0);
+        assert_eq!(super::edit_distance("abc", ""), 3);
+        assert_eq!(super::edit_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            super::edit_distance("Expenses:Grocery", "Expenses:Groceries"),
+            3
+        );
+        assert_eq!(super::edit_distance("Ausgaben:Café", "Ausgaben:Cafe"), 1);
+    }
+
+    #[test]
+    fn invalid_currency_names_are_reported_once() {
+        let mut ledger = load_string(
+            r"
+2000-01-01 open Assets:Cash
+2000-01-02 price EUR 1.1 USD
+2000-01-03 *
+  Assets:Cash  1 EUR
+  Assets:Cash -1 EUR
+",
+            crate::types::Filename::new_dummy("string"),
+        );
+        assert!(super::commodity_names(&ledger).is_empty());
+
+        for entry in &mut ledger.entries {
+            match entry {
+                crate::types::Entry::Price(price) => price.currency = "eur".into(),
+                crate::types::Entry::Transaction(transaction) => {
+                    for posting in &mut transaction.postings {
+                        posting.units.currency = "eur".into();
+                    }
+                }
+                _ => {}
+            }
+        }
+        let errors = super::commodity_names(&ledger);
+        assert_eq!(
+            errors.iter().map(UroError::message).collect::<Vec<_>>(),
+            ["Invalid currency name 'eur'."]
+        );
+    }
+
+    #[test]
+    fn existing_documents_are_not_found_again() {
+        let ledgers_dir = std::env::current_dir().unwrap().join("tests/ledgers");
+        let documents_dir = ledgers_dir.join("documents/Assets/Account1");
+        let ledger_path = ledgers_dir.join("documents.beancount");
+        let ledger = load_string(
+            &format!(
+                r#"
+option "documents" "documents"
+2000-01-01 open Assets:Account1
+2000-01-01 document Assets:Account1 "{}"
+"#,
+                documents_dir.join("2000-01-01.doc.txt").display()
+            ),
+            ledger_path.as_path().try_into().unwrap(),
+        );
+        let documents = ledger
+            .entries
+            .iter()
+            .filter_map(|e| e.as_document())
+            .collect::<Vec<_>>();
+        assert_eq!(documents.len(), 2);
+        assert!(run_validations(&ledger).is_empty());
+    }
+
+    #[test]
+    fn duplicate_documents_folders_are_scanned_once() {
+        let ledger_path = std::env::current_dir()
+            .unwrap()
+            .join("tests/ledgers/documents.beancount");
+        let load = |options: &str| {
+            load_string(
+                &format!("{options}\n2000-01-01 open Assets:Account1\n"),
+                ledger_path.as_path().try_into().unwrap(),
+            )
+        };
+        let single = load(r#"option "documents" "documents""#);
+        let duplicated = load(
+            r#"
+option "documents" "documents"
+option "documents" "./documents/"
+option "documents" "documents"
+"#,
+        );
+        let document_files = |ledger: &crate::Ledger| {
+            ledger
+                .entries
+                .iter()
+                .filter_map(|e| e.as_document().map(|d| d.filename.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(document_files(&single).len(), 2);
+        assert_eq!(document_files(&single), document_files(&duplicated));
+        assert!(duplicated.errors.is_empty());
+        assert!(run_validations(&duplicated).is_empty());
+    }
+
     // skip these on windows since the path errors are different.
     #[test]
     #[cfg(not(target_os = "windows"))]
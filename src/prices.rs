@@ -0,0 +1,191 @@
+//! A price map to look up the prices of currencies at some date.
+use std::collections::BTreeMap;
+
+use hashbrown::HashMap;
+use pyo3::prelude::*;
+
+use crate::types::{Currency, Date, Decimal, Entry};
+
+/// A map of all prices for pairs of (base, quote) currencies.
+///
+/// For each price entry `DATE price BASE RATE QUOTE`, this also contains the inverse price of the
+/// `QUOTE` currency in `BASE`, so that prices can be looked up in both directions. If there are
+/// multiple prices for a currency pair on the same date, the last one is used. An explicit price is
+/// never replaced by the inverse of a price for the other direction on the same date.
+#[derive(Clone, Debug, Default)]
+#[pyclass(frozen, skip_from_py_object, module = "uromyces")]
+pub struct PriceMap {
+    map: HashMap<(Currency, Currency), Vec<(Date, Decimal)>>,
+}
+
+impl PriceMap {
+    /// Build the price map from all price entries in the given list of entries.
+    #[must_use]
+    pub fn from_entries(entries: &[Entry]) -> Self {
+        let mut prices: HashMap<(Currency, Currency), BTreeMap<Date, Decimal>> = HashMap::new();
+        for price in entries.iter().filter_map(Entry::as_price) {
+            prices
+                .entry((price.currency.clone(), price.amount.currency.clone()))
+                .or_default()
+                .insert(price.date, price.amount.number);
+        }
+        let inverses = prices
+            .iter()
+            .flat_map(|((base, quote), rates)| {
+                rates.iter().filter_map(|(date, rate)| {
+                    Decimal::ONE
+                        .checked_div(*rate)
+                        .map(|inverse| ((quote.clone(), base.clone()), *date, inverse))
+                })
+            })
+            .collect::<Vec<_>>();
+        // explicit prices take precedence over the inverse of the price in the other direction
+        for (pair, date, inverse) in inverses {
+            prices
+                .entry(pair)
+                .or_default()
+                .entry(date)
+                .or_insert(inverse);
+        }
+        Self {
+            map: prices
+                .into_iter()
+                .map(|(pair, rates)| (pair, rates.into_iter().collect()))
+                .collect(),
+        }
+    }
+
+    /// All prices for the given currency pair, sorted by date.
+    #[must_use]
+    pub fn get_prices(&self, base: &Currency, quote: &Currency) -> &[(Date, Decimal)] {
+        self.map
+            .get(&(base.clone(), quote.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the latest price of `base` in `quote` on or before the given date.
+    ///
+    /// The price of a currency in itself is always one.
+    #[must_use]
+    pub fn get_rate(&self, base: &Currency, quote: &Currency, date: Date) -> Option<Decimal> {
+        if base == quote {
+            return Some(Decimal::ONE);
+        }
+        let prices = self.get_prices(base, quote);
+        let index = prices.partition_point(|(d, _)| *d <= date);
+        index.checked_sub(1).map(|i| prices[i].1)
+    }
+}
+
+#[pymethods]
+impl PriceMap {
+    /// Get the latest price of `base` in `quote` on or before the given date.
+    #[allow(clippy::needless_pass_by_value)]
+    fn price_at(&self, base: Currency, quote: Currency, date: Date) -> Option<Decimal> {
+        self.get_rate(&base, &quote, date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{c, d};
+
+    fn date(s: &str) -> Date {
+        Date::try_from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_price_map() {
+        let ledger = crate::load_string(
+            r"
+2020-01-01 price USD 1.25 EUR
+2020-01-10 price USD 1.10 EUR
+2020-01-10 price USD 1.00 EUR
+2020-01-20 price HOOL 0 USD
+",
+            crate::types::Filename::new_dummy("string"),
+        );
+        let prices = PriceMap::from_entries(&ledger.entries);
+        let usd = c("USD");
+        let eur = c("EUR");
+
+        assert_eq!(prices.get_prices(&usd, &eur).len(), 2);
+        assert_eq!(prices.get_rate(&usd, &eur, date("2019-12-31")), None);
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2020-01-01")),
+            Some(d("1.25"))
+        );
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2020-01-09")),
+            Some(d("1.25"))
+        );
+        // the last price on a date wins
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2020-01-10")),
+            Some(d("1.00"))
+        );
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2021-01-01")),
+            Some(d("1.00"))
+        );
+
+        // inverted rates
+        assert_eq!(
+            prices.get_rate(&eur, &usd, date("2020-01-05")),
+            Some(d("0.8"))
+        );
+        assert_eq!(
+            prices.get_rate(&eur, &usd, date("2020-01-10")),
+            Some(d("1"))
+        );
+
+        // no inverse for a zero price
+        assert_eq!(
+            prices.get_rate(&c("HOOL"), &usd, date("2020-01-20")),
+            Some(d("0"))
+        );
+        assert_eq!(prices.get_rate(&usd, &c("HOOL"), date("2020-01-20")), None);
+
+        assert_eq!(
+            prices.get_rate(&eur, &eur, date("2000-01-01")),
+            Some(d("1"))
+        );
+        assert_eq!(prices.get_rate(&eur, &c("CHF"), date("2020-01-20")), None);
+    }
+
+    #[test]
+    fn test_price_map_prefers_explicit_prices_over_inverses() {
+        let ledger = crate::load_string(
+            r"
+2020-01-01 price EUR 1.25 USD
+2020-01-01 price USD 0.75 EUR
+2020-01-02 price USD 0.70 EUR
+2020-01-02 price EUR 1.50 USD
+",
+            crate::types::Filename::new_dummy("string"),
+        );
+        let prices = PriceMap::from_entries(&ledger.entries);
+        let usd = c("USD");
+        let eur = c("EUR");
+
+        assert_eq!(prices.get_prices(&usd, &eur).len(), 2);
+        assert_eq!(prices.get_prices(&eur, &usd).len(), 2);
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2020-01-01")),
+            Some(d("0.75"))
+        );
+        assert_eq!(
+            prices.get_rate(&eur, &usd, date("2020-01-01")),
+            Some(d("1.25"))
+        );
+        assert_eq!(
+            prices.get_rate(&usd, &eur, date("2020-01-02")),
+            Some(d("0.70"))
+        );
+        assert_eq!(
+            prices.get_rate(&eur, &usd, date("2020-01-02")),
+            Some(d("1.50"))
+        );
+    }
+}
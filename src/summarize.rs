@@ -10,9 +10,11 @@ use indexmap::IndexMap;
 
 use crate::inventory::Inventory;
 use crate::inventory::Position;
+use crate::options::BeancountOptions;
 use crate::types::Filename;
 use crate::types::{
-    Account, Date, Entry, EntryMeta, Flag, Posting, SummarizationAccounts, TagsLinks, Transaction,
+    Account, Amount, Date, Decimal, Entry, EntryMeta, Flag, Posting, SummarizationAccounts,
+    TagsLinks, Transaction,
 };
 
 type AccountBalances<'a> = HashMap<&'a Account, Inventory>;
@@ -83,6 +85,64 @@ fn create_summarisation_entries(
         .collect()
 }
 
+/// Create the conversion entry for the given entries, if needed.
+///
+/// Summing up all postings at cost should result in an empty inventory. If this is not the case,
+/// this is due to conversions between currencies at a price. To restore the balance, an entry
+/// with the negated residual positions to the conversions account is created. Like in Beancount,
+/// these postings have a price of zero in the `conversion_currency`, so that the entry balances.
+fn create_conversion_entry(
+    entries: &[Entry],
+    date: Date,
+    accounts: &SummarizationAccounts,
+    options: &BeancountOptions,
+) -> Option<Entry> {
+    let mut residual = Inventory::new();
+    for e in entries {
+        if let Entry::Transaction(txn) = e {
+            for pos in &txn.postings {
+                residual.add_position(&pos.total_cost());
+            }
+        }
+    }
+    if residual.is_empty() {
+        return None;
+    }
+    let conversions_filename = Filename::new_dummy("conversions");
+    let residual_amounts = residual
+        .iter()
+        .map(|pos| pos.units().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let postings = residual
+        .iter()
+        .map(|pos| Posting {
+            price: Some(Amount::new(
+                Decimal::ZERO,
+                options.conversion_currency.clone(),
+            )),
+            ..Posting::new_simple(
+                conversions_filename.clone(),
+                accounts.previous_conversions.clone(),
+                -pos.units(),
+            )
+        })
+        .collect();
+    Some(
+        Transaction::new(
+            EntryMeta::empty(conversions_filename, 0),
+            date,
+            TagsLinks::default(),
+            TagsLinks::default(),
+            Flag::CONVERSIONS,
+            None,
+            format!("Conversion for ({residual_amounts})"),
+            postings,
+        )
+        .into(),
+    )
+}
+
 /// Limit entries to a given time interval.
 ///
 /// We first accumulate balances previous to `begin_date`. Of those, we can move the balances of
@@ -96,14 +156,16 @@ fn create_summarisation_entries(
 /// - keep all open entries from before `begin_date`
 /// - filter out any income/expense balance assertion since those would now fail
 ///   (at least if they were added to previous earnings)
+/// - zero out the residual of conversions from before `begin_date` to the conversions account
 #[must_use]
 pub fn clamp(
     entries: &[Entry],
     begin_date: Date,
     end_date: Date,
-    accounts: &SummarizationAccounts,
+    options: &BeancountOptions,
 ) -> Vec<Entry> {
     debug_assert!(entries.is_sorted());
+    let accounts = &options.get_summarization_accounts();
     let start_index = entries.partition_point(|e| e.date() < begin_date);
     let end_index = entries.partition_point(|e| e.date() < end_date);
     let entries_before = &entries[0..start_index];
@@ -173,9 +235,13 @@ pub fn clamp(
             .cloned(),
     );
 
-    // TODO: conversions
-    //       we want to sum up all postings, and insert an entry with negative
-    //       positions for the costs
+    // Add an entry for conversions that happened before `begin_date`.
+    clamped_entries.extend(create_conversion_entry(
+        entries_before,
+        summarisation_entry_date,
+        accounts,
+        options,
+    ));
 
     // debug_assert!(clamped_entries.is_sorted());
     clamped_entries.sort();
@@ -231,7 +297,7 @@ mod tests {
             &ledger.entries,
             Date::from_ymd_opt(2012, 6, 1).unwrap(),
             Date::from_ymd_opt(2012, 9, 1).unwrap(),
-            &ledger.options.get_summarization_accounts(),
+            &ledger.options,
         );
         insta::assert_json_snapshot!(clamped_entries, @r#"
         [
@@ -410,6 +476,53 @@ mod tests {
               }
             ]
           },
+          {
+            "t": "Transaction",
+            "meta": {
+              "filename": "<conversions>",
+              "lineno": 0
+            },
+            "date": "2012-05-31",
+            "tags": [],
+            "links": [],
+            "flag": "C",
+            "payee": null,
+            "narration": "Conversion for (-5000.00 USD, 6000.00 CAD)",
+            "postings": [
+              {
+                "meta": {
+                  "filename": "<conversions>"
+                },
+                "account": "Equity:Conversions:Previous",
+                "units": {
+                  "number": "5000.00",
+                  "currency": "USD"
+                },
+                "price": {
+                  "number": "0",
+                  "currency": "NOTHING"
+                },
+                "cost": null,
+                "flag": null
+              },
+              {
+                "meta": {
+                  "filename": "<conversions>"
+                },
+                "account": "Equity:Conversions:Previous",
+                "units": {
+                  "number": "-6000.00",
+                  "currency": "CAD"
+                },
+                "price": {
+                  "number": "0",
+                  "currency": "NOTHING"
+                },
+                "cost": null,
+                "flag": null
+              }
+            ]
+          },
           {
             "t": "Transaction",
             "meta": {
@@ -516,4 +629,42 @@ mod tests {
         ]
         "#);
     }
+
+    #[test]
+    fn test_conversion_entry_balances() {
+        let ledger = load_string(
+            r#"
+option "conversion_currency" "CONV"
+2012-01-01 open Assets:US:Checking
+2012-01-01 open Assets:CA:Checking
+
+2012-03-02 * "Some conversion to be summarized"
+  Assets:US:Checking   -5000.00 USD @ 1.2 CAD
+  Assets:CA:Checking    6000.00 CAD
+"#,
+            "<string>".try_into().unwrap(),
+        );
+        let clamped_entries = clamp(
+            &ledger.entries,
+            Date::from_ymd_opt(2012, 6, 1).unwrap(),
+            Date::from_ymd_opt(2012, 9, 1).unwrap(),
+            &ledger.options,
+        );
+        let conversion = clamped_entries
+            .iter()
+            .filter_map(Entry::as_transaction)
+            .find(|t| t.flag == Flag::CONVERSIONS)
+            .unwrap();
+        assert_eq!(conversion.postings.len(), 2);
+        for posting in &conversion.postings {
+            assert_eq!(
+                posting.price,
+                Some(Amount::new(Decimal::ZERO, "CONV".into()))
+            );
+        }
+        assert!(crate::booking::is_balanced(
+            &conversion.postings,
+            &ledger.options
+        ));
+    }
 }
@@ -1,6 +1,9 @@
 //! Tolerances are used to determine if transactions balance.
-use hashbrown::HashMap;
+use std::collections::BTreeMap;
 
+use hashbrown::HashMap;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use serde::{Deserialize, Serialize};
 
 use crate::inventory::Inventory;
@@ -17,6 +20,7 @@ use crate::types::{Balance, Currency, Decimal, Posting, RawPosting};
 ///
 /// In addition to validations, the tolerances can also be used to quantize numbers.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[pyclass(frozen, skip_from_py_object, module = "uromyces")]
 pub struct Tolerances {
     map: HashMap<Currency, Decimal>,
     default: Decimal,
@@ -26,18 +30,39 @@ pub struct Tolerances {
 ///
 /// The balance directive can specify an explicit tolerance which would then be used, otherwise
 /// infer a tolerance from the amount number (considering `options.inferred_tolerance_multiplier`).
+/// An inferred tolerance is at least `options.balance_tolerance_min`, which is useful for numbers
+/// without decimal digits, for which the inferred tolerance is zero.
 pub fn balance_tolerance(balance: &Balance, options: &BeancountOptions) -> Decimal {
     if let Some(explicit) = balance.tolerance {
         explicit
     } else if let Some(scaled_one) = balance.amount.number.scaled_one() {
         // twice as lenient for balances than within transactions
-        scaled_one * options.inferred_tolerance_multiplier * Decimal::TWO
+        (scaled_one * options.inferred_tolerance_multiplier * Decimal::TWO)
+            .max(options.balance_tolerance_min)
     } else {
-        Decimal::ZERO
+        options.balance_tolerance_min
     }
 }
 
+/// Check whether the difference to the amount of a balance assertion is within its tolerance.
+///
+/// A difference that is exactly equal to the tolerance is still considered to be within it. The
+/// balance checks and the padding of balances both use this to stay consistent, so that padding
+/// is only inserted if the balance check would fail otherwise.
+pub fn balance_diff_within_tolerance(
+    balance: &Balance,
+    diff: Decimal,
+    options: &BeancountOptions,
+) -> bool {
+    diff.abs() <= balance_tolerance(balance, options)
+}
+
 impl Tolerances {
+    /// The tolerances per currency, sorted by currency.
+    fn sorted(&self) -> BTreeMap<&Currency, &Decimal> {
+        self.map.iter().collect()
+    }
+
     /// Get the tolerance for a currency.
     fn get(&self, currency: &Currency) -> &Decimal {
         self.map.get(currency).unwrap_or(&self.default)
@@ -58,7 +83,13 @@ impl Tolerances {
     #[must_use]
     pub fn is_small(&self, inv: &Inventory) -> bool {
         inv.iter()
-            .all(|pos| pos.number.abs() <= *self.get(pos.currency))
+            .all(|pos| self.is_small_number(*pos.number, pos.currency))
+    }
+
+    /// Check whether the number is small under consideration of the tolerance for the currency.
+    #[must_use]
+    pub fn is_small_number(&self, number: Decimal, currency: &Currency) -> bool {
+        number.abs() <= *self.get(currency)
     }
 
     /// Set from an option string like "USD:0.04".
@@ -78,51 +109,169 @@ impl Tolerances {
         Err(())
     }
 
+    /// Set the tolerance for the currency, unless it already has a larger one.
+    fn set_max(&mut self, currency: &Currency, mut tolerance: Decimal) {
+        self.map
+            .raw_entry_mut()
+            .from_key(currency)
+            .and_modify(|_c, t| *t = *t.max(&mut tolerance))
+            .or_insert_with(|| (currency.clone(), tolerance));
+    }
+
     /// Infer tolerance for the given number and currency.
     fn add_inferred(&mut self, number: &Decimal, currency: &Currency, multiplier: &Decimal) {
         if let Some(scaled_one) = number.scaled_one() {
-            let mut tolerance = scaled_one * multiplier;
-            self.map
-                .raw_entry_mut()
-                .from_key(currency)
-                .and_modify(|_c, t| *t = *t.max(&mut tolerance))
-                .or_insert_with(|| (currency.clone(), tolerance));
+            self.set_max(currency, scaled_one * multiplier);
+        }
+    }
+
+    /// Add the tolerances that are implied for cost and price currencies.
+    fn add_cost_tolerances(&mut self, cost_tolerances: HashMap<Currency, Decimal>) {
+        for (currency, tolerance) in cost_tolerances {
+            self.set_max(&currency, tolerance);
         }
     }
 
     /// Infer tolerances from a list of raw postings.
+    ///
+    /// With the `infer_tolerance_from_cost` option, tolerances for the currencies of costs and
+    /// prices are inferred as well, from the tolerances of the units.
     #[must_use]
     pub fn infer_from_raw(postings: &[RawPosting], options: &BeancountOptions) -> Self {
         let mut tolerances = options.inferred_tolerance_default.clone();
+        let multiplier = &options.inferred_tolerance_multiplier;
+        let mut cost_tolerances = HashMap::new();
 
         for posting in postings {
-            if let Some(number) = &posting.units.number
-                && let Some(currency) = &posting.units.currency
+            let (Some(number), Some(currency)) = (&posting.units.number, &posting.units.currency)
+            else {
+                continue;
+            };
+            tolerances.add_inferred(number, currency, multiplier);
+            if options.infer_tolerance_from_cost
+                && let Some(scaled_one) = number.scaled_one()
             {
-                tolerances.add_inferred(number, currency, &options.inferred_tolerance_multiplier);
+                let units_tolerance = scaled_one * multiplier;
+                if let Some(cost) = &posting.cost
+                    && let Some(currency) = &cost.currency
+                {
+                    let numbers = [cost.number_per, cost.number_total];
+                    add_cost_tolerance(&mut cost_tolerances, currency, units_tolerance, numbers);
+                }
+                if let Some(price) = &posting.price
+                    && let Some(currency) = &price.currency
+                {
+                    let numbers = [price.number];
+                    add_cost_tolerance(&mut cost_tolerances, currency, units_tolerance, numbers);
+                }
             }
         }
 
+        tolerances.add_cost_tolerances(cost_tolerances);
         tolerances
     }
 
     /// Infer tolerances from a list of booked postings.
+    ///
+    /// Like [`Tolerances::infer_from_raw`], this considers costs and prices with the
+    /// `infer_tolerance_from_cost` option (but not for interpolated postings).
     #[must_use]
     pub fn infer_from_booked(postings: &[Posting], options: &BeancountOptions) -> Self {
         let mut tolerances = options.inferred_tolerance_default.clone();
+        let multiplier = &options.inferred_tolerance_multiplier;
+        let mut cost_tolerances = HashMap::new();
 
         for posting in postings {
-            tolerances.add_inferred(
-                &posting.units.number,
-                &posting.units.currency,
-                &options.inferred_tolerance_multiplier,
-            );
+            tolerances.add_inferred(&posting.units.number, &posting.units.currency, multiplier);
+            if options.infer_tolerance_from_cost
+                && !posting.meta.is_automatic()
+                && let Some(scaled_one) = posting.units.number.scaled_one()
+            {
+                let units_tolerance = scaled_one * multiplier;
+                if let Some(cost) = &posting.cost {
+                    let numbers = [Some(cost.number)];
+                    add_cost_tolerance(
+                        &mut cost_tolerances,
+                        &cost.currency,
+                        units_tolerance,
+                        numbers,
+                    );
+                }
+                if let Some(price) = &posting.price {
+                    let numbers = [Some(price.number)];
+                    add_cost_tolerance(
+                        &mut cost_tolerances,
+                        &price.currency,
+                        units_tolerance,
+                        numbers,
+                    );
+                }
+            }
         }
 
+        tolerances.add_cost_tolerances(cost_tolerances);
         tolerances
     }
 }
 
+/// The maximum tolerance that is implied for a cost or price currency by a single posting.
+const MAXIMUM_COST_TOLERANCE: Decimal = Decimal::HALF;
+
+/// Add the tolerance implied by a cost or price to the tolerance of its currency.
+///
+/// Like in Beancount, the tolerance of the units is multiplied with the cost or price number
+/// (the smallest one if a cost has both a per-unit and a total number), capped at 0.5. The
+/// tolerances of all postings of a transaction with the same cost or price currency add up.
+fn add_cost_tolerance<const N: usize>(
+    cost_tolerances: &mut HashMap<Currency, Decimal>,
+    currency: &Currency,
+    units_tolerance: Decimal,
+    numbers: [Option<Decimal>; N],
+) {
+    let tolerance = numbers
+        .into_iter()
+        .flatten()
+        .map(|number| units_tolerance * number)
+        .fold(MAXIMUM_COST_TOLERANCE, Decimal::min);
+    *cost_tolerances.entry_ref(currency).or_default() += tolerance;
+}
+
+#[pymethods]
+impl Tolerances {
+    /// The tolerance for all currencies that do not have an explicit one.
+    #[getter]
+    fn default(&self) -> Decimal {
+        self.default
+    }
+    /// The tolerances per currency.
+    #[getter]
+    fn tolerances<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (currency, tolerance) in self.sorted() {
+            dict.set_item(currency, tolerance)?;
+        }
+        Ok(dict)
+    }
+    /// Get the tolerance for a currency.
+    #[pyo3(name = "get")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_get(&self, currency: Currency) -> Decimal {
+        *self.get(&currency)
+    }
+    fn __repr__(&self) -> String {
+        let tolerances = self
+            .sorted()
+            .iter()
+            .map(|(c, t)| format!("{c}: {t}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "Tolerances(tolerances={{{tolerances}}}, default={})",
+            self.default
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,4 +299,102 @@ mod tests {
         );
         assert_eq!(tolerances.quantize(&c("USD"), d("1.23456789")), d("1.23"));
     }
+
+    #[test]
+    fn test_infer_from_cost() {
+        let input = r"
+2000-01-01 open Assets:Stock
+2000-01-01 open Assets:Other
+
+2000-01-02 *
+  Assets:Stock      3.0 HOOL {1.333 USD}
+  Assets:Other     -1 OTHER @ 4.00 USD
+";
+        let ledger = crate::load_string_validated(input, crate::types::Filename::new_dummy("s"));
+        assert!(!ledger.options.infer_tolerance_from_cost);
+        assert_eq!(ledger.errors.len(), 1);
+        assert!(
+            ledger.errors[0]
+                .message()
+                .starts_with("Transaction does not balance")
+        );
+
+        let input = format!("option \"infer_tolerance_from_cost\" \"TRUE\"\n{input}");
+        let ledger = crate::load_string_validated(&input, crate::types::Filename::new_dummy("s"));
+        assert!(ledger.errors.is_empty(), "{:?}", ledger.errors);
+        let postings = postings_from_strings(&[
+            "Assets:Stock 3.0 HOOL {1.333 USD}",
+            "Assets:Other -1 OTHER @ 4.00 USD",
+        ]);
+        // the units tolerance of 0.05 HOOL times the cost; units without decimal digits imply none
+        let tolerances = Tolerances::infer_from_raw(&postings, &ledger.options);
+        assert_eq!(*tolerances.get(&c("USD")), d("0.06665"));
+        let transaction = ledger.entries[2].as_transaction().unwrap();
+        let tolerances = Tolerances::infer_from_booked(&transaction.postings, &ledger.options);
+        assert_eq!(*tolerances.get(&c("USD")), d("0.06665"));
+
+        // the implied tolerances add up over postings, each of them being capped at 0.5
+        let postings = postings_from_strings(&[
+            "Assets:Stock 1.0 HOOL {100 USD}",
+            "Assets:Other -1.0 OTHER @ 0.20 USD",
+            "Assets:Other 10.00 USD",
+        ]);
+        let tolerances = Tolerances::infer_from_raw(&postings, &ledger.options);
+        assert_eq!(*tolerances.get(&c("USD")), d("0.51"));
+    }
+
+    #[test]
+    fn test_balance_tolerance_min() {
+        let input = r"
+2000-01-01 open Assets:Stock
+2000-01-01 open Assets:Other
+
+2000-01-02 *
+  Assets:Stock      10.0001 HOOL
+  Assets:Other
+
+2000-01-03 balance Assets:Stock     10 HOOL
+2000-01-03 balance Assets:Other  -10.00 HOOL
+";
+        let ledger = crate::load_string_validated(input, crate::types::Filename::new_dummy("s"));
+        assert_eq!(ledger.options.balance_tolerance_min, Decimal::ZERO);
+        assert_eq!(ledger.errors.len(), 1);
+        assert!(ledger.errors[0].message().starts_with("Balance failed"));
+
+        let input = format!("option \"uromyces_balance_tolerance_min\" \"0.001\"\n{input}");
+        let ledger = crate::load_string_validated(&input, crate::types::Filename::new_dummy("s"));
+        assert!(ledger.errors.is_empty(), "{:?}", ledger.errors);
+        let balances = ledger
+            .entries
+            .iter()
+            .filter_map(crate::types::Entry::as_balance)
+            .map(|b| balance_tolerance(b, &ledger.options))
+            .collect::<Vec<_>>();
+        // the floor does not lower larger inferred tolerances
+        assert_eq!(balances, [d("0.001"), d("0.01")]);
+    }
+
+    #[test]
+    fn test_infer_from_booked_mixed_precision() {
+        let ledger = crate::load_string(
+            r"
+2000-01-01 *
+  Assets:Cash        20.00 USD
+  Assets:Cash          1.5 USD
+  Assets:Cash          -21.50 USD
+  Assets:Cash          10 EUR
+  Assets:Cash          -10 EUR
+",
+            crate::types::Filename::new_dummy("string"),
+        );
+        let transaction = ledger.entries[0].as_transaction().unwrap();
+        let tolerances = Tolerances::infer_from_booked(&transaction.postings, &ledger.options);
+        // the largest tolerance for a currency wins
+        assert_eq!(*tolerances.get(&c("USD")), d("0.05"));
+        assert_eq!(*tolerances.get(&c("EUR")), Decimal::ZERO);
+        assert_eq!(
+            tolerances.__repr__(),
+            "Tolerances(tolerances={USD: 0.05}, default=0)"
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Display};
+use std::ops::Deref;
 use std::sync::LazyLock;
 
 use pyo3::prelude::*;
@@ -41,6 +42,21 @@ impl Account {
             .map(|index| Self::from(&self.0[0..index]))
     }
 
+    /// All ancestors of the account, from the parent up to the root account.
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> {
+        std::iter::successors(self.parent(), Self::parent)
+    }
+
+    /// Check whether this account is a descendant of the given account.
+    ///
+    /// An account is not a child of itself and `Assets:BankTwo` is not a child of `Assets:Bank`.
+    #[must_use]
+    pub fn is_child_of(&self, other: &Self) -> bool {
+        self.0
+            .strip_prefix(&*other.0)
+            .is_some_and(|rest| rest.starts_with(SEPARATOR))
+    }
+
     /// The account components.
     pub fn components(&self) -> impl Iterator<Item = &str> {
         self.0.split(SEPARATOR)
@@ -96,6 +112,14 @@ impl Display for Account {
     }
 }
 
+impl Deref for Account {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl From<&str> for Account {
     fn from(s: &str) -> Self {
         Self(s.into())
@@ -163,6 +187,43 @@ impl RootAccounts {
     }
 }
 
+#[pymethods]
+impl RootAccounts {
+    #[new]
+    #[pyo3(signature = (*, assets="Assets", liabilities="Liabilities", equity="Equity", income="Income", expenses="Expenses"))]
+    fn __new__(
+        assets: &str,
+        liabilities: &str,
+        equity: &str,
+        income: &str,
+        expenses: &str,
+    ) -> Self {
+        Self {
+            assets: assets.into(),
+            liabilities: liabilities.into(),
+            equity: equity.into(),
+            income: income.into(),
+            expenses: expenses.into(),
+        }
+    }
+    #[pyo3(name = "is_balance_sheet_account")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_is_balance_sheet_account(&self, account: Account) -> bool {
+        self.is_balance_sheet_account(&account)
+    }
+    #[pyo3(name = "is_income_statement_account")]
+    #[allow(clippy::needless_pass_by_value)]
+    fn py_is_income_statement_account(&self, account: Account) -> bool {
+        self.is_income_statement_account(&account)
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "RootAccounts(assets='{}', liabilities='{}', equity='{}', income='{}', expenses='{}')",
+            self.assets, self.liabilities, self.equity, self.income, self.expenses
+        )
+    }
+}
+
 /// The accounts that are used in summarizations.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SummarizationAccounts {
@@ -203,6 +264,32 @@ mod tests {
         assert_eq!(acc.parent(), Some(root));
     }
 
+    #[test]
+    fn test_account_ancestors() {
+        let acc: Account = "Assets:US:Bank:Checking".into();
+        assert_eq!(
+            acc.ancestors().collect::<Vec<_>>(),
+            [
+                Account::from("Assets:US:Bank"),
+                Account::from("Assets:US"),
+                Account::from("Assets"),
+            ]
+        );
+        assert_eq!(Account::from("Assets").ancestors().count(), 0);
+    }
+
+    #[test]
+    fn test_account_is_child_of() {
+        let bank: Account = "Assets:Bank".into();
+        assert!(Account::from("Assets:Bank:Checking").is_child_of(&bank));
+        assert!(Account::from("Assets:Bank:Checking:Sub").is_child_of(&bank));
+        assert!(Account::from("Assets:Bank").is_child_of(&"Assets".into()));
+        assert!(!bank.is_child_of(&bank));
+        assert!(!Account::from("Assets:BankTwo").is_child_of(&bank));
+        assert!(!Account::from("Assets:Bank:Checking").is_child_of(&"Assets:Ban".into()));
+        assert!(!Account::from("Assets").is_child_of(&bank));
+    }
+
     #[test]
     fn test_account_root() {
         let root: Account = "Assets".into();
@@ -3,9 +3,13 @@ use std::hash::Hash;
 use std::ops::Neg;
 use std::str::FromStr;
 
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyInt;
 use pyo3::{intern, prelude::*};
 use serde::{Deserialize, Serialize};
 
+use crate::options::BeancountOptions;
+use crate::types::decimal::get_decimal_decimal;
 use crate::types::repr::PyRepresentation;
 use crate::types::{Cost, Currency, Decimal};
 
@@ -34,6 +38,21 @@ impl Amount {
         Self { number, currency }
     }
 
+    /// Create a zero amount in the given currency.
+    #[must_use]
+    pub fn zero(currency: Currency) -> Self {
+        Self {
+            number: Decimal::ZERO,
+            currency,
+        }
+    }
+
+    /// Check whether the number of this amount is zero (regardless of sign and scale).
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.number.is_zero()
+    }
+
     #[must_use]
     pub fn from_cost(cost: &Cost) -> Self {
         Self {
@@ -52,6 +71,100 @@ impl Amount {
     fn __repr__(&self) -> String {
         self.py_repr()
     }
+
+    /// Create a zero amount in the given currency.
+    #[staticmethod]
+    #[pyo3(name = "zero")]
+    fn py_zero(currency: Currency) -> Self {
+        Self::zero(currency)
+    }
+
+    /// Parse an amount from a string like `10.00 USD`.
+    #[staticmethod]
+    fn from_string(string: &str) -> PyResult<Self> {
+        string
+            .parse()
+            .map_err(|()| PyValueError::new_err(format!("Invalid amount: '{string}'")))
+    }
+
+    /// Round the number to the given number of decimal digits (rounding half to even).
+    #[pyo3(name = "round")]
+    fn py_round(&self, ndigits: u32) -> Self {
+        Self::new(self.number.quantize(ndigits), self.currency.clone())
+    }
+
+    /// Round the number to the display precision of the currency.
+    fn round_for(&self, options: &BeancountOptions) -> Self {
+        Self::new(
+            options.quantize_for_display(self.number, &self.currency),
+            self.currency.clone(),
+        )
+    }
+
+    /// Check whether the number of this amount is zero.
+    #[pyo3(name = "is_zero")]
+    fn py_is_zero(&self) -> bool {
+        self.is_zero()
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn __add__(&self, other: Amount) -> PyResult<Self> {
+        self.check_same_currency(&other, "add")?;
+        let number = self.number.checked_add(other.number).ok_or_else(overflow)?;
+        Ok(Self::new(number, self.currency.clone()))
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    fn __sub__(&self, other: Amount) -> PyResult<Self> {
+        self.check_same_currency(&other, "subtract")?;
+        let number = self.number.checked_sub(other.number).ok_or_else(overflow)?;
+        Ok(Self::new(number, self.currency.clone()))
+    }
+
+    /// Multiply by a `Decimal` or `int`.
+    fn __mul__<'py>(&self, other: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let py = other.py();
+        if !(other.is_instance_of::<PyInt>() || other.is_instance(get_decimal_decimal(py)?)?) {
+            return Ok(py.NotImplemented().into_bound(py));
+        }
+        let number = self
+            .number
+            .checked_mul(other.extract()?)
+            .ok_or_else(overflow)?;
+        Ok(Self::new(number, self.currency.clone())
+            .into_pyobject(py)?
+            .into_any())
+    }
+
+    fn __rmul__<'py>(&self, other: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        self.__mul__(other)
+    }
+
+    fn __neg__(&self) -> Self {
+        -self.clone()
+    }
+
+    fn __abs__(&self) -> Self {
+        Self::new(self.number.abs(), self.currency.clone())
+    }
+}
+
+impl Amount {
+    /// Arithmetic on amounts is only possible for amounts of the same currency.
+    fn check_same_currency(&self, other: &Self, operation: &str) -> PyResult<()> {
+        if self.currency == other.currency {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "Cannot {operation} amounts with different currencies: {} and {}",
+                self.currency, other.currency
+            )))
+        }
+    }
+}
+
+fn overflow() -> PyErr {
+    PyValueError::new_err("Overflow in amount arithmetic")
 }
 
 impl<'py> IntoPyObject<'py> for &Amount {
@@ -142,6 +255,7 @@ impl RawAmount {
 #[pymethods]
 impl RawAmount {
     #[new]
+    #[pyo3(signature = (number=None, currency=None))]
     fn __new__(number: Option<Decimal>, currency: Option<Currency>) -> Self {
         Self::new(number, currency)
     }
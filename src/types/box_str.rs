@@ -1,6 +1,7 @@
 use std::convert::Infallible;
 use std::fmt::Display;
 use std::hash::Hash;
+use std::ops::Deref;
 
 use pyo3::prelude::*;
 use pyo3::pybacked::PyBackedStr;
@@ -11,7 +12,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// We are dealing with immutable strings in most places, so avoid the memory of the capacity of a
 /// `Vec`.
-#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct BoxStr(Box<str>);
 
@@ -21,6 +22,14 @@ impl Display for BoxStr {
     }
 }
 
+impl Deref for BoxStr {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl From<&str> for BoxStr {
     fn from(value: &str) -> Self {
         Self(value.into())
@@ -1,57 +1,106 @@
 //! Conversion of uromyces entries to beancount.core.data namedtuples.
+use hashbrown::HashMap;
 use pyo3::IntoPyObjectExt;
 use pyo3::sync::PyOnceLock;
-use pyo3::types::PyNone;
+use pyo3::types::{PyNone, PyString};
 use pyo3::{prelude::*, types::PyAnyMethods, types::PyType};
 
 use crate::types::{
-    Amount, Balance, Close, Commodity, Cost, Custom, CustomValue, Document, Event, Note, Open, Pad,
-    Posting, Price, Query, Transaction,
+    Amount, Balance, Close, Commodity, Cost, Currency, Custom, CustomValue, Document, Entry, Event,
+    Note, Open, Pad, Posting, Price, Query, Transaction,
 };
 
+/// The Python strings for currencies, shared within one conversion pass.
+///
+/// There are usually only a few distinct currencies but a lot of amounts, costs, and prices to
+/// convert, so this avoids creating the same Python strings over and over again.
+#[derive(Default)]
+pub(super) struct CurrencyStrings<'py>(HashMap<Currency, Bound<'py, PyString>>);
+
+impl<'py> CurrencyStrings<'py> {
+    /// Get the Python string for the currency, creating it on first use.
+    fn get(&mut self, py: Python<'py>, currency: &Currency) -> Bound<'py, PyString> {
+        if let Some(string) = self.0.get(currency) {
+            return string.clone();
+        }
+        let Ok(string) = currency.into_pyobject(py);
+        self.0.insert(currency.clone(), string.clone());
+        string
+    }
+}
+
 pub(super) trait ConvertToBeancount {
     /// Convert an object to its matching Beancount type.
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>>;
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>>;
 }
 
 impl<T: ConvertToBeancount> ConvertToBeancount for Option<T> {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         match self {
-            Some(s) => s.convert_to_beancount(py),
+            Some(s) => s.convert_to_beancount(py, strings),
             None => PyNone::get(py).into_bound_py_any(py),
         }
     }
 }
 
 impl<T: ConvertToBeancount> ConvertToBeancount for Vec<T> {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         self.iter()
-            .map(|v| v.convert_to_beancount(py))
+            .map(|v| v.convert_to_beancount(py, strings))
             .collect::<PyResult<Vec<_>>>()?
             .into_bound_py_any(py)
     }
 }
 
 impl ConvertToBeancount for Amount {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static AMOUNT: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         AMOUNT
             .import(py, "beancount.core.amount", "Amount")?
-            .call1((&self.number, &self.currency))
+            .call1((&self.number, strings.get(py, &self.currency)))
     }
 }
 
 impl ConvertToBeancount for Cost {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static AMOUNT: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         AMOUNT
             .import(py, "beancount.core.position", "Cost")?
-            .call1((&self.number, &self.currency, &self.date, &self.label))
+            .call1((
+                &self.number,
+                strings.get(py, &self.currency),
+                &self.date,
+                &self.label,
+            ))
     }
 }
 
 impl ConvertToBeancount for CustomValue {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static VALUE_TYPE: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         VALUE_TYPE
             .import(py, "beancount.parser.grammar", "ValueType")?
@@ -60,31 +109,39 @@ impl ConvertToBeancount for CustomValue {
 }
 
 impl ConvertToBeancount for Posting {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static POSTING: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         POSTING
             .import(py, "beancount.core.data", "Posting")?
             .call1((
                 &self.account,
-                &self.units.convert_to_beancount(py)?,
-                &self.cost.convert_to_beancount(py)?,
-                &self.price.convert_to_beancount(py)?,
+                &self.units.convert_to_beancount(py, strings)?,
+                &self.cost.convert_to_beancount(py, strings)?,
+                &self.price.convert_to_beancount(py, strings)?,
                 &self.flag,
-                self.meta.copy(py)?,
+                self.meta.to_beancount(py)?,
             ))
     }
 }
 
 impl ConvertToBeancount for Balance {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static BALANCE: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         BALANCE
             .import(py, "beancount.core.data", "Balance")?
             .call1((
-                self.meta.copy(py)?,
+                self.meta.to_beancount(py)?,
                 &self.date,
                 &self.account,
-                &self.amount.convert_to_beancount(py)?,
+                &self.amount.convert_to_beancount(py, strings)?,
                 &self.tolerance,
                 PyNone::get(py),
             ))
@@ -92,19 +149,31 @@ impl ConvertToBeancount for Balance {
 }
 
 impl ConvertToBeancount for Commodity {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static COMMODITY: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         COMMODITY
             .import(py, "beancount.core.data", "Commodity")?
-            .call1((self.meta.copy(py)?, &self.date, &self.currency))
+            .call1((
+                self.meta.to_beancount(py)?,
+                &self.date,
+                strings.get(py, &self.currency),
+            ))
     }
 }
 
 impl ConvertToBeancount for Close {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static CLOSE: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         CLOSE.import(py, "beancount.core.data", "Close")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.account,
         ))
@@ -112,24 +181,32 @@ impl ConvertToBeancount for Close {
 }
 
 impl ConvertToBeancount for Custom {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static CUSTOM: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         CUSTOM.import(py, "beancount.core.data", "Custom")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.r#type,
-            self.values.convert_to_beancount(py)?,
+            self.values.convert_to_beancount(py, strings)?,
         ))
     }
 }
 
 impl ConvertToBeancount for Document {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static DOCUMENT: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         DOCUMENT
             .import(py, "beancount.core.data", "Document")?
             .call1((
-                self.meta.copy(py)?,
+                self.meta.to_beancount(py)?,
                 &self.date,
                 &self.account,
                 &self.filename,
@@ -140,10 +217,14 @@ impl ConvertToBeancount for Document {
 }
 
 impl ConvertToBeancount for Event {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static EVENT: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         EVENT.import(py, "beancount.core.data", "Event")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.r#type,
             &self.description,
@@ -152,10 +233,14 @@ impl ConvertToBeancount for Event {
 }
 
 impl ConvertToBeancount for Note {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static NOTE: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         NOTE.import(py, "beancount.core.data", "Note")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.account,
             &self.comment,
@@ -166,18 +251,26 @@ impl ConvertToBeancount for Note {
 }
 
 impl ConvertToBeancount for Open {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static BOOKING: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         static OPEN: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         let booking = BOOKING.import(py, "beancount.core.data", "Booking")?;
         OPEN.import(py, "beancount.core.data", "Open")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.account,
             if self.currencies.is_empty() {
                 PyNone::get(py).into_bound_py_any(py)?
             } else {
-                self.currencies.into_bound_py_any(py)?
+                self.currencies
+                    .iter()
+                    .map(|c| strings.get(py, c))
+                    .collect::<Vec<_>>()
+                    .into_bound_py_any(py)?
             },
             match self.booking {
                 Some(b) => booking.getattr(b.value(py))?,
@@ -188,10 +281,14 @@ impl ConvertToBeancount for Open {
 }
 
 impl ConvertToBeancount for Pad {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static PAD: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         PAD.import(py, "beancount.core.data", "Pad")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.account,
             &self.source_account,
@@ -200,42 +297,93 @@ impl ConvertToBeancount for Pad {
 }
 
 impl ConvertToBeancount for Price {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static PRICE: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         PRICE.import(py, "beancount.core.data", "Price")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
-            &self.currency,
-            self.amount.convert_to_beancount(py)?,
+            strings.get(py, &self.currency),
+            self.amount.convert_to_beancount(py, strings)?,
         ))
     }
 }
 
 impl ConvertToBeancount for Transaction {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static TRANSACTION: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         TRANSACTION
             .import(py, "beancount.core.data", "Transaction")?
             .call1((
-                self.meta.copy(py)?,
+                self.meta.to_beancount(py)?,
                 &self.date,
                 &self.flag,
                 &self.payee,
                 &self.narration,
                 &self.tags,
                 &self.links,
-                self.postings.convert_to_beancount(py)?,
+                self.postings.convert_to_beancount(py, strings)?,
             ))
     }
 }
 impl ConvertToBeancount for Query {
-    fn convert_to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        _strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         static QUERY: PyOnceLock<Py<PyType>> = PyOnceLock::new();
         QUERY.import(py, "beancount.core.data", "Query")?.call1((
-            self.meta.copy(py)?,
+            self.meta.to_beancount(py)?,
             &self.date,
             &self.name,
             &self.query_string,
         ))
     }
 }
+
+impl ConvertToBeancount for Entry {
+    fn convert_to_beancount<'py>(
+        &self,
+        py: Python<'py>,
+        strings: &mut CurrencyStrings<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        match self {
+            Entry::Balance(e) => e.convert_to_beancount(py, strings),
+            Entry::Close(e) => e.convert_to_beancount(py, strings),
+            Entry::Commodity(e) => e.convert_to_beancount(py, strings),
+            Entry::Custom(e) => e.convert_to_beancount(py, strings),
+            Entry::Document(e) => e.convert_to_beancount(py, strings),
+            Entry::Event(e) => e.convert_to_beancount(py, strings),
+            Entry::Note(e) => e.convert_to_beancount(py, strings),
+            Entry::Open(e) => e.convert_to_beancount(py, strings),
+            Entry::Pad(e) => e.convert_to_beancount(py, strings),
+            Entry::Price(e) => e.convert_to_beancount(py, strings),
+            Entry::Query(e) => e.convert_to_beancount(py, strings),
+            Entry::Transaction(e) => e.convert_to_beancount(py, strings),
+        }
+    }
+}
+
+/// Convert the entries to Beancount entries, reusing the currency strings across all of them.
+///
+/// # Errors
+///
+/// Errors if the Beancount types cannot be imported or constructed.
+pub fn convert_entries<'py>(
+    py: Python<'py>,
+    entries: &[Entry],
+) -> PyResult<Vec<Bound<'py, PyAny>>> {
+    let mut strings = CurrencyStrings::default();
+    entries
+        .iter()
+        .map(|e| e.convert_to_beancount(py, &mut strings))
+        .collect()
+}
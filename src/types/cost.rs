@@ -8,7 +8,17 @@ use crate::types::repr::PyRepresentation;
 use crate::types::{BoxStr, Currency, Date, Decimal};
 
 #[derive(
-    Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, FromPyObject, IntoPyObjectRef,
+    Clone,
+    Debug,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    FromPyObject,
+    IntoPyObjectRef,
 )]
 #[serde(transparent)]
 pub struct CostLabel(BoxStr);
@@ -32,8 +42,11 @@ impl From<String> for CostLabel {
 }
 
 /// A cost (basically an Amount + date and label).
+///
+/// Costs are ordered by date, then number, then currency and finally label (no label first),
+/// which gives a stable order for the lots of an inventory.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
-#[pyclass(frozen, eq, get_all, module = "uromyces", skip_from_py_object)]
+#[pyclass(frozen, eq, ord, get_all, module = "uromyces", skip_from_py_object)]
 pub struct Cost {
     /// The per-unit cost.
     pub number: Decimal,
@@ -60,6 +73,22 @@ impl Cost {
     }
 }
 
+impl PartialOrd for Cost {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cost {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.date
+            .cmp(&other.date)
+            .then_with(|| self.number.cmp(&other.number))
+            .then_with(|| self.currency.cmp(&other.currency))
+            .then_with(|| self.label.cmp(&other.label))
+    }
+}
+
 impl Display for Cost {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} {}, {}", self.number, self.currency, self.date)?;
@@ -132,6 +161,7 @@ pub struct CostSpec {
 #[pymethods]
 impl CostSpec {
     #[new]
+    #[pyo3(signature = (number_per=None, number_total=None, currency=None, date=None, label=None, merge=None))]
     fn __new__(
         number_per: Option<Decimal>,
         number_total: Option<Decimal>,
@@ -202,4 +232,36 @@ mod tests {
         );
         assert_eq!(cost_with_label.to_string(), "1 EUR, 2012-12-12, lot-1");
     }
+
+    #[test]
+    fn test_cost_ordering() {
+        let cost = |number: i64, currency: &str, date: &str, label: Option<&str>| {
+            Cost::new(
+                Decimal::new(number, 0),
+                currency.into(),
+                Date::try_from_str(date).unwrap(),
+                label.map(Into::into),
+            )
+        };
+        let mut costs = [
+            cost(1, "USD", "2020-02-01", None),
+            cost(5, "USD", "2020-01-01", Some("b")),
+            cost(5, "USD", "2020-01-01", Some("a")),
+            cost(5, "EUR", "2020-01-01", None),
+            cost(5, "USD", "2020-01-01", None),
+            cost(2, "USD", "2020-01-01", None),
+        ];
+        costs.sort();
+        assert_eq!(
+            costs.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "2 USD, 2020-01-01",
+                "5 EUR, 2020-01-01",
+                "5 USD, 2020-01-01",
+                "5 USD, 2020-01-01, a",
+                "5 USD, 2020-01-01, b",
+                "1 USD, 2020-02-01",
+            ]
+        );
+    }
 }
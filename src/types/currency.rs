@@ -1,6 +1,8 @@
 use std::fmt::{Debug, Display};
+use std::sync::LazyLock;
 
 use pyo3::prelude::*;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::types::interned_string::InternedString;
@@ -24,6 +26,26 @@ use crate::types::interned_string::InternedString;
 )]
 pub struct Currency(InternedString);
 
+impl Currency {
+    /// Check whether this is a valid currency name.
+    ///
+    /// A valid currency name:
+    /// - Has at most 24 characters
+    /// - Starts with an uppercase letter (or a slash for a name with at least two characters)
+    /// - Ends with an uppercase letter or digit
+    /// - Otherwise contains only uppercase letters, digits, and the characters `'._-`
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        CURRENCY_RE.is_match(&self.0)
+    }
+}
+
+/// Regex for valid currency names.
+static CURRENCY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[A-Z](?:[A-Z0-9'._-]{0,22}[A-Z0-9])?|/[A-Z0-9'._-]{0,22}[A-Z0-9])$")
+        .expect("valid currency regex")
+});
+
 impl Debug for Currency {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str: &str = &self.0;
@@ -49,3 +71,36 @@ impl From<&str> for Currency {
         Self(s.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_is_valid() {
+        for valid in [
+            "USD",
+            "AAPL",
+            "X",
+            "VANGUARD_500",
+            "BRK.B",
+            "/NQH21",
+            "ABCDEFGHIJKLMNOPQRSTUVWX",
+        ] {
+            assert!(Currency::from(valid).is_valid(), "{valid}");
+        }
+        for invalid in [
+            "",
+            "usd",
+            "Usd",
+            "1ABC",
+            "USD-",
+            "/",
+            "TOO-LONG-FOR-A-CURRENCY-NAME",
+            "ABCDEFGHIJKLMNOPQRSTUVWXY",
+            "US D",
+        ] {
+            assert!(!Currency::from(invalid).is_valid(), "{invalid}");
+        }
+    }
+}
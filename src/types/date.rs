@@ -1,6 +1,6 @@
 use std::fmt::{Debug, Display};
 
-use chrono::{Datelike, Days, NaiveDate};
+use chrono::{Datelike, Days, Months, NaiveDate};
 use pyo3::{prelude::*, types::PyDate};
 use serde::{Deserialize, Serialize};
 
@@ -60,6 +60,51 @@ impl Date {
     pub fn previous_day(self) -> Option<Self> {
         self.0.checked_sub_days(ONE_DAY).map(Self)
     }
+
+    /// Add the given (possibly negative) number of days to this date.
+    ///
+    /// Returns `None` if the resulting date is out of range.
+    #[must_use]
+    pub fn add_days(self, days: i64) -> Option<Self> {
+        let abs = Days::new(days.unsigned_abs());
+        if days < 0 {
+            self.0.checked_sub_days(abs)
+        } else {
+            self.0.checked_add_days(abs)
+        }
+        .map(Self)
+    }
+
+    /// Add the given (possibly negative) number of months to this date.
+    ///
+    /// If the day does not exist in the resulting month, the last day of that month is used, so
+    /// e.g. 2024-01-31 plus one month is 2024-02-29. Returns `None` if the resulting date is out
+    /// of range.
+    #[must_use]
+    pub fn add_months(self, months: i32) -> Option<Self> {
+        let abs = Months::new(months.unsigned_abs());
+        if months < 0 {
+            self.0.checked_sub_months(abs)
+        } else {
+            self.0.checked_add_months(abs)
+        }
+        .map(Self)
+    }
+
+    /// Get the first day of the month of this date.
+    #[must_use]
+    pub fn start_of_month(self) -> Self {
+        Self(self.0 - Days::new(u64::from(self.0.day0())))
+    }
+
+    /// Iterate over all dates from `begin` (inclusive) to `end` (exclusive).
+    pub fn range(begin: Self, end: Self) -> impl Iterator<Item = Self> {
+        begin
+            .0
+            .iter_days()
+            .take_while(move |date| *date < end.0)
+            .map(Self)
+    }
 }
 
 impl Display for Date {
@@ -91,6 +136,16 @@ impl<'py> IntoPyObject<'py> for &Date {
     }
 }
 
+impl<'py> IntoPyObject<'py> for Date {
+    type Target = PyDate;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        (&self).into_pyobject(py)
+    }
+}
+
 impl<'py> FromPyObject<'_, 'py> for Date {
     type Error = PyErr;
 
@@ -173,6 +228,60 @@ mod test {
         assert!(MIN_DATE.previous_day().is_none());
     }
 
+    #[test]
+    fn date_add_days() {
+        let date = Date::from_ymd_opt(2024, 2, 28).unwrap();
+        assert_eq!(date.add_days(1).unwrap().to_string(), "2024-02-29");
+        assert_eq!(date.add_days(2).unwrap().to_string(), "2024-03-01");
+        assert_eq!(date.add_days(0), Some(date));
+        assert_eq!(date.add_days(-59).unwrap().to_string(), "2023-12-31");
+        let date = Date::from_ymd_opt(2023, 2, 28).unwrap();
+        assert_eq!(date.add_days(1).unwrap().to_string(), "2023-03-01");
+
+        assert!(MIN_DATE.add_days(-1).is_none());
+        assert!(date.add_days(i64::MAX).is_none());
+        assert!(date.add_days(i64::MIN).is_none());
+    }
+
+    #[test]
+    fn date_add_months() {
+        let date = Date::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(date.add_months(1).unwrap().to_string(), "2024-02-29");
+        assert_eq!(date.add_months(2).unwrap().to_string(), "2024-03-31");
+        assert_eq!(date.add_months(3).unwrap().to_string(), "2024-04-30");
+        assert_eq!(date.add_months(13).unwrap().to_string(), "2025-02-28");
+        assert_eq!(date.add_months(-2).unwrap().to_string(), "2023-11-30");
+        assert_eq!(date.add_months(-12).unwrap().to_string(), "2023-01-31");
+        assert_eq!(date.add_months(0), Some(date));
+        let date = Date::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(date.add_months(12).unwrap().to_string(), "2025-02-28");
+        assert_eq!(date.add_months(48).unwrap().to_string(), "2028-02-29");
+
+        assert!(MIN_DATE.add_months(-1).is_none());
+    }
+
+    #[test]
+    fn date_start_of_month() {
+        let date = Date::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(date.start_of_month().to_string(), "2024-02-01");
+        let date = Date::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(date.start_of_month(), date);
+    }
+
+    #[test]
+    fn date_range() {
+        let begin = Date::from_ymd_opt(2024, 2, 27).unwrap();
+        let end = Date::from_ymd_opt(2024, 3, 2).unwrap();
+        assert_eq!(
+            Date::range(begin, end)
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            ["2024-02-27", "2024-02-28", "2024-02-29", "2024-03-01"]
+        );
+        assert_eq!(Date::range(begin, begin).count(), 0);
+        assert_eq!(Date::range(end, begin).count(), 0);
+    }
+
     #[test]
     fn date_debug() {
         let date = Date::from_ymd_opt(2023, 6, 15).unwrap();
@@ -9,6 +9,7 @@ use pyo3::prelude::*;
 use pyo3::pybacked::PyBackedStr;
 use pyo3::sync::PyOnceLock;
 use pyo3::types::{PyAnyMethods, PyTuple, PyType};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -38,6 +39,7 @@ impl Decimal {
     pub const ZERO: Decimal = Self(rust_decimal::Decimal::ZERO);
     pub const ONE: Decimal = Self(rust_decimal::Decimal::ONE);
     pub const TWO: Decimal = Self(rust_decimal::Decimal::TWO);
+    pub const HALF: Decimal = Self(rust_decimal::Decimal::from_parts(5, 0, 0, false, 1));
 
     pub const MAX_SCALE: u32 = rust_decimal::Decimal::MAX_SCALE;
 
@@ -74,6 +76,24 @@ impl Decimal {
         self.0.checked_div(rhs.0).map(Self)
     }
 
+    /// Checked addition. Returns `None` if overflow happens.
+    #[must_use]
+    pub(crate) fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    /// Checked subtraction. Returns `None` if overflow happens.
+    #[must_use]
+    pub(crate) fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
+
+    /// Checked multiplication. Returns `None` if overflow happens.
+    #[must_use]
+    pub(crate) fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.0.checked_mul(rhs.0).map(Self)
+    }
+
     /// Check if sign of the Decimal is positive (also true for 0).
     #[must_use]
     pub(crate) fn is_sign_positive(&self) -> bool {
@@ -106,6 +126,18 @@ impl Decimal {
         self.0.scale()
     }
 
+    /// Convert to an integer if this is a whole number written without any decimal digits.
+    ///
+    /// So `5` gives an integer, but `5.0` does not.
+    #[must_use]
+    pub(crate) fn to_integer(self) -> Option<i64> {
+        if self.0.scale() == 0 {
+            self.0.to_i64()
+        } else {
+            None
+        }
+    }
+
     /// Scale ONE to the scale of self or None if the scale of self is 0.
     #[must_use]
     pub(crate) fn scaled_one(&self) -> Option<Self> {
@@ -122,6 +154,40 @@ impl Decimal {
         }
     }
 
+    /// Round to the given number of decimal digits, padding with zeros if necessary.
+    ///
+    /// For midpoints, this rounds to the nearest even digit (like Python's `Decimal.quantize`).
+    #[must_use]
+    pub(crate) fn quantize(&self, scale: u32) -> Self {
+        let mut rounded = self.0.round_dp(scale);
+        rounded.rescale(scale);
+        Self(rounded)
+    }
+
+    /// Render the Decimal with commas as thousands separators.
+    #[must_use]
+    pub(crate) fn to_string_with_commas(self) -> String {
+        let string = self.0.to_string();
+        let (sign, unsigned) = string
+            .strip_prefix('-')
+            .map_or(("", string.as_str()), |s| ("-", s));
+        let (integer, fraction) = unsigned
+            .split_once('.')
+            .map_or((unsigned, None), |(i, f)| (i, Some(f)));
+        let mut res = String::from(sign);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                res.push(',');
+            }
+            res.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            res.push('.');
+            res.push_str(fraction);
+        }
+        res
+    }
+
     /// Extract a Decimal from a string (ignoring commas).
     pub(crate) fn from_str_with_commas(s: &str) -> Result<Self, DecimalError> {
         if s.contains(',') {
@@ -136,6 +202,11 @@ impl Decimal {
     pub(crate) fn from_str_exact(s: &str) -> Result<Self, DecimalError> {
         Ok(rust_decimal::Decimal::from_str_exact(s).map(Self)?)
     }
+
+    /// Extract a Decimal from a string in scientific notation (like `1.5e3`).
+    pub(crate) fn from_scientific(s: &str) -> Result<Self, DecimalError> {
+        Ok(rust_decimal::Decimal::from_scientific(s).map(Self)?)
+    }
 }
 
 impl Display for Decimal {
@@ -225,11 +296,20 @@ impl<'a, 'py> FromPyObject<'a, 'py> for Decimal {
 
     fn extract(obj: Borrowed<'a, 'py, PyAny>) -> Result<Self, Self::Error> {
         let str = &obj.str()?.extract::<PyBackedStr>()?;
-        let dec = rust_decimal::Decimal::from_str(str).or_else(|_| {
-            rust_decimal::Decimal::from_scientific(str)
-                .map_err(|e| PyValueError::new_err(e.to_string()))
-        })?;
-        Ok(Decimal(dec))
+        rust_decimal::Decimal::from_str(str)
+            .map(Decimal)
+            .or_else(|_| Decimal::from_scientific(str))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+impl<'py> IntoPyObject<'py> for Decimal {
+    type Target = PyAny;
+    type Output = Bound<'py, Self::Target>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> Result<Self::Output, Self::Error> {
+        (&self).into_pyobject(py)
     }
 }
 
@@ -290,6 +370,30 @@ mod tests {
         assert!(Decimal::from_str_exact("++1").is_err());
         assert!(Decimal::from_str_exact("11111111111111111111111111111111111111111").is_err());
         assert!(Decimal::from_str_exact("0.000000000000000000000000000000000000001").is_err());
+
+        assert!(Decimal::from_str_exact("1.5e3").is_err());
+        assert_eq!(Decimal::from_scientific("1.5e3"), Ok(Decimal::new(1500, 0)));
+        assert_eq!(Decimal::from_scientific("25E-2"), Ok(Decimal::d("0.25")));
+        assert!(Decimal::from_scientific("1.5").is_err());
+    }
+
+    #[test]
+    fn test_decimal_quantize_and_commas() {
+        assert_eq!(Decimal::d("1.005").quantize(2).to_string(), "1.00");
+        assert_eq!(Decimal::d("1.015").quantize(2).to_string(), "1.02");
+        assert_eq!(Decimal::d("1.5").quantize(3).to_string(), "1.500");
+        assert_eq!(Decimal::d("-2.5").quantize(0).to_string(), "-2");
+
+        assert_eq!(Decimal::d("0.5").to_string_with_commas(), "0.5");
+        assert_eq!(Decimal::d("123").to_string_with_commas(), "123");
+        assert_eq!(
+            Decimal::d("1234.5678").to_string_with_commas(),
+            "1,234.5678"
+        );
+        assert_eq!(
+            Decimal::d("-1234567.00").to_string_with_commas(),
+            "-1,234,567.00"
+        );
     }
 
     #[test]
@@ -1,3 +1,5 @@
+use std::hash::{Hash, Hasher};
+
 use pyo3::exceptions::{PyKeyError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::sync::PyOnceLock;
@@ -23,9 +25,11 @@ pub enum MetaValue {
     Bool(bool),
     Amount(Amount),
     Currency(Currency),
+    /// Integer - used for lineno and for metadata numbers without decimal digits.
+    ///
+    /// This needs to come before the `Decimal` variant so that Python ints are kept as ints.
+    Integer(i64),
     Decimal(Decimal),
-    /// Integer - used for lineno
-    Integer(u32),
 }
 
 impl From<&str> for MetaValue {
@@ -83,6 +87,14 @@ impl Meta {
     pub fn push(&mut self, value: MetaKeyValuePair) {
         self.0.push(value);
     }
+    /// Set the value for the key, replacing an existing value in place or appending it.
+    pub fn insert(&mut self, key: &str, value: Option<MetaValue>) {
+        if let Some(kv) = self.0.iter_mut().find(|m| m.key == key) {
+            kv.value = value;
+        } else {
+            self.0.push(MetaKeyValuePair::new(key.to_owned(), value));
+        }
+    }
     pub fn append(&mut self, other: &mut Meta) {
         self.0.append(&mut other.0);
     }
@@ -98,6 +110,18 @@ impl Meta {
     pub fn contains_key(&self, key: &str) -> bool {
         self.0.iter().any(|m| m.key == key)
     }
+    /// Get the value for a key (`None` if the key is missing or has no value).
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        self.0
+            .iter()
+            .find(|m| m.key == key)
+            .and_then(|m| m.value.as_ref())
+    }
+    /// Iterate over the key-value pairs in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&MetaValue>)> {
+        self.0.iter().map(|m| (m.key.as_str(), m.value.as_ref()))
+    }
 
     /// Convert the metadata to a Python dict with the provied filename and lineno.
     ///
@@ -123,11 +147,20 @@ impl Meta {
         Ok(meta)
     }
 
-    fn get(&self, key: &str) -> Option<&MetaValue> {
-        self.0
-            .iter()
-            .find(|m| m.key == key)
-            .and_then(|m| m.value.as_ref())
+    /// Like [`Meta::to_py_dict`], but with `Decimal`s for integer numbers (like in Beancount).
+    fn to_beancount_py_dict<'py>(
+        &self,
+        py: Python<'py>,
+        filename: Option<&Filename>,
+        line: Option<LineNumber>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let meta = self.to_py_dict(py, filename, line)?;
+        for kv in &self.0 {
+            if let Some(MetaValue::Integer(int)) = kv.value {
+                meta.set_item(&kv.key, Decimal::new(int, 0))?;
+            }
+        }
+        Ok(meta)
     }
 
     fn get_as_pyany<'py>(&self, key: &str, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
@@ -139,8 +172,32 @@ impl Meta {
     }
 }
 
+/// The position of the source text of a directive in its file.
+///
+/// Lines are 1-based (like the `lineno` of entries), columns are 0-based byte offsets into the
+/// line. The end position is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[pyclass(frozen, eq, hash, get_all, module = "uromyces", skip_from_py_object)]
+pub struct SourceSpan {
+    /// The 1-based line number of the start of the directive.
+    pub start_line: LineNumber,
+    /// The 0-based column of the start of the directive.
+    pub start_col: u32,
+    /// The 1-based line number of the end of the directive.
+    pub end_line: LineNumber,
+    /// The 0-based column of the end of the directive.
+    pub end_col: u32,
+    /// The byte offset of the start of the directive.
+    pub start_byte: usize,
+    /// The byte offset of the end of the directive.
+    pub end_byte: usize,
+}
+
 /// The entry metadata which all entries carry.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// The span is not serialised and ignored for comparisons and hashing, so that entries that are
+/// loaded from JSON are equal to the parsed ones.
+#[derive(Clone, Debug)]
 #[pyclass(frozen, mapping, module = "uromyces", skip_from_py_object)]
 pub struct EntryMeta {
     /// Entry metadata.
@@ -151,9 +208,34 @@ pub struct EntryMeta {
     /// The 1-based line number.
     #[pyo3(get)]
     pub lineno: LineNumber,
+    /// The position of the source text of the directive (if the entry was parsed from a file).
+    #[pyo3(get)]
+    pub span: Option<SourceSpan>,
+}
+
+impl PartialEq for EntryMeta {
+    fn eq(&self, other: &Self) -> bool {
+        self.meta == other.meta && self.filename == other.filename && self.lineno == other.lineno
+    }
+}
+
+impl Eq for EntryMeta {}
+
+impl Hash for EntryMeta {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.meta.hash(state);
+        self.filename.hash(state);
+        self.lineno.hash(state);
+    }
 }
 
 impl EntryMeta {
+    /// Convert to a dict for the corresponding Beancount entry.
+    pub(crate) fn to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.meta
+            .to_beancount_py_dict(py, Some(&self.filename), Some(self.lineno))
+    }
+
     /// Create a new entry metadata.
     #[must_use]
     pub fn new(meta: Meta, filename: Filename, lineno: LineNumber) -> Self {
@@ -161,9 +243,17 @@ impl EntryMeta {
             meta,
             filename,
             lineno,
+            span: None,
         }
     }
 
+    /// Set the position of the source text of the directive.
+    #[must_use]
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
     /// Create a new entry metadata (with empty metadata).
     #[must_use]
     pub fn empty(filename: Filename, lineno: LineNumber) -> Self {
@@ -171,6 +261,7 @@ impl EntryMeta {
             meta: Meta::default(),
             filename,
             lineno,
+            span: None,
         }
     }
 
@@ -192,7 +283,7 @@ impl EntryMeta {
     pub fn get(&self, key: &str) -> Option<MetaValue> {
         match key {
             "filename" => Some(MetaValue::String(self.filename.to_string())),
-            "lineno" => Some(MetaValue::Integer(self.lineno)),
+            "lineno" => Some(MetaValue::Integer(self.lineno.into())),
             _ => self.meta.get(key).cloned(),
         }
     }
@@ -208,6 +299,7 @@ impl EntryMeta {
             meta,
             filename: filename.ok_or_else(|| PyValueError::new_err("Missing filename"))?,
             lineno: lineno.ok_or_else(|| PyValueError::new_err("Missing lineno"))?,
+            span: None,
         })
     }
 
@@ -327,6 +419,9 @@ impl EntryMeta {
     }
 }
 
+/// Metadata key that marks postings whose units, price, or cost were interpolated.
+pub(crate) const AUTOMATIC_META: &str = "__automatic__";
+
 /// The posting metadata which postings carry.
 /// Unlike `EntryMeta`, filename and line are optional since postings
 /// may be generated by plugins without source locations.
@@ -344,6 +439,12 @@ pub struct PostingMeta {
 }
 
 impl PostingMeta {
+    /// Convert to a dict for the corresponding Beancount posting.
+    pub(crate) fn to_beancount<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        self.meta
+            .to_beancount_py_dict(py, self.filename.as_ref(), self.lineno)
+    }
+
     /// Create a new posting metadata with just a filename.
     #[must_use]
     pub(crate) fn with_filename(filename: Filename) -> Self {
@@ -354,6 +455,27 @@ impl PostingMeta {
         }
     }
 
+    /// Mark the posting as interpolated (like Beancount does with the `__automatic__` key).
+    pub(crate) fn set_automatic(&mut self) {
+        if !self.is_automatic() {
+            self.meta.push(MetaKeyValuePair::new(
+                AUTOMATIC_META.to_owned(),
+                Some(MetaValue::Bool(true)),
+            ));
+        }
+    }
+
+    /// The metadata key-value pairs (without filename and line number).
+    pub(crate) fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    /// Whether the posting has been marked as interpolated.
+    #[must_use]
+    pub fn is_automatic(&self) -> bool {
+        matches!(self.meta.get(AUTOMATIC_META), Some(MetaValue::Bool(true)))
+    }
+
     pub(crate) fn keys(&self) -> Vec<String> {
         let mut keys = Vec::new();
         if self.filename.is_some() {
@@ -463,11 +585,12 @@ impl<'de> Visitor<'de> for PostingMetaVisitor {
 
         while let Some(key) = access.next_key::<String>()? {
             match key.as_str() {
+                // Missing positions are serialized as `null` in some places.
                 "filename" => {
-                    filename = Some(access.next_value()?);
+                    filename = access.next_value()?;
                 }
                 "lineno" => {
-                    lineno = Some(access.next_value()?);
+                    lineno = access.next_value()?;
                 }
                 _ => {
                     meta.push(MetaKeyValuePair::new(key, access.next_value()?));
@@ -520,10 +643,9 @@ impl<'de> Deserialize<'de> for EntryMeta {
                     filename: posting_meta
                         .filename
                         .ok_or_else(|| serde::de::Error::missing_field("filename"))?,
-                    lineno: posting_meta
-                        .lineno
-                        .ok_or_else(|| serde::de::Error::missing_field("lineno"))?,
+                    lineno: posting_meta.lineno.unwrap_or(0),
                     meta: posting_meta.meta,
+                    span: None,
                 })
             })
     }
@@ -611,24 +733,39 @@ impl PostingMeta {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_meta_insert_replaces_in_place() {
+        let mut meta = Meta::default();
+        meta.insert("a", Some("1".into()));
+        meta.insert("b", Some("2".into()));
+        meta.insert("c", None);
+        assert_eq!(meta.get("b"), Some(&"2".into()));
+        assert_eq!(meta.get("c"), None);
+        assert_eq!(meta.get("missing"), None);
+
+        meta.insert("a", Some(MetaValue::Integer(3)));
+        meta.insert("c", Some("4".into()));
+        let pairs = meta.iter().collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                ("a", Some(&MetaValue::Integer(3))),
+                ("b", Some(&"2".into())),
+                ("c", Some(&"4".into())),
+            ]
+        );
+    }
+
     #[test]
     fn test_entry_meta_serialize() {
-        let meta = EntryMeta {
-            filename: Filename::new_dummy("test"),
-            lineno: 42,
-            meta: Meta::default(),
-        };
+        let meta = EntryMeta::empty(Filename::new_dummy("test"), 42);
         let json = serde_json::to_string(&meta).unwrap();
         assert_eq!(json, r#"{"filename":"<test>","lineno":42}"#);
     }
 
     #[test]
     fn test_entry_meta_serialize_with_meta() {
-        let mut meta = EntryMeta {
-            filename: Filename::new_dummy("test"),
-            lineno: 42,
-            meta: Meta::default(),
-        };
+        let mut meta = EntryMeta::empty(Filename::new_dummy("test"), 42);
         meta.add_meta("foo", "bar".into());
         let json = serde_json::to_string(&meta).unwrap();
         assert_eq!(json, r#"{"filename":"<test>","lineno":42,"foo":"bar"}"#);
@@ -645,11 +782,7 @@ mod tests {
 
     #[test]
     fn test_entry_meta_roundtrip() {
-        let mut original = EntryMeta {
-            filename: Filename::new_dummy("example"),
-            lineno: 100,
-            meta: Meta::default(),
-        };
+        let mut original = EntryMeta::empty(Filename::new_dummy("example"), 100);
         original.add_meta("note", "test note".into());
 
         let json = serde_json::to_string(&original).unwrap();
@@ -682,6 +815,21 @@ mod tests {
         assert!(meta.meta.is_empty());
     }
 
+    #[test]
+    fn test_meta_deserialize_null_positions() {
+        let json = r#"{"filename":null,"lineno":null}"#;
+        let meta: PostingMeta = serde_json::from_str(json).unwrap();
+        assert!(meta.filename.is_none());
+        assert!(meta.lineno.is_none());
+
+        let meta: EntryMeta = serde_json::from_str(r#"{"filename":"<test>"}"#).unwrap();
+        assert_eq!(meta.lineno, 0);
+        let meta: EntryMeta =
+            serde_json::from_str(r#"{"filename":"<test>","lineno":null}"#).unwrap();
+        assert_eq!(meta.lineno, 0);
+        assert!(serde_json::from_str::<EntryMeta>(r#"{"lineno":1}"#).is_err());
+    }
+
     #[test]
     fn test_posting_meta_deserialize_with_all() {
         let json = r#"{"filename":"<test>","lineno":42,"note":"hello"}"#;
@@ -51,11 +51,16 @@
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 
-use pyo3::exceptions::PyTypeError;
-use pyo3::types::{PyBool, PyDate, PyInt, PyString};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyBool, PyDate, PyDict, PyInt, PyList, PyString};
 use pyo3::{PyTypeInfo, prelude::*};
 use serde::{Deserialize, Serialize};
 
+use crate::errors::UroError;
+use crate::inventory::{Inventory, Position};
+use crate::options::BeancountOptions;
+use crate::tolerances::Tolerances;
+
 mod account;
 mod amount;
 mod booking;
@@ -70,6 +75,7 @@ mod interned_string;
 mod metadata;
 mod paths;
 mod repr;
+mod sort_order;
 mod tags_links;
 
 pub(crate) use account::JoinAccount;
@@ -82,12 +88,15 @@ pub use currency::Currency;
 pub use date::{Date, MIN_DATE};
 pub use decimal::Decimal;
 pub use flag::Flag;
-pub use metadata::{EntryMeta, Meta, MetaKeyValuePair, MetaValue, PostingMeta};
+pub use metadata::{EntryMeta, Meta, MetaKeyValuePair, MetaValue, PostingMeta, SourceSpan};
 pub use paths::{AbsoluteUTF8Path, Filename};
+pub use sort_order::EntrySortOrder;
 pub use tags_links::TagsLinks;
 
-use convert_to_beancount::ConvertToBeancount;
+pub use convert_to_beancount::convert_entries;
+use convert_to_beancount::{ConvertToBeancount, CurrencyStrings};
 use decimal::get_decimal_decimal;
+use repr::PyRepresentation;
 
 /// The type to use for line numbers in file positions.
 pub type LineNumber = u32;
@@ -123,6 +132,105 @@ pub struct Plugin {
     pub config: Option<String>,
 }
 
+impl Plugin {
+    /// Parse the config of the plugin as JSON (`null` if the plugin has no config).
+    ///
+    /// # Errors
+    ///
+    /// Errors (mentioning the plugin name) if the config is not valid JSON.
+    pub fn config_as_json(&self) -> Result<serde_json::Value, UroError> {
+        let Some(config) = &self.config else {
+            return Ok(serde_json::Value::Null);
+        };
+        serde_json::from_str(config).map_err(|e| {
+            UroError::new(format!(
+                "Invalid JSON config for plugin '{}': {e}",
+                self.name
+            ))
+        })
+    }
+}
+
+/// Convert a JSON value to the corresponding Python object (like `json.loads` would).
+fn json_to_py<'py>(py: Python<'py>, value: &serde_json::Value) -> PyResult<Bound<'py, PyAny>> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => PyBool::new(py, *b).to_owned().into_any(),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into_pyobject(py)?.into_any()
+            } else if let Some(u) = n.as_u64() {
+                u.into_pyobject(py)?.into_any()
+            } else {
+                n.as_f64().into_pyobject(py)?.into_any()
+            }
+        }
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(values) => PyList::new(
+            py,
+            values
+                .iter()
+                .map(|v| json_to_py(py, v))
+                .collect::<PyResult<Vec<_>>>()?,
+        )?
+        .into_any(),
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, v) in map {
+                dict.set_item(key, json_to_py(py, v)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+#[pymethods]
+impl Plugin {
+    /// Parse the config of the plugin as JSON (`None` if the plugin has no config).
+    #[pyo3(name = "config_as_json")]
+    fn py_config_as_json<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let value = self
+            .config_as_json()
+            .map_err(|e| PyValueError::new_err(e.message().to_owned()))?;
+        json_to_py(py, &value)
+    }
+}
+
+/// A keyword argument to `_replace` for an optional field.
+///
+/// Unlike for an `Option`, passing `None` explicitly unsets the field, while leaving the
+/// argument out keeps the current value.
+enum ReplaceOptional<T> {
+    Keep,
+    Replace(Option<T>),
+}
+
+impl<T: Clone> ReplaceOptional<T> {
+    fn or(self, current: Option<&T>) -> Option<T> {
+        match self {
+            Self::Keep => current.cloned(),
+            Self::Replace(value) => value,
+        }
+    }
+}
+
+impl<'a, 'py, T> FromPyObject<'a, 'py> for ReplaceOptional<T>
+where
+    T: FromPyObject<'a, 'py>,
+    PyErr: From<T::Error>,
+{
+    type Error = PyErr;
+
+    fn extract(obj: Borrowed<'a, 'py, PyAny>) -> Result<Self, Self::Error> {
+        if obj.is_none() {
+            Ok(Self::Replace(None))
+        } else {
+            Ok(Self::Replace(Some(obj.extract::<T>()?)))
+        }
+    }
+}
+
 /// A custom value - a value and associated type.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[pyclass(frozen, from_py_object, module = "uromyces")]
@@ -272,6 +380,15 @@ impl Posting {
             flag,
         }
     }
+
+    /// Whether some number of this posting was interpolated during booking.
+    #[getter]
+    fn interpolated(&self) -> bool {
+        self.meta.is_automatic()
+    }
+    fn __repr__(&self) -> String {
+        self.py_repr()
+    }
 }
 
 impl Posting {
@@ -321,6 +438,97 @@ pub struct Balance {
     pub tolerance: Option<Decimal>,
 }
 
+impl Balance {
+    /// What this assertion checks, as given by uromyces-specific metadata.
+    ///
+    /// With `uromyces_cost_basis: TRUE`, the assertion checks the total cost of all lots held at
+    /// cost in the currency of the asserted amount. With `uromyces_lot_cost: AMOUNT` (and
+    /// optionally `uromyces_lot_date: DATE`), it is restricted to the lots with that per-unit cost
+    /// (and date).
+    ///
+    /// # Errors
+    ///
+    /// If one of these metadata values has the wrong type, if a lot date is given without a lot
+    /// cost, or if both the cost basis and a lot cost are given.
+    pub fn target(&self) -> Result<BalanceTarget, BalanceMetaError> {
+        let cost_basis = match self.meta.get(COST_BASIS_META) {
+            None => false,
+            Some(MetaValue::Bool(cost_basis)) => cost_basis,
+            Some(value) => return Err(BalanceMetaError::InvalidCostBasis(value)),
+        };
+        let cost = match self.meta.get(LOT_COST_META) {
+            None => None,
+            Some(MetaValue::Amount(amount)) => Some(amount),
+            Some(value) => return Err(BalanceMetaError::InvalidLotCost(value)),
+        };
+        let date = match self.meta.get(LOT_DATE_META) {
+            None => None,
+            Some(MetaValue::Date(date)) => Some(date),
+            Some(value) => return Err(BalanceMetaError::InvalidLotDate(value)),
+        };
+        match (cost_basis, cost, date) {
+            (true, Some(_), _) => Err(BalanceMetaError::CostBasisWithLotCost),
+            (_, None, Some(_)) => Err(BalanceMetaError::LotDateWithoutLotCost),
+            (_, Some(cost), date) => Ok(BalanceTarget::Lots(cost, date)),
+            (true, None, None) => Ok(BalanceTarget::CostBasis),
+            (false, None, None) => Ok(BalanceTarget::Units),
+        }
+    }
+}
+
+/// What a balance assertion checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BalanceTarget {
+    /// The number of units (like in Beancount).
+    Units,
+    /// The total cost of all lots held at cost.
+    CostBasis,
+    /// The number of units in the lots with the given per-unit cost (and date).
+    Lots(Amount, Option<Date>),
+}
+
+const COST_BASIS_META: &str = "uromyces_cost_basis";
+const LOT_COST_META: &str = "uromyces_lot_cost";
+const LOT_DATE_META: &str = "uromyces_lot_date";
+
+/// Invalid uromyces-specific metadata on a balance assertion.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BalanceMetaError {
+    InvalidCostBasis(MetaValue),
+    InvalidLotCost(MetaValue),
+    InvalidLotDate(MetaValue),
+    LotDateWithoutLotCost,
+    CostBasisWithLotCost,
+}
+impl std::error::Error for BalanceMetaError {}
+impl std::fmt::Display for BalanceMetaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Self::InvalidCostBasis(v) => {
+                write!(f, "Invalid {COST_BASIS_META} '{v}' (expected a boolean)")
+            }
+            Self::InvalidLotCost(v) => {
+                write!(f, "Invalid {LOT_COST_META} '{v}' (expected an amount)")
+            }
+            Self::InvalidLotDate(v) => {
+                write!(f, "Invalid {LOT_DATE_META} '{v}' (expected a date)")
+            }
+            Self::LotDateWithoutLotCost => {
+                write!(
+                    f,
+                    "{LOT_DATE_META} is only valid together with {LOT_COST_META}"
+                )
+            }
+            Self::CostBasisWithLotCost => {
+                write!(
+                    f,
+                    "{COST_BASIS_META} cannot be combined with {LOT_COST_META}"
+                )
+            }
+        }
+    }
+}
+
 /// An account close entry.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[pyclass(frozen, eq, get_all, from_py_object, module = "uromyces")]
@@ -481,10 +689,19 @@ impl Transaction {
             postings,
         }
     }
+
+    /// The residual of the transaction, i.e., the sum of the weights of its postings.
+    ///
+    /// The weights take costs and prices into account, so for a balanced transaction, this is
+    /// empty or at least small with respect to the inferred tolerances.
+    #[must_use]
+    pub fn residual(&self) -> Inventory {
+        crate::booking::compute_residual(&self.postings)
+    }
 }
 
 /// The Beancount entries (raw, after parsing).
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, IntoPyObject)]
 #[serde(tag = "t")]
 pub enum RawEntry {
     Balance(Balance),
@@ -595,8 +812,13 @@ impl Balance {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Balance"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -648,8 +870,13 @@ impl Close {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Close"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -701,8 +928,13 @@ impl Commodity {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Commodity"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -758,8 +990,13 @@ impl Custom {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Custom"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -815,8 +1052,13 @@ impl Document {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Document"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -872,8 +1114,13 @@ impl Event {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Event"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -929,8 +1176,13 @@ impl Note {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Note"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -958,7 +1210,7 @@ impl Open {
     }
 
     #[allow(clippy::too_many_arguments)]
-    #[pyo3(signature = (*, meta=None, date=None, tags=None, links=None, account=None, currencies=None, booking=None))]
+    #[pyo3(signature = (*, meta=None, date=None, tags=None, links=None, account=None, currencies=None, booking=ReplaceOptional::Keep))]
     fn _replace(
         &self,
         meta: Option<EntryMeta>,
@@ -967,7 +1219,7 @@ impl Open {
         links: Option<TagsLinks>,
         account: Option<Account>,
         currencies: Option<Vec<Currency>>,
-        booking: Option<Booking>,
+        booking: ReplaceOptional<Booking>,
     ) -> Self {
         Self {
             meta: meta.unwrap_or_else(|| self.meta.clone()),
@@ -976,7 +1228,7 @@ impl Open {
             links: links.unwrap_or_else(|| self.links.clone()),
             account: account.unwrap_or_else(|| self.account.clone()),
             currencies: currencies.unwrap_or_else(|| self.currencies.clone()),
-            booking: booking.or(self.booking),
+            booking: booking.or(self.booking.as_ref()),
         }
     }
     fn __repr__(&self) -> String {
@@ -991,8 +1243,13 @@ impl Open {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Open"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -1048,8 +1305,13 @@ impl Pad {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Pad"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -1105,8 +1367,13 @@ impl Price {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Price"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -1162,8 +1429,13 @@ impl Query {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Query"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 #[pymethods]
@@ -1217,6 +1489,19 @@ impl Transaction {
             postings: postings.unwrap_or_else(|| self.postings.clone()),
         }
     }
+    /// The tolerances that are inferred from the postings to check whether this balances.
+    fn inferred_tolerances(&self, options: &BeancountOptions) -> Tolerances {
+        Tolerances::infer_from_booked(&self.postings, options)
+    }
+    /// The residual of the transaction (as a list of amounts).
+    #[pyo3(name = "residual")]
+    fn py_residual(&self) -> Vec<Amount> {
+        self.residual().iter().map(|pos| pos.units()).collect()
+    }
+    /// Whether the transaction balances, using the inferred tolerances.
+    fn is_balanced(&self, options: &BeancountOptions) -> bool {
+        crate::booking::is_balanced(&self.postings, options)
+    }
     fn __repr__(&self) -> String {
         format!("<{self:?}>")
     }
@@ -1229,8 +1514,13 @@ impl Transaction {
         let entry: BorrowedEntry = self.into();
         serde_json::to_string(&entry).map_err(|e| PyTypeError::new_err(e.to_string()))
     }
+    /// The type of this entry (a class attribute), as used for the `t` tag in the JSON.
+    #[classattr]
+    fn kind() -> &'static str {
+        "Transaction"
+    }
     fn _convert<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        self.convert_to_beancount(py)
+        self.convert_to_beancount(py, &mut CurrencyStrings::default())
     }
 }
 
@@ -1254,6 +1544,63 @@ impl Entry {
         }
     }
 
+    /// Get the entry metadata mutably.
+    #[must_use]
+    pub(crate) fn meta_mut(&mut self) -> &mut EntryMeta {
+        match self {
+            Self::Balance(e) => &mut e.meta,
+            Self::Close(e) => &mut e.meta,
+            Self::Commodity(e) => &mut e.meta,
+            Self::Custom(e) => &mut e.meta,
+            Self::Document(e) => &mut e.meta,
+            Self::Event(e) => &mut e.meta,
+            Self::Note(e) => &mut e.meta,
+            Self::Open(e) => &mut e.meta,
+            Self::Pad(e) => &mut e.meta,
+            Self::Price(e) => &mut e.meta,
+            Self::Query(e) => &mut e.meta,
+            Self::Transaction(e) => &mut e.meta,
+        }
+    }
+
+    /// Get the tags of the entry.
+    #[must_use]
+    pub(crate) fn tags(&self) -> &TagsLinks {
+        match self {
+            Self::Balance(e) => &e.tags,
+            Self::Close(e) => &e.tags,
+            Self::Commodity(e) => &e.tags,
+            Self::Custom(e) => &e.tags,
+            Self::Document(e) => &e.tags,
+            Self::Event(e) => &e.tags,
+            Self::Note(e) => &e.tags,
+            Self::Open(e) => &e.tags,
+            Self::Pad(e) => &e.tags,
+            Self::Price(e) => &e.tags,
+            Self::Query(e) => &e.tags,
+            Self::Transaction(e) => &e.tags,
+        }
+    }
+
+    /// Get the links of the entry.
+    #[must_use]
+    pub(crate) fn links(&self) -> &TagsLinks {
+        match self {
+            Self::Balance(e) => &e.links,
+            Self::Close(e) => &e.links,
+            Self::Commodity(e) => &e.links,
+            Self::Custom(e) => &e.links,
+            Self::Document(e) => &e.links,
+            Self::Event(e) => &e.links,
+            Self::Note(e) => &e.links,
+            Self::Open(e) => &e.links,
+            Self::Pad(e) => &e.links,
+            Self::Price(e) => &e.links,
+            Self::Query(e) => &e.links,
+            Self::Transaction(e) => &e.links,
+        }
+    }
+
     /// Get the entry date.
     #[must_use]
     pub(crate) fn date(&self) -> Date {
@@ -1274,9 +1621,11 @@ impl Entry {
     }
 
     crate::macros::as_inner_method!(as_balance, Balance);
+    crate::macros::as_inner_method!(as_close, Close);
+    crate::macros::as_inner_method!(as_commodity, Commodity);
     crate::macros::as_inner_method!(as_document, Document);
+    crate::macros::as_inner_method!(as_open, Open);
     crate::macros::as_inner_method!(as_pad, Pad);
-    #[cfg(test)]
     crate::macros::as_inner_method!(as_price, Price);
     crate::macros::as_inner_method!(as_transaction, Transaction);
 
@@ -1284,28 +1633,10 @@ impl Entry {
     ///
     /// Is used to implement the [`Ord`] and [`PartialOrd`] traits below.
     ///
-    /// Entries are sorted by date, and on a day are sorted as follows:
-    ///
-    /// - Open
-    /// - Balance
-    /// - ... all others
-    /// - Document
-    /// - Close
-    fn sort_key(&self) -> (&Date, i8) {
-        match self {
-            Self::Balance(e) => (&e.date, -1),
-            Self::Close(e) => (&e.date, 2),
-            Self::Commodity(e) => (&e.date, 0),
-            Self::Custom(e) => (&e.date, 0),
-            Self::Document(e) => (&e.date, 1),
-            Self::Event(e) => (&e.date, 0),
-            Self::Note(e) => (&e.date, 0),
-            Self::Open(e) => (&e.date, -2),
-            Self::Pad(e) => (&e.date, 0),
-            Self::Price(e) => (&e.date, 0),
-            Self::Query(e) => (&e.date, 0),
-            Self::Transaction(e) => (&e.date, 0),
-        }
+    /// Entries are sorted by date, and on a day by the default order of their types (see
+    /// [`EntrySortOrder::DEFAULT`]). Use [`EntrySortOrder::sort`] for a different order.
+    fn sort_key(&self) -> (Date, i8) {
+        (self.date(), EntrySortOrder::DEFAULT.ordinal(self))
     }
 
     /// Get the accounts for the entry.
@@ -1326,12 +1657,58 @@ impl Entry {
             Self::Transaction(e) => e.postings.iter().map(|p| &p.account).collect(),
         }
     }
+
+    /// Get the currencies for the entry (in order of appearance, possibly with duplicates).
+    #[must_use]
+    pub fn currencies(&self) -> Vec<&Currency> {
+        match self {
+            Self::Balance(e) => vec![&e.amount.currency],
+            Self::Close(..)
+            | Self::Custom(..)
+            | Self::Document(..)
+            | Self::Event(..)
+            | Self::Note(..)
+            | Self::Pad(..)
+            | Self::Query(..) => Vec::new(),
+            Self::Commodity(e) => vec![&e.currency],
+            Self::Open(e) => e.currencies.iter().collect(),
+            Self::Price(e) => vec![&e.currency, &e.amount.currency],
+            Self::Transaction(e) => e
+                .postings
+                .iter()
+                .flat_map(|p| {
+                    std::iter::once(&p.units.currency)
+                        .chain(p.cost.as_ref().map(|c| &c.currency))
+                        .chain(p.price.as_ref().map(|p| &p.currency))
+                })
+                .collect(),
+        }
+    }
 }
 
 impl RawEntry {
     #[cfg(test)]
     crate::macros::as_inner_method!(as_raw_transaction, RawTransaction);
 
+    /// Get the entry metadata.
+    #[must_use]
+    pub(crate) fn meta(&self) -> &EntryMeta {
+        match self {
+            Self::Balance(e) => &e.meta,
+            Self::Close(e) => &e.meta,
+            Self::Commodity(e) => &e.meta,
+            Self::Custom(e) => &e.meta,
+            Self::Document(e) => &e.meta,
+            Self::Event(e) => &e.meta,
+            Self::Note(e) => &e.meta,
+            Self::Open(e) => &e.meta,
+            Self::Pad(e) => &e.meta,
+            Self::Price(e) => &e.meta,
+            Self::Query(e) => &e.meta,
+            Self::RawTransaction(e) => &e.meta,
+        }
+    }
+
     /// Sort key for an entry.
     ///
     /// Is used to implement the `[Ord]` and `[PartialOrd]` traits below.
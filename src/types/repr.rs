@@ -1,6 +1,37 @@
 //! Implementations for the `__repr__` Python dunder method.
 
-use crate::types::{Amount, Cost, CostLabel, CostSpec, Currency, Date, Decimal, RawAmount};
+use crate::types::{
+    Account, Amount, Cost, CostLabel, CostSpec, Currency, Date, Decimal, Flag, MetaValue, Posting,
+    PostingMeta, RawAmount,
+};
+
+/// Quote a string like Python does for `str` objects.
+///
+/// Single quotes are used unless the string contains a single quote but no double quotes.
+fn quote(s: &str) -> String {
+    let quote = if s.contains('\'') && !s.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push(quote);
+    for c in s.chars() {
+        match c {
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c == quote => {
+                res.push('\\');
+                res.push(c);
+            }
+            c => res.push(c),
+        }
+    }
+    res.push(quote);
+    res
+}
 
 pub(crate) trait PyRepresentation {
     /// Build the Python string representation of the object.
@@ -26,6 +57,12 @@ impl PyRepresentation for bool {
     }
 }
 
+impl PyRepresentation for Account {
+    fn py_repr(&self) -> String {
+        format!("'{self}'")
+    }
+}
+
 impl PyRepresentation for CostLabel {
     fn py_repr(&self) -> String {
         format!("'{self}'")
@@ -55,6 +92,12 @@ impl PyRepresentation for Decimal {
     }
 }
 
+impl PyRepresentation for Flag {
+    fn py_repr(&self) -> String {
+        format!("'{self}'")
+    }
+}
+
 impl PyRepresentation for Amount {
     fn py_repr(&self) -> String {
         format!(
@@ -101,9 +144,55 @@ impl PyRepresentation for CostSpec {
     }
 }
 
+impl PyRepresentation for MetaValue {
+    fn py_repr(&self) -> String {
+        match self {
+            MetaValue::String(string) | MetaValue::Tag(string) => quote(string),
+            MetaValue::Account(account) => account.py_repr(),
+            MetaValue::Date(date) => date.py_repr(),
+            MetaValue::Bool(bool) => bool.py_repr(),
+            MetaValue::Amount(amount) => amount.py_repr(),
+            MetaValue::Currency(currency) => currency.py_repr(),
+            MetaValue::Integer(int) => int.to_string(),
+            MetaValue::Decimal(decimal) => decimal.py_repr(),
+        }
+    }
+}
+
+impl PyRepresentation for PostingMeta {
+    fn py_repr(&self) -> String {
+        let filename = self
+            .filename
+            .as_ref()
+            .map(|f| format!("'filename': {}", quote(f)));
+        let lineno = self.lineno.map(|l| format!("'lineno': {l}"));
+        let items = self.meta().iter().map(|(key, value)| {
+            let value = value.map_or_else(|| "None".to_string(), PyRepresentation::py_repr);
+            format!("{}: {value}", quote(key))
+        });
+        let items = filename.into_iter().chain(lineno).chain(items);
+        format!("{{{}}}", items.collect::<Vec<_>>().join(", "))
+    }
+}
+
+impl PyRepresentation for Posting {
+    fn py_repr(&self) -> String {
+        format!(
+            "Posting(account={}, units={}, cost={}, price={}, flag={}, meta={})",
+            self.account.py_repr(),
+            self.units.py_repr(),
+            self.cost.py_repr(),
+            self.price.py_repr(),
+            self.flag.py_repr(),
+            self.meta.py_repr(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::test_utils::{c, d};
+    use crate::test_utils::{a, c, d};
+    use crate::types::Filename;
 
     use super::*;
 
@@ -205,4 +294,38 @@ mod tests {
             "CostSpec(number_per=None, number_total=None, currency=None, date=None, label=None, merge=False)"
         );
     }
+
+    #[test]
+    fn test_posting_repr() {
+        let mut posting = Posting::new_simple(
+            Filename::new_dummy("string"),
+            Account::from("Assets:Cash"),
+            a("10 USD"),
+        );
+        assert_eq!(
+            posting.py_repr(),
+            "Posting(account='Assets:Cash', units=Amount(number=Decimal('10'), currency='USD'), cost=None, price=None, flag=None, meta={'filename': '<string>'})"
+        );
+
+        let date = Date::from_ymd_opt(2024, 1, 15).unwrap();
+        posting.cost = Some(Cost::new(d("1.5"), c("EUR"), date, None));
+        posting.flag = Some(Flag::WARNING);
+        posting.meta.lineno = Some(3);
+        posting.meta.set_automatic();
+        assert_eq!(
+            posting.py_repr(),
+            "Posting(account='Assets:Cash', units=Amount(number=Decimal('10'), currency='USD'), cost=Cost(number=Decimal('1.5'), currency='EUR', date=datetime.date(2024, 1, 15), label=None), price=None, flag='!', meta={'filename': '<string>', 'lineno': 3, '__automatic__': True})"
+        );
+    }
+
+    #[test]
+    fn test_meta_value_repr() {
+        assert_eq!(MetaValue::from("it's").py_repr(), r#""it's""#);
+        assert_eq!(
+            MetaValue::from(r#"it's "quoted""#).py_repr(),
+            r#"'it\'s "quoted"'"#
+        );
+        assert_eq!(MetaValue::from("a\\b\nc").py_repr(), r"'a\\b\nc'");
+        assert_eq!(MetaValue::Integer(2).py_repr(), "2");
+    }
 }
@@ -0,0 +1,141 @@
+use super::{Date, Entry, LineNumber};
+
+/// The order of entries of the different types on the same day.
+///
+/// Entries are sorted by date, then by the ordinal of their type and then by their line number.
+/// Types can share an ordinal, then their entries are just sorted by line number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntrySortOrder {
+    pub balance: i8,
+    pub close: i8,
+    pub commodity: i8,
+    pub custom: i8,
+    pub document: i8,
+    pub event: i8,
+    pub note: i8,
+    pub open: i8,
+    pub pad: i8,
+    pub price: i8,
+    pub query: i8,
+    pub transaction: i8,
+}
+
+impl EntrySortOrder {
+    /// The default order of the types (as used by the [`Ord`] implementation of [`Entry`]).
+    ///
+    /// On a day, entries are sorted as follows:
+    ///
+    /// - Open
+    /// - Balance
+    /// - ... all others
+    /// - Document
+    /// - Close
+    pub const DEFAULT: Self = Self {
+        balance: -1,
+        close: 2,
+        commodity: 0,
+        custom: 0,
+        document: 1,
+        event: 0,
+        note: 0,
+        open: -2,
+        pad: 0,
+        price: 0,
+        query: 0,
+        transaction: 0,
+    };
+
+    /// Get the ordinal for the type of the given entry.
+    #[must_use]
+    pub fn ordinal(&self, entry: &Entry) -> i8 {
+        match entry {
+            Entry::Balance(..) => self.balance,
+            Entry::Close(..) => self.close,
+            Entry::Commodity(..) => self.commodity,
+            Entry::Custom(..) => self.custom,
+            Entry::Document(..) => self.document,
+            Entry::Event(..) => self.event,
+            Entry::Note(..) => self.note,
+            Entry::Open(..) => self.open,
+            Entry::Pad(..) => self.pad,
+            Entry::Price(..) => self.price,
+            Entry::Query(..) => self.query,
+            Entry::Transaction(..) => self.transaction,
+        }
+    }
+
+    /// The sort key for the given entry.
+    fn sort_key(&self, entry: &Entry) -> (Date, i8, LineNumber) {
+        (entry.date(), self.ordinal(entry), entry.meta().lineno)
+    }
+
+    /// Sort the given entries in this order.
+    pub fn sort(&self, entries: &mut [Entry]) {
+        entries.sort_by_key(|e| self.sort_key(e));
+    }
+}
+
+impl Default for EntrySortOrder {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Filename;
+
+    #[test]
+    fn test_sort_with_custom_order() {
+        let ledger = crate::load_string(
+            r#"
+2020-01-01 open Assets:Cash
+2020-01-02 note Assets:Cash "note"
+2020-01-02 *
+  Assets:Cash  1 USD
+  Assets:Cash -1 USD
+2020-01-02 price EUR 1.1 USD
+2020-01-02 event "location" "Berlin"
+2020-01-02 balance Assets:Cash 0 USD
+"#,
+            Filename::new_dummy("string"),
+        );
+        let type_names = |entries: &[Entry]| {
+            entries
+                .iter()
+                .map(|e| match e {
+                    Entry::Balance(..) => "balance",
+                    Entry::Event(..) => "event",
+                    Entry::Note(..) => "note",
+                    Entry::Open(..) => "open",
+                    Entry::Price(..) => "price",
+                    Entry::Transaction(..) => "transaction",
+                    _ => "other",
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut entries = ledger.entries.clone();
+        EntrySortOrder::DEFAULT.sort(&mut entries);
+        assert_eq!(entries, ledger.entries);
+        assert_eq!(
+            type_names(&entries),
+            ["open", "balance", "note", "transaction", "price", "event"]
+        );
+
+        // prices before transactions and notes at the end, events share the ordinal of
+        // transactions and are sorted by line.
+        let order = EntrySortOrder {
+            price: -1,
+            balance: -1,
+            note: 3,
+            ..EntrySortOrder::default()
+        };
+        order.sort(&mut entries);
+        assert_eq!(
+            type_names(&entries),
+            ["open", "price", "balance", "transaction", "event", "note"]
+        );
+    }
+}
@@ -35,6 +35,11 @@ impl TagsLinks {
         self.0.iter().any(|v| *v == value)
     }
 
+    /// Iterate over the tags or links in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
     /// Reomve a tag or link. Returns whether it was present in the set.
     pub fn remove(&mut self, value: &str) -> bool {
         if let Some(index) = self.0.iter().position(|v| *v == value) {
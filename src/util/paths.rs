@@ -1,4 +1,4 @@
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
 
 use glob;
 
@@ -9,7 +9,10 @@ use crate::types::AbsoluteUTF8Path;
 pub enum GlobIncludeError {
     BasePathHasNoParent,
     GlobReadError,
+    HomeDirNotFound,
     InvalidGlobPattern(String),
+    LiteralFileNotFound,
+    NoMatchingFiles,
     NonUnicodePath,
 }
 
@@ -23,9 +26,18 @@ impl std::fmt::Display for GlobIncludeError {
             Self::GlobReadError => {
                 write!(f, "IO error on reading glob")
             }
+            Self::HomeDirNotFound => {
+                write!(f, "could not determine the home directory to expand '~'")
+            }
             Self::InvalidGlobPattern(msg) => {
                 write!(f, "Invalid glob pattern: {msg}")
             }
+            Self::LiteralFileNotFound => {
+                write!(f, "file does not exist")
+            }
+            Self::NoMatchingFiles => {
+                write!(f, "glob does not match any files")
+            }
             Self::NonUnicodePath => {
                 write!(f, "encountered non-Unicode path during glob")
             }
@@ -33,44 +45,97 @@ impl std::fmt::Display for GlobIncludeError {
     }
 }
 
+/// Canonicalize a path that was found for an include.
+fn canonicalize(path: &Path) -> Result<AbsoluteUTF8Path, GlobIncludeError> {
+    match dunce::canonicalize(path) {
+        Ok(p) => p
+            .as_path()
+            .try_into()
+            .map_err(|_| GlobIncludeError::NonUnicodePath),
+        Err(_) => Err(GlobIncludeError::GlobReadError),
+    }
+}
+
+/// Split off a leading `~` (the home directory) from an include.
+///
+/// Returns the remainder of the include relative to the home directory.
+fn strip_home_prefix(include: &str) -> Option<&str> {
+    let rest = include.strip_prefix('~')?;
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(std::path::is_separator)
+    }
+}
+
 /// For the given include directive, find matching files.
+///
+/// If the include resolves to an existing file, it is included directly, so that filenames
+/// containing glob metacharacters like `[` can be included. Otherwise, the include is treated as
+/// a glob pattern, which needs to match at least one file. A leading `~` is expanded to the home
+/// directory.
 // TODO: consider restricting the allowed kinds of patterns.
 pub fn glob_include(
     base_path: &AbsoluteUTF8Path,
     include: &str,
+) -> Result<Vec<AbsoluteUTF8Path>, GlobIncludeError> {
+    glob_include_with_home(base_path, include, std::env::home_dir())
+}
+
+/// Like [`glob_include`], with the home directory to expand `~` to.
+fn glob_include_with_home(
+    base_path: &AbsoluteUTF8Path,
+    include: &str,
+    home_dir: Option<PathBuf>,
 ) -> Result<Vec<AbsoluteUTF8Path>, GlobIncludeError> {
     let has_root = matches!(
         Path::new(include).components().next(),
         Some(Component::Prefix(..) | Component::RootDir)
     );
 
-    let pattern = if has_root {
-        include.to_owned()
+    let (literal, pattern) = if has_root {
+        (include.into(), include.to_owned())
     } else {
-        let dirname = base_path
-            .as_ref()
-            .parent()
-            .ok_or(GlobIncludeError::BasePathHasNoParent)?;
-        dirname
+        let (dirname, include) = if let Some(rest) = strip_home_prefix(include) {
+            let home_dir = home_dir
+                .filter(|h| h.is_absolute())
+                .ok_or(GlobIncludeError::HomeDirNotFound)?;
+            (home_dir, rest)
+        } else {
+            let dirname = base_path
+                .as_ref()
+                .parent()
+                .ok_or(GlobIncludeError::BasePathHasNoParent)?;
+            (dirname.to_path_buf(), include)
+        };
+        let dirname_str = dirname.to_str().ok_or(GlobIncludeError::NonUnicodePath)?;
+        // Only the include itself is a pattern, the directory might contain metacharacters.
+        let pattern = Path::new(&glob::Pattern::escape(dirname_str))
             .join(include)
             .to_str()
             .expect("paths joined from unicode parts to be unicode")
-            .to_owned()
+            .to_owned();
+        (dirname.join(include), pattern)
     };
 
-    glob::glob(&pattern)
+    if literal.is_file() {
+        return Ok(vec![canonicalize(&literal)?]);
+    }
+    if !include.contains(['*', '?', '[']) {
+        return Err(GlobIncludeError::LiteralFileNotFound);
+    }
+
+    let paths = glob::glob(&pattern)
         .map_err(|e| GlobIncludeError::InvalidGlobPattern(e.msg.to_owned()))?
         .map(|glob_result| match glob_result {
             Err(_) => Err(GlobIncludeError::GlobReadError),
-            Ok(path) => match dunce::canonicalize(path) {
-                Ok(p) => p
-                    .as_path()
-                    .try_into()
-                    .map_err(|_| GlobIncludeError::NonUnicodePath),
-                Err(_) => Err(GlobIncludeError::GlobReadError),
-            },
+            Ok(path) => canonicalize(&path),
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if paths.is_empty() {
+        return Err(GlobIncludeError::NoMatchingFiles);
+    }
+    Ok(paths)
 }
 
 #[cfg(test)]
@@ -93,6 +158,55 @@ mod tests {
         assert!(msg.contains("wildcards"));
     }
 
+    #[test]
+    fn test_literal_include() {
+        let src_lib: AbsoluteUTF8Path = std::env::current_dir()
+            .unwrap()
+            .join("src/lib.rs")
+            .as_path()
+            .try_into()
+            .unwrap();
+        let res = glob_include(&src_lib, "types/mod.rs").unwrap();
+        assert_eq!(res.len(), 1);
+        assert!(res[0].as_ref().ends_with("types/mod.rs"));
+        assert!(matches!(
+            glob_include(&src_lib, "does-not-exist.rs"),
+            Err(GlobIncludeError::LiteralFileNotFound)
+        ));
+        assert!(matches!(
+            glob_include(&src_lib, "does-not-exist-*.rs"),
+            Err(GlobIncludeError::NoMatchingFiles)
+        ));
+    }
+
+    #[test]
+    fn test_home_include() {
+        let src_lib: AbsoluteUTF8Path = std::env::current_dir()
+            .unwrap()
+            .join("src/lib.rs")
+            .as_path()
+            .try_into()
+            .unwrap();
+        let fake_home = std::env::current_dir().unwrap().join("src/types");
+        let res = glob_include_with_home(&src_lib, "~/mod.rs", Some(fake_home.clone())).unwrap();
+        assert_eq!(res.len(), 1);
+        assert!(res[0].as_ref().ends_with("types/mod.rs"));
+        let res = glob_include_with_home(&src_lib, "~/*.rs", Some(fake_home.clone())).unwrap();
+        assert!(res.len() > 6);
+
+        // only a leading `~` (followed by a separator) is expanded
+        assert!(matches!(
+            glob_include_with_home(&src_lib, "~mod.rs", Some(fake_home)),
+            Err(GlobIncludeError::LiteralFileNotFound)
+        ));
+        assert!(matches!(
+            glob_include_with_home(&src_lib, "~/mod.rs", None),
+            Err(GlobIncludeError::HomeDirNotFound)
+        ));
+        // relative includes are not affected by the home directory
+        assert!(glob_include_with_home(&src_lib, "types/mod.rs", None).is_ok());
+    }
+
     #[test]
     fn test_glob() {
         let src_lib = std::env::current_dir().unwrap().join("src/lib.rs");
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// A simple timer that can be used to log the time that certain steps took.
 pub(crate) struct SimpleTimer {
@@ -17,10 +17,16 @@ impl SimpleTimer {
         self.time = Instant::now();
     }
 
-    /// Report the elapsed time since init or the last log and reset.
-    pub fn elapsed(&mut self, step: &str) -> String {
+    /// Get the elapsed time since init or the last lap and reset.
+    pub fn lap(&mut self) -> Duration {
         let elapsed = self.time.elapsed();
         self.reset();
+        elapsed
+    }
+
+    /// Report the elapsed time since init or the last log and reset.
+    pub fn elapsed(&mut self, step: &str) -> String {
+        let elapsed = self.lap();
         format!(
             "{:>4}.{:03}ms - {step}",
             elapsed.as_millis(),
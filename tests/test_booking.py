@@ -0,0 +1,106 @@
+from __future__ import annotations
+
+import datetime
+from decimal import Decimal
+from typing import TYPE_CHECKING
+
+from uromyces import Amount
+from uromyces import compute_residual
+from uromyces import Cost
+from uromyces import is_balanced
+from uromyces import Posting
+from uromyces import posting_weight
+from uromyces import Transaction
+
+if TYPE_CHECKING:
+    from uromyces import Ledger
+
+
+def test_compute_residual_and_is_balanced(load_doc: Ledger) -> None:
+    """
+    option "operating_currency" "USD"
+    """
+    options = load_doc.options
+    cash = Posting("Assets:Cash", Amount(Decimal("-100.00"), "USD"))
+    stock = Posting(
+        "Assets:Stock",
+        Amount(Decimal(10), "HOOL"),
+        Cost(Decimal("10.00"), "USD", datetime.date(2020, 1, 1), None),
+    )
+    assert compute_residual([cash, stock]) == []
+    assert is_balanced([cash, stock], options)
+
+    cash_off_by_one = Posting("Assets:Cash", Amount(Decimal("-99.00"), "USD"))
+    assert compute_residual([cash_off_by_one, stock]) == [
+        Amount(Decimal("1.00"), "USD")
+    ]
+    assert not is_balanced([cash_off_by_one, stock], options)
+
+    # small residuals are within the tolerance inferred from the units
+    stock_rounded = Posting(
+        "Assets:Stock",
+        Amount(Decimal(10), "HOOL"),
+        Cost(Decimal("9.9996"), "USD", datetime.date(2020, 1, 1), None),
+    )
+    assert compute_residual([cash, stock_rounded]) == [
+        Amount(Decimal("-0.0040"), "USD")
+    ]
+    assert is_balanced([cash, stock_rounded], options)
+
+    assert compute_residual([]) == []
+    assert is_balanced([], options)
+
+
+def test_posting_weight() -> None:
+    units = Amount(Decimal(10), "HOOL")
+    cost = Cost(Decimal("10.00"), "USD", datetime.date(2020, 1, 1), None)
+    price = Amount(Decimal("12.00"), "USD")
+
+    assert posting_weight(Posting("Assets:Stock", units)) == units
+    assert posting_weight(
+        Posting("Assets:Stock", units, price=price)
+    ) == Amount(Decimal("120.00"), "USD")
+    # the cost takes precedence over the price
+    assert posting_weight(
+        Posting("Assets:Stock", units, cost=cost, price=price)
+    ) == Amount(Decimal("100.00"), "USD")
+
+
+def test_transaction_residual(load_doc: Ledger) -> None:
+    """
+    2020-01-01 open Assets:Cash
+    2020-01-01 open Assets:Stock
+
+    2020-01-02 * "Buy at cost, but pay too little"
+      Assets:Stock    10 HOOL {10.00 USD}
+      Assets:Cash   -99.00 USD
+
+    2020-01-03 * "Exchange at a price"
+      Assets:Cash   -100.00 USD @ 0.90 EUR
+      Assets:Cash     90.00 EUR
+    """
+    buy, exchange = [
+        e for e in load_doc.entries if isinstance(e, Transaction)
+    ]
+    assert buy.residual() == [Amount(Decimal("1.00"), "USD")]
+    assert not buy.is_balanced(load_doc.options)
+    assert exchange.residual() == []
+    assert exchange.is_balanced(load_doc.options)
+
+
+def test_booking_error_kind(load_doc: Ledger) -> None:
+    """
+    2020-01-01 open Assets:Cash
+    2020-01-01 open Assets:Stock
+
+    2020-01-01 * "Buy"
+      Assets:Stock    10 HOOL {10.00 USD}
+      Assets:Cash
+
+    2020-01-02 * "Sell more than there is"
+      Assets:Stock   -20 HOOL {10.00 USD}
+      Assets:Cash
+    """
+    (error,) = load_doc.errors
+    assert error.kind == "insufficient_lots"
+    assert error.message.startswith("Not enough lots in inventory")
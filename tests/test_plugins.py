@@ -2,9 +2,11 @@ from __future__ import annotations
 
 from typing import TYPE_CHECKING
 
+import pytest
 from beancount.plugins.auto_accounts import auto_insert_open
 
 from uromyces._plugins import import_plugin
+from uromyces._uromyces import load_string
 
 if TYPE_CHECKING:
     from uromyces import Ledger
@@ -89,3 +91,22 @@ def test_insert_pythonpath_not_found(load_doc: Ledger) -> None:
     errors = load_doc.errors
     assert errors
     assert errors[0].message.startswith("Importing plugin 't' failed")
+
+
+def test_plugin_config_as_json() -> None:
+    ledger = load_string(
+        r"""
+plugin "json" "{\"accounts\": [\"Assets:Cash\"], \"on\": true}"
+plugin "no_config"
+plugin "invalid" "not json"
+""",
+        "<string>",
+    )
+    json_plugin, no_config, invalid = ledger.plugins
+    assert json_plugin.config_as_json() == {
+        "accounts": ["Assets:Cash"],
+        "on": True,
+    }
+    assert no_config.config_as_json() is None
+    with pytest.raises(ValueError, match="Invalid JSON config for plugin"):
+        invalid.config_as_json()
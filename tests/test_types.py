@@ -11,16 +11,21 @@ import pytest
 from beancount.core import amount
 from beancount.core import data
 
+from uromyces import account_ancestors
+from uromyces import account_is_child_of
 from uromyces import Amount
 from uromyces import Balance
 from uromyces import Close
 from uromyces import Commodity
+from uromyces import convert_entries
 from uromyces import Cost
 from uromyces import Custom
 from uromyces import CustomValue
 from uromyces import Document
+from uromyces import entry_from_json
 from uromyces import EntryMeta
 from uromyces import Event
+from uromyces import load_string
 from uromyces import Note
 from uromyces import Open
 from uromyces import Pad
@@ -30,13 +35,70 @@ from uromyces import Query
 from uromyces import RawAmount
 from uromyces import Transaction
 from uromyces._convert import beancount_to_uromyces
+from uromyces._uromyces import Booking
 from uromyces._uromyces import CostSpec
+from uromyces._uromyces import date_add_days
+from uromyces._uromyces import date_add_months
+from uromyces._uromyces import date_range
+from uromyces._uromyces import date_start_of_month
+from uromyces._uromyces import RootAccounts
 
 if TYPE_CHECKING:
     from uromyces import Directive
     from uromyces import Ledger
 
 
+def test_account_helpers() -> None:
+    assert account_ancestors("Assets:US:Bank:Checking") == [
+        "Assets:US:Bank",
+        "Assets:US",
+        "Assets",
+    ]
+    assert account_ancestors("Assets") == []
+
+    assert account_is_child_of("Assets:Bank:Checking", "Assets:Bank")
+    assert account_is_child_of("Assets:Bank:Checking", "Assets")
+    assert not account_is_child_of("Assets:Bank", "Assets:Bank")
+    assert not account_is_child_of("Assets:BankTwo", "Assets:Bank")
+    assert not account_is_child_of("Assets:Bank:Checking", "Assets:Ban")
+
+
+def test_date_helpers() -> None:
+    assert date_add_days(date(2024, 2, 28), 1) == date(2024, 2, 29)
+    assert date_add_days(date(2023, 2, 28), 1) == date(2023, 3, 1)
+    assert date_add_days(date(2024, 1, 1), -1) == date(2023, 12, 31)
+    assert date_add_months(date(2024, 1, 31), 1) == date(2024, 2, 29)
+    assert date_add_months(date(2023, 1, 31), 1) == date(2023, 2, 28)
+    assert date_add_months(date(2024, 3, 31), -1) == date(2024, 2, 29)
+    assert date_start_of_month(date(2024, 2, 29)) == date(2024, 2, 1)
+    assert date_range(date(2024, 2, 28), date(2024, 3, 2)) == [
+        date(2024, 2, 28),
+        date(2024, 2, 29),
+        date(2024, 3, 1),
+    ]
+    assert date_range(date(2024, 3, 2), date(2024, 2, 28)) == []
+    with pytest.raises(ValueError, match=r"out of range"):
+        date_add_months(date(2024, 1, 1), 2**31 - 1)
+
+
+def test_root_accounts() -> None:
+    roots = RootAccounts()
+    assert roots == RootAccounts(assets="Assets", expenses="Expenses")
+    assert roots.is_balance_sheet_account("Assets:Cash")
+    assert roots.is_balance_sheet_account("Equity:Opening-Balances")
+    assert not roots.is_balance_sheet_account("Income:Salary")
+    assert roots.is_income_statement_account("Expenses:Food")
+    assert not roots.is_income_statement_account("Vermoegen:Bank")
+
+    german = RootAccounts(assets="Vermoegen", expenses="Ausgaben")
+    assert german.assets == "Vermoegen"
+    assert german.liabilities == "Liabilities"
+    assert german.is_balance_sheet_account("Vermoegen:Bank")
+    assert not german.is_balance_sheet_account("Assets:Bank")
+    assert german.is_income_statement_account("Ausgaben:Essen")
+    assert not german.is_income_statement_account("Expenses:Food")
+
+
 def test_amount() -> None:
     amt = Amount(Decimal("10.00"), "USD")
     amt2 = Amount(Decimal(10), "USD")
@@ -48,6 +110,72 @@ def test_amount() -> None:
     assert hash(amt) == hash(amt2)
 
 
+def test_amount_zero() -> None:
+    zero = Amount.zero("USD")
+    assert zero == Amount(Decimal(0), "USD")
+    assert zero.currency == "USD"
+    assert zero.is_zero()
+    assert Amount(Decimal("0.00"), "USD").is_zero()
+    assert Amount(Decimal("-0"), "USD").is_zero()
+    assert Amount(Decimal("-0"), "USD") == zero
+    assert hash(Amount(Decimal("-0"), "USD")) == hash(zero)
+    assert not Amount(Decimal("0.01"), "USD").is_zero()
+
+
+def test_amount_arithmetic() -> None:
+    amt = Amount(Decimal("10.00"), "USD")
+    other = Amount(Decimal("2.5"), "USD")
+    assert amt + other == Amount(Decimal("12.50"), "USD")
+    assert amt - other == Amount(Decimal("7.50"), "USD")
+    assert -amt == Amount(Decimal("-10.00"), "USD")
+    assert abs(-amt) == amt
+    assert str(amt * Decimal("1.50")) == "15.0000 USD"
+    assert str(amt * 3) == "30.00 USD"
+    assert str(3 * amt) == "30.00 USD"
+    with pytest.raises(ValueError, match=r"different currencies"):
+        _ = amt + Amount(Decimal(1), "EUR")
+    with pytest.raises(TypeError):
+        _ = amt * 1.5  # type: ignore[operator]
+
+
+def test_amount_from_string() -> None:
+    assert Amount.from_string("10.00 USD") == Amount(Decimal("10.00"), "USD")
+    assert Amount.from_string(" -1  EUR ") == Amount(Decimal(-1), "EUR")
+    for invalid in ["10", "USD", "", "10 USD EUR", "ten USD"]:
+        with pytest.raises(ValueError, match=r"Invalid amount"):
+            Amount.from_string(invalid)
+
+
+def test_amount_round() -> None:
+    def rounded(number: str, ndigits: int) -> Decimal:
+        return Amount(Decimal(number), "USD").round(ndigits).number
+
+    assert rounded("1.2345", 2) == Decimal("1.23")
+    assert str(rounded("1", 2)) == "1.00"
+    # midpoints are rounded to even
+    assert str(rounded("2.5", 0)) == "2"
+    assert str(rounded("3.5", 0)) == "4"
+    assert str(rounded("-2.5", 0)) == "-2"
+    assert str(rounded("1.125", 2)) == "1.12"
+    assert Amount(Decimal("2.5"), "EUR").round(0).currency == "EUR"
+
+
+def test_amount_round_for() -> None:
+    ledger = load_string(
+        """
+2000-01-01 price HOOL 10.125 USD
+2000-01-02 price HOOL 10.12 USD
+2000-01-03 price HOOL 10.13 USD
+"""
+    )
+    options = ledger.options
+    amount = Amount(Decimal("1.125"), "USD")
+    assert amount.round_for(options) == Amount(Decimal("1.12"), "USD")
+    # the number is kept as-is for unknown currencies
+    amount = Amount(Decimal("1.125"), "EUR")
+    assert amount.round_for(options) == amount
+
+
 def test_raw_amount() -> None:
     amt = RawAmount(Decimal("10.00"), "USD")
     assert str(amt) == "10.00 USD"
@@ -61,6 +189,8 @@ def test_raw_amount() -> None:
     amt = RawAmount(Decimal(10), None)
     assert str(amt) == "10"
     assert repr(amt) == "RawAmount(number=Decimal('10'), currency=None)"
+    assert RawAmount() == RawAmount(None, None)
+    assert RawAmount(currency="USD") == RawAmount(None, "USD")
 
 
 def test_amount_decimal_edge_cases() -> None:
@@ -88,6 +218,47 @@ def test_cost() -> None:
     )
 
 
+def test_cost_ordering() -> None:
+    later = Cost(Decimal(1), "USD", date(2000, 1, 2), None)
+    cheap = Cost(Decimal(5), "USD", date(2000, 1, 1), None)
+    label_b = Cost(Decimal(10), "USD", date(2000, 1, 1), "b")
+    label_a = Cost(Decimal(10), "USD", date(2000, 1, 1), "a")
+    no_label = Cost(Decimal(10), "USD", date(2000, 1, 1), None)
+    costs = [later, label_b, cheap, label_a, no_label]
+    assert sorted(costs) == [cheap, no_label, label_a, label_b, later]
+    assert cheap < later
+    assert cheap <= cheap
+    assert later > label_b
+    assert later >= later
+
+
+def test_convert_entries_shares_currency_strings() -> None:
+    ledger = load_string(
+        "2000-01-01 open Assets:Cash\n"
+        + "".join(
+            f"2000-01-02 * \"{i}\"\n"
+            "  Assets:Cash  1 USD @ 1.00 EUR\n"
+            "  Assets:Cash\n"
+            for i in range(1000)
+        )
+    )
+    assert not ledger.errors
+    converted = convert_entries(ledger.entries)
+    assert all(isinstance(e, data.ALL_DIRECTIVES) for e in converted)
+    currencies = [
+        amt.currency
+        for txn in converted
+        if isinstance(txn, data.Transaction)
+        for posting in txn.postings
+        for amt in (posting.units, posting.price)
+        if amt is not None
+    ]
+    usd = next(c for c in currencies if c == "USD")
+    eur = next(c for c in currencies if c == "EUR")
+    assert all(c is usd or c is eur for c in currencies)
+    assert converted == [e._convert() for e in ledger.entries]  # noqa: SLF001
+
+
 def test_cost_spec() -> None:
     cost = CostSpec(
         Decimal("10.00"), None, "USD", date(2000, 1, 1), None, merge=False
@@ -98,6 +269,11 @@ def test_cost_spec() -> None:
         " currency='USD', date=datetime.date(2000, 1, 1), label=None,"
         " merge=False)"
     )
+    total = CostSpec(number_total=Decimal(100), currency="USD", merge=True)
+    assert total.number_per is None
+    assert total.number_total == Decimal(100)
+    assert total.merge
+    assert CostSpec() == CostSpec(None, None, None, None, None, merge=False)
 
 
 def test_equals() -> None:
@@ -173,6 +349,19 @@ def test_balance() -> None:
     )
 
 
+def test_open_replace_booking() -> None:
+    header = EntryMeta({"filename": "<string>", "lineno": 0})
+    open_entry = Open(
+        header, date(2022, 12, 12), "Assets:Cash", ["USD"], Booking.FIFO
+    )
+    assert open_entry._replace(account="Assets:Other").booking == Booking.FIFO
+    assert open_entry._replace(booking=Booking.LIFO).booking == Booking.LIFO
+    # passing None explicitly unsets the booking method
+    assert open_entry._replace(booking=None).booking is None
+    with pytest.raises(TypeError):
+        open_entry._replace(booking="FIFO")  # type: ignore[arg-type]
+
+
 HEADER = EntryMeta({"filename": "<string>", "lineno": 0})
 DATE = date(2022, 12, 12)
 TAGS = {"a-tag"}
@@ -306,6 +495,8 @@ def test_entry_types(entry: Directive) -> None:
 
     json_loaded = loads(entry.to_json())
     assert json_loaded["t"] == entry.__class__.__name__
+    assert entry.kind == json_loaded["t"]
+    assert entry_from_json(entry.to_json()) == entry
     assert json_loaded["date"] == "2022-12-12"
     assert json_loaded["tags"] == ["a-tag"]
     assert json_loaded["links"] == ["a-link"]
@@ -386,3 +577,81 @@ def test_transaction() -> None:
     )
     t = transaction._replace(postings=postings)
     assert t.postings == postings
+
+
+def test_transaction_inferred_tolerances(load_doc: Ledger) -> None:
+    """
+    2000-01-01 open Assets:Cash
+
+    2000-01-02 *
+      Assets:Cash        20.00 USD
+      Assets:Cash          1.5 USD
+      Assets:Cash       -21.50 USD
+      Assets:Cash           10 EUR
+      Assets:Cash          -10 EUR
+    """
+    assert not load_doc.errors
+    txn = load_doc.entries[-1]
+    assert isinstance(txn, Transaction)
+    tolerances = txn.inferred_tolerances(load_doc.options)
+    # the largest tolerance for a currency wins
+    assert tolerances.tolerances == {"USD": Decimal("0.05")}
+    assert tolerances.get("USD") == Decimal("0.05")
+    assert tolerances.get("EUR") == Decimal(0)
+    assert tolerances.default == Decimal(0)
+    assert repr(tolerances) == (
+        "Tolerances(tolerances={USD: 0.05}, default=0)"
+    )
+
+
+def test_posting_interpolated(load_doc: Ledger) -> None:
+    """
+    2000-01-01 open Assets:Cash
+    2000-01-01 open Assets:Other
+
+    2000-01-02 *
+      Assets:Cash         20 USD
+      Assets:Other
+
+    2000-01-03 *
+      Assets:Cash         10 APL @ USD
+      Assets:Other       -50 USD
+    """
+    assert not load_doc.errors
+    auto_txn, price_txn = load_doc.entries[-2:]
+    assert isinstance(auto_txn, Transaction)
+    assert isinstance(price_txn, Transaction)
+
+    explicit, auto = auto_txn.postings
+    assert not explicit.interpolated
+    assert "__automatic__" not in explicit.meta
+    assert auto.interpolated
+    assert auto.meta["__automatic__"] is True
+    assert auto.units == Amount(Decimal(-20), "USD")
+
+    with_price, explicit = price_txn.postings
+    assert with_price.interpolated
+    assert not explicit.interpolated
+    assert not Posting("Assets:Cash", Amount(Decimal(1), "USD")).interpolated
+
+
+def test_entry_meta_span(load_doc: Ledger) -> None:
+    """
+    2000-01-01 open Assets:Cash
+
+    2000-01-02 * "payee" "narration"
+      Assets:Cash         20 USD
+      Assets:Cash        -20 USD
+    """
+    assert not load_doc.errors
+    open_entry, txn = load_doc.entries
+    span = txn.meta.span
+    assert span is not None
+    assert span.start_line == txn.meta.lineno
+    assert span.start_col == 0
+    assert span.end_line == span.start_line + 3
+    assert span.end_col == 0
+    assert open_entry.meta.span is not None
+    assert open_entry.meta.span.end_byte == span.start_byte - 1
+
+    assert EntryMeta({"filename": "<string>", "lineno": 0}).span is None
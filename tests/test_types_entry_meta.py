@@ -4,11 +4,13 @@ from collections.abc import ItemsView
 from collections.abc import KeysView
 from collections.abc import Mapping
 from collections.abc import ValuesView
+from decimal import Decimal
 from pathlib import Path
 
 import pytest
 
 from uromyces import EntryMeta
+from uromyces import load_string
 from uromyces import PostingMeta
 
 
@@ -117,3 +119,32 @@ def test_entry_meta_constructor() -> None:
     header = EntryMeta(
         {"filename": home, "lineno": 0, "__implicit_prices": "string"}
     )
+
+
+def test_entry_meta_integer_and_decimal_numbers() -> None:
+    ledger = load_string(
+        """
+2022-01-01 open Assets:Cash
+  integer: 5
+  negative: -5
+  fractional: 5.0
+"""
+    )
+    meta = ledger.entries[0].meta
+    assert meta["integer"] == 5
+    assert type(meta["integer"]) is int
+    assert meta["negative"] == -5
+    assert type(meta["negative"]) is int
+    assert meta["fractional"] == Decimal("5.0")
+    assert isinstance(meta["fractional"], Decimal)
+
+    # Beancount uses Decimals for all numbers, so converted entries do too
+    converted = ledger.entries[0]._convert()  # noqa: SLF001
+    assert converted.meta["integer"] == Decimal(5)
+    assert type(converted.meta["integer"]) is Decimal
+    assert type(converted.meta["negative"]) is Decimal
+    assert type(converted.meta["lineno"]) is int
+
+    # Python ints stay ints as well
+    entry_meta = EntryMeta({"filename": "<dummy>", "lineno": 0, "int": 1})
+    assert type(entry_meta["int"]) is int
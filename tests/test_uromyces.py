@@ -1,18 +1,35 @@
 from __future__ import annotations
 
+import datetime
+import re
+import time
 from collections.abc import Mapping
+from decimal import Decimal
 from pathlib import Path
 from typing import NamedTuple
+from typing import TYPE_CHECKING
 
 import pytest
 
+from uromyces import Amount
 from uromyces import Balance
+from uromyces import diff_entries
+from uromyces import EntryMeta
+from uromyces import filter_entries
 from uromyces import load_file
 from uromyces import load_string
+from uromyces import Open
+from uromyces import parse_only
+from uromyces import RawTransaction
+from uromyces import Transaction
 from uromyces._uromyces import Booking
 from uromyces._uromyces import Precisions
+from uromyces._uromyces import PrecisionStats
 from uromyces._uromyces import UromycesOptions
 
+if TYPE_CHECKING:
+    from uromyces import Ledger
+
 
 class _BeancountStyleError(NamedTuple):
     source: dict[str, str | int] | None
@@ -75,6 +92,14 @@ def test_load_ledger_options(test_ledgers_dir: Path) -> None:
     assert options.conversion_currency == "NOTHING"
     assert options.documents == []
     assert not options.insert_pythonpath
+    assert not options.warn_duplicate_includes
+    assert not options.warn_redundant_balances
+    assert not options.warn_unused_open_currencies
+    assert not options.strict_options
+    assert not options.allow_single_posting_transactions
+    assert options.account_rounding is None
+    assert not options.infer_tolerance_from_cost
+    assert options.balance_tolerance_min == 0
     assert options.booking_method == Booking.STRICT
 
     assert isinstance(options.display_precisions, Mapping)
@@ -83,6 +108,38 @@ def test_load_ledger_options(test_ledgers_dir: Path) -> None:
     assert usd.common == 2
     assert isinstance(usd, Precisions)
 
+    number = Decimal("1234.5678")
+    assert options.quantize_for_display(number, "USD") == Decimal("1234.57")
+    assert options.format_for_display(number, "USD") == "1234.57"
+    assert options.format_for_display(number, "UNKNOWN") == "1234.5678"
+
+
+def test_ledger_display_precisions_stats() -> None:
+    ledger = load_string(
+        """
+2020-01-01 open Assets:Cash
+2020-01-01 open Assets:Stock
+
+2020-01-02 *
+  Assets:Cash  -10.00 USD
+  Assets:Cash    1.5 USD
+  Assets:Cash    8.50 USD
+
+2020-01-03 price STOCK 12.1234 USD
+""",
+        "/string.beancount",
+    )
+    stats = ledger.display_precisions_stats()
+    assert list(stats) == ["USD"]
+    usd = stats["USD"]
+    assert isinstance(usd, PrecisionStats)
+    assert usd.has_sign
+    assert usd.common == 2
+    assert usd.max == 4
+    assert usd.counts == {1: 1, 2: 2, 4: 1}
+    precisions = ledger.options.display_precisions["USD"]
+    assert (precisions.common, precisions.max) == (usd.common, usd.max)
+
 
 def test_ledger_add_error(test_ledgers_dir: Path) -> None:
     ledger = load_file(test_ledgers_dir / "example.beancount")
@@ -100,3 +157,407 @@ def test_ledger_add_error(test_ledgers_dir: Path) -> None:
     home = str(Path.home())
     ledger.add_error(_BeancountStyleError({"filename": home}, "asdf", None))
     assert len(ledger.errors) == 2
+
+
+def test_ledger_errors_by_file(test_ledgers_dir: Path) -> None:
+    main_file = test_ledgers_dir / "errors-in-includes.beancount"
+    included = test_ledgers_dir / "errors-in-includes" / "included.beancount"
+    ledger = load_file(main_file)
+    ledger.add_error(_BeancountStyleError(None, "no source", None))
+
+    errors_by_file = ledger.errors_by_file()
+    assert set(errors_by_file) == {str(main_file), str(included), None}
+    assert [e.message for e in errors_by_file[str(main_file)]] == [
+        "Duplicate open directive for account Assets:Cash."
+    ]
+    assert [e.message for e in errors_by_file[str(included)]] == [
+        "Closing unopened account Assets:Unknown."
+    ]
+    assert [e.message for e in errors_by_file[None]] == ["no source"]
+
+
+def test_ledger_errors_sorted(test_ledgers_dir: Path) -> None:
+    main_file = str(test_ledgers_dir / "errors-in-includes.beancount")
+    included = str(
+        test_ledgers_dir / "errors-in-includes" / "included.beancount"
+    )
+    ledger = load_file(main_file)
+    ledger.add_error(_BeancountStyleError(None, "no source", None))
+    ledger.add_error(
+        _BeancountStyleError({"filename": included, "lineno": 10}, "i10", None)
+    )
+    ledger.add_error(
+        _BeancountStyleError({"filename": main_file, "lineno": 1}, "m1", None)
+    )
+
+    errors = ledger.errors_sorted()
+    assert [(e.filename, e.lineno) for e in errors] == [
+        (main_file, 1),
+        (main_file, 4),
+        (included, 3),
+        (included, 10),
+        (None, None),
+    ]
+    assert errors[-1].message == "no source"
+    # the errors of the ledger itself are unchanged
+    assert ledger.errors[-1].message == "m1"
+
+
+def test_syntax_error_span() -> None:
+    ledger = load_string("2000-01-01 *\n  Assets:Cash  1 USD USD\n")
+    assert len(ledger.errors) == 1
+    error = ledger.errors[0]
+    assert error.message.startswith("Invalid syntax")
+    assert error.span == (2, 21, 2, 24)
+
+    ledger.add_error(_BeancountStyleError(None, "asdf", None))
+    assert ledger.errors[1].span is None
+
+
+def test_ledger_timings(test_ledgers_dir: Path) -> None:
+    start = time.perf_counter()
+    ledger = load_file(test_ledgers_dir / "errors-in-includes.beancount")
+    elapsed = datetime.timedelta(seconds=time.perf_counter() - start)
+    timings = ledger.timings
+    steps = [timings.parse, timings.combine, timings.book, timings.plugins]
+    assert all(isinstance(step, datetime.timedelta) for step in steps)
+    assert all(step <= timings.total for step in steps)
+    assert timings.total <= elapsed
+    assert timings.validations == datetime.timedelta(0)
+    assert "LoadTimings(parse=" in repr(timings)
+
+    ledger.run_validations()
+    assert ledger.timings.validations <= ledger.timings.total
+    assert timings.total <= ledger.timings.total
+
+    # a single file without includes
+    single = load_string('2020-01-01 open Assets:Cash "STRICT"', "<string>")
+    assert single.timings.validations == datetime.timedelta(0)
+
+
+def test_ledger_repr(load_doc: Ledger) -> None:
+    """
+    option "title" "Test ledger"
+
+    2013-05-01 open Assets:Bank:Checking
+    2013-05-01 open Equity:Opening-Balances
+
+    2013-05-03 *
+      Assets:Bank:Checking                100.00 USD
+      Equity:Opening-Balances
+    """
+    assert repr(load_doc) == (
+        "<Ledger 'Test ledger': 3 entries, 0 errors, 2013-05-01 to 2013-05-03>"
+    )
+
+    empty = load_string("", "<string>")
+    assert repr(empty) == "<Ledger (untitled): 0 entries, 0 errors>"
+
+
+def test_ledger_assert_balances(load_doc: Ledger) -> None:
+    """
+    2013-05-01 open Assets:Bank:Checking
+    2013-05-01 open Equity:Opening-Balances
+
+    2013-05-03 *
+      Assets:Bank:Checking                100.00 USD
+      Equity:Opening-Balances
+    """
+    assert not load_doc.errors
+    may_4 = datetime.date(2013, 5, 4)
+    results = load_doc.assert_balances(
+        [
+            ("Assets:Bank:Checking", may_4, Amount(Decimal("100.00"), "USD")),
+            ("Assets:Bank", may_4, Amount(Decimal("100.00"), "USD")),
+            ("Assets:Bank:Checking", may_4, Amount(Decimal("10"), "EUR")),
+            ("Assets:Bank:Checking", may_4, Amount(Decimal("0"), "EUR")),
+        ]
+    )
+    assert results[0] is None
+    assert results[1] is None
+    assert results[2] is not None
+    assert "10 too little" in results[2].message
+    assert results[3] is None
+
+
+def test_ledger_currencies(load_doc: Ledger) -> None:
+    """
+    2013-04-30 commodity CHF
+    2013-05-01 open Assets:Bank:Checking  EUR,USD
+    2013-05-01 open Assets:Stocks
+    2013-05-01 open Equity:Opening-Balances
+
+    2013-05-03 *
+      Assets:Stocks                       10 HOOL {50.00 JPY}
+      Equity:Opening-Balances
+
+    2013-05-04 price HOOL  52.00 GBP
+
+    2013-05-05 balance Assets:Bank:Checking  0.00 EUR
+    """
+    assert not load_doc.errors
+    # JPY is only used as a cost currency
+    assert load_doc.currencies() == [
+        "CHF",
+        "EUR",
+        "USD",
+        "HOOL",
+        "JPY",
+        "GBP",
+    ]
+
+
+def test_ledger_all_tags_and_links(load_doc: Ledger) -> None:
+    """
+    2020-01-01 open Assets:Cash
+    2020-01-01 open Expenses:Food
+
+    2020-01-02 * "Lunch" #food ^receipt-1
+      Assets:Cash     -5 USD
+      Expenses:Food
+
+    2020-01-03 * "Dinner" #food #berlin ^receipt-2 ^receipt-1
+      Assets:Cash    -15 USD
+      Expenses:Food
+
+    pushtag #trip
+    2020-01-04 note Assets:Cash "Counted"
+    poptag #trip
+    """
+    assert not load_doc.errors
+    assert load_doc.all_tags() == ["berlin", "food", "trip"]
+    assert load_doc.all_links() == ["receipt-1", "receipt-2"]
+
+
+def test_ledger_accounts(load_doc: Ledger) -> None:
+    """
+    2013-05-01 open Assets:Bank:Checking  EUR,USD  "FIFO"
+      name: "Checking"
+    2013-05-01 open Assets:Cash
+    2014-01-01 close Assets:Bank:Checking
+    """
+    assert not load_doc.errors
+    checking, cash = load_doc.accounts()
+    assert checking.account == "Assets:Bank:Checking"
+    assert checking.open_date == datetime.date(2013, 5, 1)
+    assert checking.close_date == datetime.date(2014, 1, 1)
+    assert checking.currencies == ["EUR", "USD"]
+    assert checking.booking == Booking.FIFO
+    assert checking.meta["name"] == "Checking"
+    assert cash.account == "Assets:Cash"
+    assert cash.close_date is None
+    assert cash.currencies == []
+    assert cash.booking is None
+
+
+def test_ledger_filtered(load_doc: Ledger) -> None:
+    """
+    option "title" "Filtered"
+    2020-01-01 open Assets:Cash
+    2020-01-02 event "location" "Berlin"
+    2020-01-03 event "location" "Paris"
+    2020-01-04 event "location" "Rome"
+    """
+
+    def dates(ledger: Ledger) -> list[int]:
+        return [e.date.day for e in ledger.entries]
+
+    assert dates(load_doc.filtered()) == [1, 2, 3, 4]
+    filtered = load_doc.filtered(
+        datetime.date(2020, 1, 2), datetime.date(2020, 1, 4)
+    )
+    assert dates(filtered) == [2, 3]
+    assert filtered.options.title == "Filtered"
+    assert dates(load_doc.filtered(begin=datetime.date(2020, 1, 3))) == [3, 4]
+    assert dates(load_doc.filtered(end=datetime.date(2020, 1, 2))) == [1]
+    assert not load_doc.filtered(
+        datetime.date(2020, 1, 4), datetime.date(2020, 1, 2)
+    ).entries
+    # the ledger itself is unchanged
+    assert dates(load_doc) == [1, 2, 3, 4]
+
+
+def test_ledger_insert_entry(load_doc: Ledger) -> None:
+    """
+    2020-01-01 open Assets:Cash
+    2020-01-01 open Income:Work
+
+    2020-01-02 * "Salary"
+      Assets:Cash      100 USD
+      Income:Work
+
+    2020-01-03 * "Salary"
+      Assets:Cash      100 USD
+      Income:Work
+    """
+    meta = EntryMeta({"filename": "<string>", "lineno": 0})
+    entry = Open(meta, datetime.date(2020, 1, 2), "Expenses:Food", [], None)
+    load_doc.insert_entry(entry)
+
+    def entry_types(ledger: Ledger) -> list[tuple[int, str]]:
+        return [(e.date.day, type(e).__name__) for e in ledger.entries]
+
+    assert entry_types(load_doc) == [
+        (1, "Open"),
+        (1, "Open"),
+        (2, "Open"),
+        (2, "Transaction"),
+        (3, "Transaction"),
+    ]
+    assert load_doc.entries[2] == entry
+
+
+def test_ledger_commodity_meta(load_doc: Ledger) -> None:
+    """
+    2013-04-30 commodity CHF
+      name: "Swiss Franc"
+      precision: 2
+
+    2013-05-01 open Assets:Bank:Checking  CHF,EUR
+    """
+    assert not load_doc.errors
+    meta = load_doc.commodity_meta("CHF")
+    assert meta is not None
+    assert meta["name"] == "Swiss Franc"
+    assert meta["precision"] == 2
+    # EUR is used but has no commodity directive
+    assert load_doc.commodity_meta("EUR") is None
+
+
+def test_ledger_prices(load_doc: Ledger) -> None:
+    """
+    2020-01-01 price USD 1.25 EUR
+    2020-01-10 price USD 1.00 EUR
+    """
+    assert not load_doc.errors
+    jan_5 = datetime.date(2020, 1, 5)
+    assert load_doc.price_at("USD", "EUR", jan_5) == Decimal("1.25")
+    assert load_doc.price_at("EUR", "USD", jan_5) == Decimal("0.8")
+    assert load_doc.price_at("USD", "EUR", datetime.date(2019, 1, 1)) is None
+    assert load_doc.price_at("USD", "CHF", jan_5) is None
+
+    prices = load_doc.get_prices()
+    jan_10 = datetime.date(2020, 1, 10)
+    assert prices.price_at("USD", "EUR", jan_10) == Decimal("1.00")
+    assert prices.price_at("EUR", "USD", jan_10) == Decimal(1)
+
+
+def test_ledger_group_transactions_by(load_doc: Ledger) -> None:
+    """
+    2013-05-01 open Assets:Bank:Checking
+    2013-05-01 open Expenses:Food
+
+    2013-05-03 * "Shop" "Groceries"
+      Assets:Bank:Checking                -10.00 USD
+      Expenses:Food
+
+    2013-05-12 * "Restaurant" "Dinner"
+      Assets:Bank:Checking                -30.00 USD
+      Expenses:Food
+
+    2013-06-02 * "Shop" "Groceries"
+      Assets:Bank:Checking                -20.00 USD
+      Expenses:Food
+
+    2013-06-03 * "Without payee"
+      Assets:Bank:Checking                -20.00 USD
+      Expenses:Food
+    """
+    assert not load_doc.errors
+
+    by_month = load_doc.group_transactions_by(
+        lambda t: (t.date.year, t.date.month)
+    )
+    assert list(by_month.keys()) == [(2013, 5), (2013, 6)]
+    assert [t.payee for t in by_month[(2013, 5)]] == ["Shop", "Restaurant"]
+    assert len(by_month[(2013, 6)]) == 2
+
+    by_payee = load_doc.group_transactions_by(lambda t: t.payee)
+    assert list(by_payee.keys()) == ["Shop", "Restaurant", None]
+    assert len(by_payee["Shop"]) == 2
+    assert [t.narration for t in by_payee[None]] == ["Without payee"]
+
+    with pytest.raises(ZeroDivisionError):
+        load_doc.group_transactions_by(lambda _t: 1 / 0)
+
+
+def test_filter_entries(load_doc: Ledger) -> None:
+    """
+    2013-01-01 open Assets:Bank:Checking
+    2013-01-01 open Expenses:Food
+
+    2013-05-02 * "Shop" "Groceries"
+      Assets:Bank:Checking                -10.00 USD
+      Expenses:Food
+
+    2013-05-12 * "Restaurant" "Dinner"
+      Assets:Bank:Checking                -30.00 USD
+      Expenses:Food
+
+    2013-06-03 * "Without payee"
+      Assets:Bank:Checking                -20.00 USD
+      Expenses:Food
+    """
+    assert not load_doc.errors
+    entries = load_doc.entries
+
+    assert filter_entries(entries) == entries
+    assert filter_entries(entries, account_regex="^Expenses") == entries[1:]
+    assert filter_entries(entries, payee_regex="^Sh") == [entries[2]]
+    by_narration = filter_entries(entries, narration_regex="(?i)payee|dinner")
+    assert by_narration == [
+        e
+        for e in entries
+        if isinstance(e, Transaction)
+        and re.search("(?i)payee|dinner", e.narration)
+    ]
+    assert not filter_entries(
+        entries, narration_regex="Dinner", account_regex="Income"
+    )
+
+    with pytest.raises(ValueError, match=r"Invalid regex"):
+        filter_entries(entries, payee_regex="(")
+
+
+def test_diff_entries() -> None:
+    source = """
+2020-01-01 open Assets:Cash
+2020-01-01 open Expenses:Food
+
+2020-01-02 * "Bakery" "Bread"
+  Assets:Cash     -5 USD
+  Expenses:Food
+"""
+    entries = load_string(source).entries
+    moved = load_string("\n\n" + source).entries
+    changed = load_string(source.replace("Bread", "Cake")).entries
+
+    assert diff_entries(entries, entries) == ([], [])
+    assert diff_entries(entries, moved) == (entries, moved)
+    assert diff_entries(entries, moved, ignore_positions=True) == ([], [])
+    only_a, only_b = diff_entries(entries, changed, ignore_positions=True)
+    assert only_a == [entries[2]]
+    assert only_b == [changed[2]]
+
+
+def test_parse_only() -> None:
+    entries, errors = parse_only(
+        """
+2020-01-02 open Assets:Cash
+2020-01-01 * "Unbooked"
+  Assets:Cash     10 USD
+  Assets:Other
+2020-01-03 open Assets:Invalid USD asdf
+"""
+    )
+    assert len(errors) == 1
+    assert errors[0].message.startswith("Invalid syntax")
+    assert errors[0].filename == "<string>"
+    # the entries are in the order of the file and not booked
+    open_entry, transaction = entries
+    assert isinstance(open_entry, Open)
+    assert isinstance(transaction, RawTransaction)
+    assert transaction.postings[1].units.number is None
+
+    _, errors = parse_only("2020-01-01 open Assets:Cash", "/home/ledger")
+    assert not errors